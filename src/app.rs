@@ -1,22 +1,221 @@
-use std::path::PathBuf;
-use std::collections::HashSet;
-use tasd_lib::TASD;
-use tasd_lib::Packet;
-use color_eyre::Result;
+use std::path::{Path, PathBuf};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use tasd_lib::{Serializable, TASD};
+use tasd_lib::{Category, Comment, GameTitle, InputChunk, MovieLicense, Packet, SourceLink, TotalRerecords, Verified};
+use color_eyre::{eyre::eyre, Result};
 use ratatui::style::Color;
 use crossterm::event::{KeyEvent, KeyModifiers};
 
+use crate::browser::Browser;
+use crate::checkpoint::{self, Checkpoint};
+use crate::clipboard::{self, SystemClipboard};
+use crate::checksum::{self, Integrity};
+use crate::compare::FileDiff;
+use crate::compression::{self, Compression};
+use crate::config::{Column, Config};
+use crate::convert;
+use crate::expr::{Expr, Inputs};
+use crate::filter::Filter;
+use crate::finder::{Finder, TitleCache};
+use crate::frame_count;
+use crate::heatmap;
+use crate::input_index::InputIndex;
+use crate::latches;
+use crate::layout;
+use crate::marks;
+use crate::metadata::{self, MetadataPane};
+use crate::packet_list::{HexView, PacketList};
+use crate::notes;
+use crate::optimize;
+use crate::attachments;
+use crate::attributions::{self, AttributionEditor};
+use crate::catalog;
+use crate::controller::{self, Button, ControllerType};
+use crate::picker::{Picker, PickerTarget};
+use crate::plugins::Registry;
+use crate::repair::{Dropped, Salvage};
+use crate::report;
+use crate::segment;
+use crate::signature;
+use crate::stats;
+use crate::tasvideos;
+use crate::timing;
+use crate::tree;
+use crate::worker::{self, Done, Loaded, Worker};
+
 /// Current view/mode of the application
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AppMode {
     /// Normal navigation mode
     Normal,
-    /// Inputting mode (unused but kept for future extension)
+    /// Toggling the buttons of the current frame
     Input,
     /// Help screen mode
     Help,
     /// Command mode
     Command,
+    /// Choosing a value from a searchable list
+    Picker,
+    /// Editing the list of Attribution packets
+    Attributions,
+    /// Showing a read-only text popup
+    Popup,
+    /// Showing the file size breakdown chart
+    Sizes,
+    /// Browsing the TASD files of a directory
+    Browser,
+    /// Searching for a file to open
+    Finder,
+    /// Typing a `/` search for frames
+    Search,
+    /// Selecting and editing metadata in the sidebar
+    Metadata,
+    /// Listing every packet of the file
+    Packets,
+}
+
+/// Most undo steps kept, since a packet snapshot holds the whole file
+const MAX_UNDO: usize = 100;
+
+/// Shown when an edit is refused because the file is open read-only
+const READ_ONLY: &str = "Read-only: edits are blocked (:set readonly off allows them)";
+
+/// One reversible change to the file
+pub enum Change {
+    /// Bytes of a port's input stream replaced at a byte offset: frames modified, inserted or deleted
+    Inputs { port: u8, offset: usize, removed: Vec<u8>, inserted: Vec<u8> },
+    /// The whole file replaced, for metadata edits and commands (serialized like a checkpoint)
+    Packets(Vec<u8>),
+}
+
+/// An edit made with keys, kept so `.` can make it again at the cursor
+#[derive(Debug, Clone, Copy)]
+pub enum Operation {
+    /// Flip a button of a port on `count` frames from the cursor
+    Toggle { port: u8, button: Button, count: usize },
+    /// Delete `count` frames (`dd`)
+    Delete { count: usize },
+    /// Insert `count` neutral frames after or before the cursor (`o`/`O`)
+    Insert { after: bool, count: usize },
+    /// Paste the register, or the system clipboard, `count` times after or before the cursor (`p`/Ctrl+p)
+    Paste { after: bool, count: usize, clipboard: bool },
+}
+
+impl Operation {
+    /// The same operation with another count, as given before `.`
+    fn with_count(self, count: usize) -> Self {
+        match self {
+            Operation::Toggle { port, button, .. } => Operation::Toggle { port, button, count },
+            Operation::Delete { .. } => Operation::Delete { count },
+            Operation::Insert { after, .. } => Operation::Insert { after, count },
+            Operation::Paste { after, clipboard, .. } => Operation::Paste { after, count, clipboard },
+        }
+    }
+}
+
+/// A group of changes made by one action, and the generation of the file they lead back to
+pub struct Step {
+    changes: Vec<Change>,
+    generation: u64,
+}
+
+/// Undo and redo stacks; each step is a group of changes made by one action
+#[derive(Default)]
+pub struct EditHistory {
+    undo: Vec<Step>,
+    redo: Vec<Step>,
+    /// Identifies the current state of the file, so comparing it with the saved one tells
+    /// whether there are unsaved changes without looking at the data
+    generation: u64,
+    /// Last generation handed out; every new edit gets a fresh one
+    latest: u64,
+}
+
+impl EditHistory {
+    /// Record an action, given the changes that undo it
+    pub fn record(&mut self, changes: Vec<Change>) {
+        self.undo.push(Step { changes, generation: self.generation });
+        if self.undo.len() > MAX_UNDO {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+        self.latest += 1;
+        self.generation = self.latest;
+    }
+
+    /// Number of steps that can be undone
+    pub fn depth(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// Number of steps that can be redone
+    pub fn redo_depth(&self) -> usize {
+        self.redo.len()
+    }
+}
+
+/// Frames copied with `yy`, as input bytes per port
+pub struct FrameRegister {
+    /// Number of frames copied
+    pub frames: usize,
+    ports: Vec<(u8, Vec<u8>)>,
+}
+
+impl FrameRegister {
+    /// Copied bytes of a port
+    fn port(&self, port: u8) -> Option<&[u8]> {
+        self.ports.iter().find(|(p, _)| *p == port).map(|(_, bytes)| bytes.as_slice())
+    }
+}
+
+/// A file open in a tab other than the shown one, with everything that belongs to it
+struct Document {
+    tasd: TASD,
+    input_index: InputIndex,
+    cursor: InputCursor,
+    total_frames: usize,
+    frame_report: frame_count::Report,
+    filter: Option<Filter>,
+    highlight: Option<Filter>,
+    search: Option<Filter>,
+    input_window_start: usize,
+    ports: Vec<u8>,
+    controllers: HashMap<u8, ControllerType>,
+    edit_port: u8,
+    history: EditHistory,
+    modified: bool,
+    saved_generation: u64,
+    integrity: Integrity,
+    banner: Option<String>,
+    parse_errors: Vec<Dropped>,
+    checkpoints: Vec<Checkpoint>,
+    marks: BTreeMap<char, usize>,
+    hidden_ports: HashSet<u8>,
+    paddle_ports: HashSet<u8>,
+    port_order: Vec<u8>,
+    column_offset: usize,
+    split: Option<Pane>,
+}
+
+/// The other pane of a `:split`, with its own cursor and window over the same file
+pub struct Pane {
+    cursor: InputCursor,
+    input_window_start: usize,
+    /// Drawn below the active pane rather than above it
+    pub below: bool,
+}
+
+/// A scrollable read-only text popup
+pub struct Popup {
+    /// Popup title
+    pub title: String,
+    /// Lines of text
+    pub lines: Vec<String>,
+    /// First visible line
+    pub scroll: usize,
 }
 
 /// Input position information
@@ -36,18 +235,6 @@ impl InputCursor {
         }
     }
 
-    pub fn next(&mut self) {
-        if self.input_index < self.total_inputs.saturating_sub(1) {
-            self.input_index += 1;
-        }
-    }
-
-    pub fn prev(&mut self) {
-        if self.input_index > 0 {
-            self.input_index -= 1;
-        }
-    }
-
     pub fn jump_to(&mut self, index: usize) {
         if index < self.total_inputs {
             self.input_index = index;
@@ -72,24 +259,110 @@ impl InputCursor {
 pub struct App {
     /// Path to the TASD file
     pub file_path: PathBuf,
+    /// Every file given on the command line, for `:next`/`:prev`
+    pub arg_list: Vec<PathBuf>,
+    /// Position of the open file in `arg_list`
+    pub arg_index: usize,
+    /// Files of `arg_list` open in other tabs, kept with their edits and undo history
+    parked: HashMap<PathBuf, Document>,
     /// TASD data
     pub tasd: TASD,
+    /// Location of every port's input in `tasd`, rebuilt when the packets change
+    pub input_index: InputIndex,
     /// Current application mode
     pub mode: AppMode,
     /// Should the application exit
     pub exit: bool,
-    /// Position in the input list
+    /// Position in the input list (a row of the filtered view when a filter is active)
     pub cursor: InputCursor,
+    /// Number of frames in the movie
+    pub total_frames: usize,
+    /// How the input chunks line up with TotalFrames, refreshed along with `total_frames`
+    pub frame_report: frame_count::Report,
+    /// Active `:filter`, if any
+    pub filter: Option<Filter>,
+    /// Frames highlighted with `:highlight`, if any
+    pub highlight: Option<Filter>,
+    /// Frames found by the last `/` search, cycled with n/N
+    pub search: Option<Filter>,
+    /// Movie compared against with `:diff`, its differences visited with ]c/[c
+    pub diff: Option<FileDiff>,
     /// Visible inputs window (start index)
     pub input_window_start: usize,
+    /// The pane not being edited, while the input table is `:split`
+    pub split: Option<Pane>,
     /// Display settings
     pub display: DisplaySettings,
     /// Available ports (1-based port numbers)
     pub ports: Vec<u8>,
+    /// Controller type attached to each port
+    pub controllers: HashMap<u8, ControllerType>,
     /// Vim-style number prefix for commands
     pub number_buffer: Option<usize>,
+    /// First key of a `dd`/`yy` operator or a `]c`/`[c` motion, waiting for the second
+    pub pending_operator: Option<char>,
+    /// Frames copied with `yy`
+    pub register: Option<FrameRegister>,
+    /// The last edit, repeated by `.`
+    last_operation: Option<Operation>,
+    /// The next yank or paste uses the system clipboard (after `"+`)
+    clipboard_next: bool,
+    /// System clipboard, for `"+yy`/`"+p` and copying metadata
+    pub clipboard: SystemClipboard,
     /// Command buffer
     pub command_buffer: String,
+    /// Feedback from the last command, shown in the status bar
+    pub status_message: Option<String>,
+    /// Warning shown above the inputs for the whole session (e.g. a damaged file)
+    pub banner: Option<String>,
+    /// Byte ranges skipped while opening a damaged file, listed by `:errors`
+    pub parse_errors: Vec<Dropped>,
+    /// User configuration (`:set` changes apply to the running session)
+    pub config: Config,
+    /// Result of checking stored input checksums
+    pub integrity: Integrity,
+    /// Open value picker, if any
+    pub picker: Option<Picker>,
+    /// Attribution editor state
+    pub attribution_editor: AttributionEditor,
+    /// Metadata pane state
+    pub metadata_pane: MetadataPane,
+    /// Open text popup, if any
+    pub popup: Option<Popup>,
+    /// First visible line of the help
+    pub help_scroll: usize,
+    /// Help lines that fit on screen, updated when rendering
+    pub help_page: usize,
+    /// Packet count and bytes per packet type, while the size chart is open
+    pub size_chart: Option<Vec<(String, usize, usize)>>,
+    /// Named snapshots taken with `:checkpoint`
+    pub checkpoints: Vec<Checkpoint>,
+    /// Frames bookmarked with `m<letter>`, saved next to the file
+    pub marks: BTreeMap<char, usize>,
+    /// Directory browser, while open
+    pub browser: Option<Browser>,
+    /// Packet browser, while open
+    pub packet_list: Option<PacketList>,
+    /// Fuzzy file finder, while open
+    pub finder: Option<Finder>,
+    /// Game titles read by the file finder, kept for the whole session
+    pub title_cache: Arc<Mutex<TitleCache>>,
+    /// Renderers for packets without a built-in display
+    pub renderers: Registry,
+    /// Port whose buttons are toggled in input mode
+    pub edit_port: u8,
+    /// Undo/redo stacks for edits to the open file
+    pub history: EditHistory,
+    /// The packets differ from what was last loaded or saved
+    pub modified: bool,
+    /// History generation of the file as last loaded or saved
+    saved_generation: u64,
+    /// Inputs were edited since the last key was handled
+    inputs_edited: bool,
+    /// Heatmap densities as last computed, dropped whenever the frame count or input changes
+    heatmap: Option<heatmap::Cache>,
+    /// Saves and exports running in the background
+    pub worker: Worker,
 }
 
 /// UI display settings
@@ -100,6 +373,16 @@ pub struct DisplaySettings {
     pub highlight_color: Color,
     /// Maximum inputs to show at once - dynamically updated based on window size
     pub max_visible_inputs: usize,
+    /// Ports whose columns are hidden from the input table
+    pub hidden_ports: HashSet<u8>,
+    /// Ports read as an Arkanoid paddle with `:controller`, since files can't declare one
+    pub paddle_ports: HashSet<u8>,
+    /// Ports shown first in the input table, in this order
+    pub port_order: Vec<u8>,
+    /// Number of port columns scrolled out to the left
+    pub column_offset: usize,
+    /// Rows of the heatmap strip as last drawn
+    pub heatmap_rows: usize,
 }
 
 impl DisplaySettings {
@@ -108,37 +391,404 @@ impl DisplaySettings {
             show_debug: false,
             highlight_color: Color::Yellow,
             max_visible_inputs: 20, // Default value, will be updated based on window size
+            hidden_ports: HashSet::new(),
+            paddle_ports: HashSet::new(),
+            port_order: Vec::new(),
+            column_offset: 0,
+            heatmap_rows: 0,
         }
     }
 }
 
 impl App {
-    pub fn new(tasd: TASD, file_path: PathBuf) -> Self {
+    pub fn new(tasd: TASD, file_path: PathBuf, config: Config) -> Self {
         // Detect available ports
         let ports = App::detect_ports(&tasd);
 
+        // Resolve the controller plugged into each port
+        let controllers = ports.iter()
+            .map(|port| (*port, controller::detect_controller(&tasd.packets, *port)))
+            .collect();
+
         // Count total inputs
-        let total_inputs = App::count_inputs(&tasd);
+        let total_inputs = App::count_inputs(&tasd, &controllers);
+        let frame_report = frame_count::check(&tasd.packets, &controllers);
 
         let mut cursor = InputCursor::new();
         cursor.total_inputs = total_inputs;
 
+        // Verify any input checksums stored in the file
+        let integrity = checksum::verify(&tasd.packets);
+
+        let mut display = DisplaySettings::new();
+        if config.remember_order {
+            display.port_order = layout::load_port_order(&file_path).unwrap_or_default();
+        }
+        let renderers = Registry::new(&config);
+        let input_index = InputIndex::build(&tasd.packets);
+        let marks = marks::load(&file_path);
+
         Self {
+            arg_list: vec![file_path.clone()],
+            arg_index: 0,
+            parked: HashMap::new(),
             file_path,
             tasd,
+            input_index,
             mode: AppMode::Normal,
             exit: false,
             cursor,
+            total_frames: total_inputs,
+            frame_report,
+            filter: None,
+            highlight: None,
+            search: None,
+            diff: None,
             input_window_start: 0,
-            display: DisplaySettings::new(),
+            split: None,
+            display,
             ports,
+            controllers,
             number_buffer: None,
+            pending_operator: None,
+            register: None,
+            last_operation: None,
+            clipboard_next: false,
+            clipboard: SystemClipboard::default(),
             command_buffer: String::new(),
+            status_message: None,
+            banner: None,
+            parse_errors: Vec::new(),
+            config,
+            integrity,
+            picker: None,
+            attribution_editor: AttributionEditor::new(),
+            metadata_pane: MetadataPane::new(),
+            popup: None,
+            help_scroll: 0,
+            help_page: 0,
+            size_chart: None,
+            checkpoints: Vec::new(),
+            marks,
+            browser: None,
+            packet_list: None,
+            finder: None,
+            title_cache: Arc::default(),
+            renderers,
+            edit_port: 0,
+            history: EditHistory::default(),
+            modified: false,
+            saved_generation: 0,
+            inputs_edited: false,
+            heatmap: None,
+            worker: Worker::default(),
+        }
+    }
+
+    /// Switch to another file of the argument list, loading it unless it is already open in a tab.
+    /// Reopening the shown file with unsaved changes needs `force`, like vim's `:e!`.
+    fn open_arg(&mut self, index: usize, force: bool) -> Result<String> {
+        let path = self.arg_list.get(index)
+            .cloned()
+            .ok_or_else(|| eyre!("No file {} in the argument list", index + 1))?;
+        if index == self.arg_index && self.modified && !force {
+            return Err(eyre!("No write since last change (add ! to discard it)"));
+        }
+        let summary = format!("\"{}\" ({} of {})", path.display(), index + 1, self.arg_list.len());
+
+        // Opening the shown file again reloads it from disk
+        if index != self.arg_index {
+            if let Some(document) = self.parked.remove(&path) {
+                let current = self.park();
+                self.parked.insert(std::mem::replace(&mut self.file_path, path), current);
+                self.arg_index = index;
+                self.unpark(document);
+                return Ok(summary);
+            }
+        }
+        self.load_in_background(path, force)?;
+        Ok(format!("Opening {}", summary))
+    }
+
+    /// Read and index a file on the worker; it is shown by `show_loaded` once ready
+    fn load_in_background(&mut self, path: PathBuf, force: bool) -> Result<()> {
+        if self.worker.writing(&path) {
+            return Err(eyre!("{} is busy (still loading or saving)", path.display()));
+        }
+        let lenient = self.config.lenient;
+        let generation = self.history.generation;
+        self.worker.spawn(format!("Opening {}", path.display()), path.clone(), move || {
+            let salvage = if lenient {
+                crate::salvage_tasd(&path)?
+            } else {
+                let tasd = crate::load_tasd(&path)?;
+                Salvage { kept: tasd.packets.len(), tasd, dropped: Vec::new() }
+            };
+            let input_index = InputIndex::build(&salvage.tasd.packets);
+            Ok(Done::Loaded(Box::new(Loaded { path, force, generation, salvage, input_index })))
+        });
+        Ok(())
+    }
+
+    /// Show a file loaded by `load_in_background`, giving it a tab if it has none yet
+    fn show_loaded(&mut self, loaded: Loaded) -> Result<String> {
+        let Loaded { path, force, generation, salvage, input_index } = loaded;
+        let reloading = path == self.file_path;
+        if reloading && self.modified && !force {
+            return Err(eyre!("No write since last change (add ! to discard it)"));
+        }
+        if reloading && self.history.generation != generation && !force {
+            return Err(eyre!("{} was edited while it reloaded (:e! to reload anyway)", path.display()));
+        }
+        let index = match self.arg_list.iter().position(|arg| *arg == path) {
+            Some(index) => index,
+            None => {
+                self.arg_list.push(path.clone());
+                self.arg_list.len() - 1
+            }
+        };
+        if !reloading {
+            let current = self.park();
+            self.parked.insert(self.file_path.clone(), current);
+        }
+
+        self.file_path = path;
+        self.arg_index = index;
+        self.banner = salvage.banner();
+        self.parse_errors = salvage.dropped;
+        self.checkpoints.clear();
+        self.marks = marks::load(&self.file_path);
+        self.display.column_offset = 0;
+        self.display.hidden_ports.clear();
+        self.display.paddle_ports.clear();
+        self.display.port_order = if self.config.remember_order {
+            layout::load_port_order(&self.file_path).unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        self.cursor.input_index = 0;
+        self.input_window_start = 0;
+        self.modified = false;
+        self.history = EditHistory::default();
+        self.saved_generation = self.history.generation;
+        self.reload_indexed(salvage.tasd, input_index);
+        if let Some(diff) = &mut self.diff {
+            diff.update(&self.tasd.packets, self.config.align_diff);
         }
+
+        Ok(format!("\"{}\" ({} of {})", self.file_path.display(), index + 1, self.arg_list.len()))
+    }
+
+    /// Take the shown file's state out of the app, to keep in its tab
+    fn park(&mut self) -> Document {
+        Document {
+            tasd: std::mem::replace(&mut self.tasd, TASD::new()),
+            input_index: std::mem::take(&mut self.input_index),
+            cursor: std::mem::replace(&mut self.cursor, InputCursor::new()),
+            total_frames: self.total_frames,
+            frame_report: std::mem::take(&mut self.frame_report),
+            filter: self.filter.take(),
+            highlight: self.highlight.take(),
+            search: self.search.take(),
+            input_window_start: self.input_window_start,
+            ports: std::mem::take(&mut self.ports),
+            controllers: std::mem::take(&mut self.controllers),
+            edit_port: self.edit_port,
+            history: std::mem::take(&mut self.history),
+            modified: self.modified,
+            saved_generation: self.saved_generation,
+            integrity: std::mem::replace(&mut self.integrity, Integrity::Unchecked),
+            banner: self.banner.take(),
+            parse_errors: std::mem::take(&mut self.parse_errors),
+            checkpoints: std::mem::take(&mut self.checkpoints),
+            marks: std::mem::take(&mut self.marks),
+            hidden_ports: std::mem::take(&mut self.display.hidden_ports),
+            paddle_ports: std::mem::take(&mut self.display.paddle_ports),
+            port_order: std::mem::take(&mut self.display.port_order),
+            column_offset: self.display.column_offset,
+            split: self.split.take(),
+        }
+    }
+
+    /// Show a file parked in a tab
+    fn unpark(&mut self, document: Document) {
+        self.tasd = document.tasd;
+        self.input_index = document.input_index;
+        self.cursor = document.cursor;
+        self.total_frames = document.total_frames;
+        self.frame_report = document.frame_report;
+        self.filter = document.filter;
+        self.highlight = document.highlight;
+        self.search = document.search;
+        self.input_window_start = document.input_window_start;
+        self.ports = document.ports;
+        self.controllers = document.controllers;
+        self.edit_port = document.edit_port;
+        self.history = document.history;
+        self.modified = document.modified;
+        self.saved_generation = document.saved_generation;
+        self.integrity = document.integrity;
+        self.banner = document.banner;
+        self.parse_errors = document.parse_errors;
+        self.checkpoints = document.checkpoints;
+        self.marks = document.marks;
+        self.display.hidden_ports = document.hidden_ports;
+        self.display.paddle_ports = document.paddle_ports;
+        self.display.port_order = document.port_order;
+        self.display.column_offset = document.column_offset;
+        self.split = document.split;
+        self.heatmap = None;
+        if let Some(diff) = &mut self.diff {
+            diff.update(&self.tasd.packets, self.config.align_diff);
+        }
+        self.update_input_window();
+    }
+
+    /// Whether the file of a tab has unsaved changes
+    pub fn tab_modified(&self, index: usize) -> bool {
+        if index == self.arg_index {
+            return self.modified;
+        }
+        self.arg_list.get(index)
+            .and_then(|path| self.parked.get(path))
+            .is_some_and(|document| document.modified)
+    }
+
+    /// Split the input table into two panes, both starting at the current frame
+    fn split_panes(&mut self) {
+        self.split = Some(Pane {
+            cursor: self.cursor,
+            input_window_start: self.input_window_start,
+            below: true,
+        });
+    }
+
+    /// Swap the cursor and window with the other pane's, which may be out of range after edits
+    pub fn swap_panes(&mut self) {
+        if let Some(pane) = &mut self.split {
+            let total_inputs = self.cursor.total_inputs;
+            std::mem::swap(&mut self.cursor, &mut pane.cursor);
+            std::mem::swap(&mut self.input_window_start, &mut pane.input_window_start);
+            self.cursor.total_inputs = total_inputs;
+            self.cursor.jump_to(self.cursor.input_index);
+        }
+    }
+
+    /// Move to the other pane of a `:split`
+    fn focus_other_pane(&mut self) {
+        if self.split.is_none() {
+            self.status_message = Some("Only one pane (:split adds another)".to_string());
+            return;
+        }
+        self.swap_panes();
+        if let Some(pane) = &mut self.split {
+            pane.below = !pane.below;
+        }
+        self.update_input_window();
+    }
+
+    /// Close the active pane, leaving the other one
+    fn close_pane(&mut self) {
+        if self.split.is_none() {
+            self.status_message = Some("Cannot close the last pane".to_string());
+            return;
+        }
+        self.swap_panes();
+        self.split = None;
+        self.update_input_window();
+    }
+
+    /// Switch to the next or previous tab, wrapping around
+    fn next_tab(&mut self, forward: bool) {
+        let count = self.arg_list.len();
+        if count < 2 {
+            self.status_message = Some("Only one file is open".to_string());
+            return;
+        }
+        let index = if forward { (self.arg_index + 1) % count } else { (self.arg_index + count - 1) % count };
+        self.status_message = Some(match self.open_arg(index, false) {
+            Ok(summary) => summary,
+            Err(e) => format!("Open failed: {}", e),
+        });
+    }
+
+    /// Open a file, adding it to the argument list if it is not already there
+    fn open_path(&mut self, path: PathBuf, force: bool) -> Result<String> {
+        if let Some(index) = self.arg_list.iter().position(|arg| *arg == path) {
+            return self.open_arg(index, force);
+        }
+        // The file gets a tab once it has loaded, so one that fails doesn't get one
+        self.load_in_background(path.clone(), force)?;
+        Ok(format!("Opening {}", path.display()))
+    }
+
+    /// Controller of each port, as declared by the file unless chosen with `:controller`
+    fn resolve_controllers(&self, tasd: &TASD) -> HashMap<u8, ControllerType> {
+        self.ports.iter()
+            .map(|port| {
+                let controller = controller::detect_controller(&tasd.packets, *port);
+                if self.display.paddle_ports.contains(port) {
+                    (*port, ControllerType::NesArkanoid(controller.code()))
+                } else {
+                    (*port, controller)
+                }
+            })
+            .collect()
+    }
+
+    /// Read a port as a controller files can't declare, e.g. `:controller 2 arkanoid`;
+    /// `auto` goes back to the declared one
+    fn set_controller(&mut self, args: &str) -> Result<String> {
+        let (port, kind) = args.trim().split_once(' ').ok_or_else(|| eyre!("Usage: :controller PORT arkanoid|auto"))?;
+        let port: u8 = port.parse().map_err(|_| eyre!("Invalid port: {}", port))?;
+        if !self.ports.contains(&port) {
+            return Err(eyre!("No port {}", port));
+        }
+        match kind.trim() {
+            "arkanoid" | "paddle" => self.display.paddle_ports.insert(port),
+            "auto" => self.display.paddle_ports.remove(&port),
+            other => return Err(eyre!("Unknown controller: {} (arkanoid or auto)", other)),
+        };
+
+        self.controllers = self.resolve_controllers(&self.tasd);
+        self.recount();
+        let index = self.cursor.input_index;
+        self.cursor.jump_to(index);
+        self.update_input_window();
+        Ok(format!("Port {}: {}", port, self.controller(port).name()))
+    }
+
+    /// Replace the loaded TASD and recompute everything derived from it
+    fn reload(&mut self, tasd: TASD) {
+        let input_index = InputIndex::build(&tasd.packets);
+        self.reload_indexed(tasd, input_index);
+    }
+
+    /// Replace the loaded TASD with one whose input index is already built
+    fn reload_indexed(&mut self, tasd: TASD, input_index: InputIndex) {
+        self.ports = App::detect_ports(&tasd);
+        self.controllers = self.resolve_controllers(&tasd);
+        self.filter = None;
+        self.highlight = None;
+        self.integrity = checksum::verify(&tasd.packets);
+        self.input_index = input_index;
+        self.tasd = tasd;
+        self.recount();
+
+        let index = self.cursor.input_index;
+        self.cursor.jump_to(index);
+        self.update_input_window();
+    }
+
+    /// Hash of a serialized file, to notice unsaved changes
+    fn fingerprint(data: &[u8]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        data.hash(&mut hasher);
+        hasher.finish()
     }
 
     /// Detect all ports used in the TASD file
-    fn detect_ports(tasd: &TASD) -> Vec<u8> {
+    pub fn detect_ports(tasd: &TASD) -> Vec<u8> {
         let mut port_set = HashSet::new();
 
         for packet in &tasd.packets {
@@ -168,30 +818,16 @@ impl App {
     }
 
     /// Count total inputs in the TASD file - improved to be more accurate
-    fn count_inputs(tasd: &TASD) -> usize {
-        // First, check if there's a TotalFrames packet
-        for packet in &tasd.packets {
-            if let Packet::TotalFrames(tf) = packet {
-                return tf.frames as usize;
-            }
-        }
-
-        // If no TotalFrames packet, try to count frames from input chunks
-        let mut max_inputs = 0;
-
-        for port in 1..=4 { // Check common port numbers
-            let mut inputs_for_port = 0;
+    pub fn count_inputs(tasd: &TASD, controllers: &HashMap<u8, ControllerType>) -> usize {
+        // Count frames from input chunks
+        let max_inputs = frame_count::port_frames(&tasd.packets, controllers).iter()
+            .map(|port| port.frames)
+            .max()
+            .unwrap_or(0);
 
-            for packet in &tasd.packets {
-                if let Packet::InputChunk(chunk) = packet {
-                    if chunk.port == port {
-                        // For NES, usually each byte is one input frame
-                        inputs_for_port += chunk.inputs.len();
-                    }
-                }
-            }
-
-            max_inputs = max_inputs.max(inputs_for_port);
+        // Trust TotalFrames, but don't hide input stored past it
+        if let Some(declared) = frame_count::declared(&tasd.packets) {
+            return declared.max(max_inputs);
         }
 
         // If we have input chunks, return that count
@@ -205,6 +841,382 @@ impl App {
             .count()
     }
 
+    /// Ports shown as columns in the input table, in display order
+    pub fn visible_ports(&self) -> Vec<u8> {
+        self.table_columns().into_iter()
+            .filter_map(|column| match column {
+                Column::Port(port) => Some(port),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Columns of the input table, from `:set columns` or the default layout, with every port on its own
+    pub fn table_columns(&self) -> Vec<Column> {
+        let ordered = self.display.port_order.iter().filter(|port| self.ports.contains(port));
+        let rest = self.ports.iter().filter(|port| !self.display.port_order.contains(port));
+        let ports: Vec<u8> = ordered.chain(rest)
+            .copied()
+            .filter(|port| !self.display.hidden_ports.contains(port))
+            .collect();
+
+        let Some(layout) = &self.config.columns else {
+            let mut columns = vec![Column::Frame];
+            if self.config.events {
+                columns.push(Column::Events);
+            }
+            columns.extend(ports.into_iter().map(Column::Port));
+            return columns;
+        };
+
+        let listed: Vec<u8> = layout.iter()
+            .filter_map(|column| match column {
+                Column::Port(port) => Some(*port),
+                _ => None,
+            })
+            .collect();
+        let mut columns = Vec::new();
+        for column in layout {
+            match column {
+                Column::Ports => columns.extend(ports.iter().filter(|port| !listed.contains(port)).copied().map(Column::Port)),
+                Column::Port(port) if !ports.contains(port) || columns.contains(column) => {}
+                column if columns.contains(column) => {}
+                column => columns.push(*column),
+            }
+        }
+        // `:set events` still adds its column, after the frame number
+        if self.config.events && !columns.contains(&Column::Events) {
+            let at = columns.iter().position(|column| *column == Column::Frame).map_or(0, |i| i + 1);
+            columns.insert(at, Column::Events);
+        }
+        columns
+    }
+
+    /// Put the given ports first in the input table
+    fn set_port_order(&mut self, ports: &str) -> Result<()> {
+        let mut order = Vec::new();
+        for port in ports.split_whitespace() {
+            let port: u8 = port.parse().map_err(|_| eyre!("Invalid port: {}", port))?;
+            if !self.ports.contains(&port) {
+                return Err(eyre!("No port {}", port));
+            }
+            if !order.contains(&port) {
+                order.push(port);
+            }
+        }
+        self.display.port_order = order;
+
+        if self.config.remember_order {
+            layout::save_port_order(&self.file_path, &self.display.port_order)?;
+        }
+        Ok(())
+    }
+
+    /// Movie frame shown on a row of the input table
+    pub fn frame_at(&self, row: usize) -> usize {
+        match &self.filter {
+            Some(filter) => filter.frames.get(row).copied().unwrap_or(row),
+            None => row,
+        }
+    }
+
+    /// Movie frame under the cursor
+    pub fn current_frame(&self) -> usize {
+        self.frame_at(self.cursor.input_index)
+    }
+
+    /// Move the cursor to a movie frame, or the next visible one when filtered
+    pub fn jump_to_frame(&mut self, frame: usize) {
+        let row = match &self.filter {
+            Some(filter) => filter.frames.partition_point(|f| *f < frame),
+            None => frame,
+        };
+        self.cursor.jump_to(row);
+    }
+
+    /// Concatenated input bytes of a port
+    pub fn port_inputs(&self, port: u8) -> Vec<u8> {
+        let mut inputs = Vec::new();
+        for packet in &self.tasd.packets {
+            if let Packet::InputChunk(chunk) = packet {
+                if chunk.port == port {
+                    inputs.extend_from_slice(&chunk.inputs);
+                }
+            }
+        }
+        inputs
+    }
+
+    /// Bytes of a port's input stream, cut short where the input ends
+    pub fn read_inputs(&self, port: u8, offset: usize, len: usize) -> Vec<u8> {
+        self.input_index.read(&self.tasd.packets, port, offset, len)
+    }
+
+    /// One whole frame of a port's input, or None past the end of its input
+    pub fn frame_inputs(&self, port: u8, frame: usize) -> Option<Vec<u8>> {
+        self.input_index.frame(&self.tasd.packets, port, frame, self.controller(port).frame_size())
+    }
+
+    /// Frames matching a button expression
+    pub fn matching_frames(&self, text: &str) -> Result<Vec<usize>> {
+        let expr = Expr::parse(text)?;
+        let inputs = Inputs {
+            ports: self.ports.iter()
+                .map(|port| (*port, self.controller(*port), self.port_inputs(*port)))
+                .collect(),
+        };
+        expr.check(&inputs)?;
+        Ok(expr.matching_frames(&inputs, self.total_frames))
+    }
+
+    /// Port searched and edited: the edit port if it is shown, otherwise the first shown port
+    pub fn select_port(&mut self) -> Option<u8> {
+        let ports = self.visible_ports();
+        if !ports.contains(&self.edit_port) {
+            self.edit_port = *ports.first()?;
+        }
+        Some(self.edit_port)
+    }
+
+    /// Make a port the one edits and searches apply to, showing its column if it was hidden
+    fn set_active_port(&mut self, port: u8) -> Result<()> {
+        if !self.ports.contains(&port) {
+            return Err(eyre!("No port {}", port));
+        }
+        self.display.hidden_ports.remove(&port);
+        self.edit_port = port;
+        Ok(())
+    }
+
+    /// Frames of the selected port matching a search: a button expression, or raw bytes like `0xFC`
+    fn search_frames(&mut self, query: &str) -> Result<Vec<usize>> {
+        let port = self.select_port().ok_or_else(|| eyre!("No ports to search"))?;
+        let controller = self.controller(port);
+        let inputs = self.port_inputs(port);
+
+        if let Some(hex) = query.strip_prefix("0x").or_else(|| query.strip_prefix("0X")) {
+            let bytes = (0..hex.len()).step_by(2)
+                .map(|i| hex.get(i..i + 2).and_then(|pair| u8::from_str_radix(pair, 16).ok()))
+                .collect::<Option<Vec<u8>>>()
+                .filter(|bytes| !bytes.is_empty() && bytes.len() <= controller.frame_size())
+                .ok_or_else(|| eyre!("Expected up to {} hex bytes after 0x", controller.frame_size()))?;
+            // Shorter patterns match the first bytes of each frame
+            return Ok(inputs.chunks(controller.frame_size().max(1))
+                .enumerate()
+                .filter(|(_, frame)| frame.starts_with(&bytes))
+                .map(|(frame, _)| frame)
+                .collect());
+        }
+
+        let expr = Expr::parse(query)?;
+        let inputs = Inputs { ports: vec![(port, controller, inputs)] };
+        expr.check(&inputs)?;
+        Ok(expr.matching_frames(&inputs, self.total_frames))
+    }
+
+    /// Search the selected port and jump to the first match after the cursor
+    fn search(&mut self, query: &str) {
+        match self.search_frames(query) {
+            Ok(frames) => {
+                self.search = Some(Filter { expr: query.to_string(), frames });
+                self.next_match(true);
+            }
+            Err(e) => {
+                self.search = None;
+                self.status_message = Some(format!("Search failed: {}", e));
+            }
+        }
+    }
+
+    /// Jump to the next (or previous) search match, wrapping around the movie
+    fn next_match(&mut self, forward: bool) {
+        let Some(query) = self.search.as_ref().map(|search| search.expr.clone()) else {
+            self.status_message = Some("No previous search (/ searches frames)".to_string());
+            return;
+        };
+        // Search again, so matches follow edits made since
+        let frames = match self.search_frames(&query) {
+            Ok(frames) => frames,
+            Err(e) => {
+                self.status_message = Some(format!("Search failed: {}", e));
+                return;
+            }
+        };
+
+        let current = self.current_frame();
+        let target = if forward {
+            frames.iter().find(|frame| **frame > current).or(frames.first())
+        } else {
+            frames.iter().rev().find(|frame| **frame < current).or(frames.last())
+        };
+        match target.copied() {
+            Some(frame) => {
+                let wrapped = if forward { frame <= current } else { frame >= current };
+                self.jump_to_frame(frame);
+                self.update_input_window();
+                if wrapped {
+                    self.status_message = Some(format!(
+                        "Search hit {}, continuing at {}",
+                        if forward { "BOTTOM" } else { "TOP" },
+                        if forward { "TOP" } else { "BOTTOM" },
+                    ));
+                }
+            }
+            None => self.status_message = Some(format!("Pattern not found: {}", query)),
+        }
+        self.search = Some(Filter { expr: query, frames });
+    }
+
+    /// Jump to the next (or previous) frame that differs from the `:diff` movie
+    fn next_difference(&mut self, forward: bool) {
+        let count = self.take_number_buffer();
+        let Some(diff) = &self.diff else {
+            self.status_message = Some("No diff open (:diff FILE compares against another movie)".to_string());
+            return;
+        };
+
+        let current = self.current_frame();
+        let target = if forward {
+            diff.frames.iter().filter(|frame| **frame > current).nth(count.saturating_sub(1))
+        } else {
+            diff.frames.iter().rev().filter(|frame| **frame < current).nth(count.saturating_sub(1))
+        };
+        match target.copied() {
+            Some(frame) => {
+                self.jump_to_frame(frame);
+                self.update_input_window();
+            }
+            None if diff.frames.is_empty() => self.status_message = Some(format!("No differences from {}", diff.name)),
+            None => self.status_message = Some(format!("No more differences {}", if forward { "below" } else { "above" })),
+        }
+    }
+
+    /// Move to the `count`th frame where the active port's input changes, e.g. where a held input ends
+    fn next_input_change(&mut self, forward: bool) {
+        let count = self.take_number_buffer();
+        let Some(port) = self.select_port() else {
+            self.status_message = Some("No ports to search".to_string());
+            return;
+        };
+        let size = self.controller(port).frame_size().max(1);
+        let inputs = self.port_inputs(port);
+        let frames: Vec<&[u8]> = inputs.chunks_exact(size).collect();
+        self.move_to_change(forward, count, |frame| frames.get(frame).copied());
+    }
+
+    /// Move to the `count`th frame where a button of the active port is pressed or released
+    fn next_button_change(&mut self, forward: bool, glyph: &str) {
+        let count = self.take_number_buffer();
+        let Some(port) = self.select_port() else {
+            self.status_message = Some("No ports to search".to_string());
+            return;
+        };
+        let controller = self.controller(port);
+        let Some(button) = controller.buttons().iter().find(|button| button.glyph.eq_ignore_ascii_case(glyph)) else {
+            self.status_message = Some(format!("No button {} on port {}", glyph, port));
+            return;
+        };
+        let size = controller.frame_size().max(1);
+        let inputs = self.port_inputs(port);
+        let frames: Vec<bool> = inputs.chunks_exact(size).map(|frame| controller.is_pressed(button, frame)).collect();
+        self.move_to_change(forward, count, |frame| frames.get(frame).copied());
+    }
+
+    /// Step frame by frame from the current one until the value changes, `count` times
+    fn move_to_change<T: PartialEq>(&mut self, forward: bool, count: usize, value: impl Fn(usize) -> Option<T>) {
+        let mut frame = self.current_frame();
+        for _ in 0..count {
+            let current = value(frame);
+            let next = if forward {
+                (frame + 1..self.total_frames).find(|f| value(*f) != current)
+            } else {
+                (0..frame).rev().find(|f| value(*f) != current)
+            };
+            match next {
+                Some(next) => frame = next,
+                None => break,
+            }
+        }
+
+        if frame == self.current_frame() {
+            self.status_message = Some(format!("No change {}", if forward { "below" } else { "above" }));
+        } else {
+            self.jump_to_frame(frame);
+            self.update_input_window();
+        }
+    }
+
+    /// Button density of each row of a heatmap `rows` tall, recomputed only after an edit or when
+    /// the height or the shown ports change
+    pub fn heatmap_densities(&mut self, rows: usize) -> &[f32] {
+        let ports = self.visible_ports();
+        let stale = self.heatmap.as_ref().is_none_or(|cache| cache.rows != rows || cache.ports != ports);
+        if stale {
+            let inputs: Vec<(ControllerType, Vec<u8>)> = ports.iter()
+                .map(|port| (self.controller(*port), self.port_inputs(*port)))
+                .collect();
+            let densities = heatmap::densities(&inputs, self.total_frames, rows);
+            self.heatmap = Some(heatmap::Cache { rows, ports, densities });
+        }
+        self.heatmap.as_ref().map(|cache| cache.densities.as_slice()).unwrap_or_default()
+    }
+
+    /// Move to the start of the next/previous dense section of the heatmap
+    fn next_dense_section(&mut self, forward: bool) {
+        let rows = if self.display.heatmap_rows > 0 { self.display.heatmap_rows } else { self.display.max_visible_inputs };
+        let per_row = heatmap::frames_per_row(self.total_frames, rows);
+        let dense = heatmap::dense_rows(self.heatmap_densities(rows));
+        let starts = (0..dense.len())
+            .filter(|row| dense[*row] && (*row == 0 || !dense[row - 1]))
+            .map(|row| row * per_row);
+
+        let current = self.current_frame();
+        let target = if forward {
+            starts.into_iter().find(|frame| *frame > current)
+        } else {
+            starts.into_iter().rev().find(|frame| *frame < current)
+        };
+        match target {
+            Some(frame) => {
+                self.jump_to_frame(frame);
+                self.update_input_window();
+            }
+            None => self.status_message = Some(format!("No dense section {}", if forward { "below" } else { "above" })),
+        }
+    }
+
+    /// Compare against another movie, highlighting the frames that differ
+    fn open_diff(&mut self, path: &str) -> Result<usize> {
+        let mut diff = FileDiff::open(&PathBuf::from(path))?;
+        diff.update(&self.tasd.packets, self.config.align_diff);
+        let count = diff.frames.len();
+        self.diff = Some(diff);
+        Ok(count)
+    }
+
+    /// Show only the frames matching an expression, returning the match count
+    fn set_filter(&mut self, expr: &str) -> Result<usize> {
+        let frames = self.matching_frames(expr)?;
+
+        let current = self.current_frame();
+        let count = frames.len();
+        self.filter = Some(Filter { expr: expr.to_string(), frames });
+        self.cursor.total_inputs = count;
+        self.input_window_start = 0;
+        self.jump_to_frame(current);
+        self.update_input_window();
+        Ok(count)
+    }
+
+    /// Go back to showing every frame, keeping the cursor on the same frame
+    fn clear_filter(&mut self) {
+        let current = self.current_frame();
+        self.filter = None;
+        self.cursor.total_inputs = self.total_frames;
+        self.jump_to_frame(current);
+        self.update_input_window();
+    }
+
     /// Update visible window to ensure cursor is visible
     pub fn update_input_window(&mut self) {
         // If cursor is before visible window, adjust window start
@@ -269,14 +1281,83 @@ impl App {
         count
     }
 
+    /// Framerate used for time conversion, honoring any override
+    pub fn framerate(&self) -> f64 {
+        self.config.framerate
+            .unwrap_or_else(|| timing::default_framerate(&self.tasd.packets))
+    }
+
     /// Handle key events
     pub fn handle_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
-        match self.mode {
+        // Any keypress dismisses the previous command's feedback
+        self.status_message = None;
+
+        // Commands and metadata editors can change any packet, so keep a copy to undo them with;
+        // only the keys that apply a change pay for it, since the copy holds the whole file
+        let enter = key_event.code == crossterm::event::KeyCode::Enter;
+        let mut snapshot = match self.mode {
+            AppMode::Command if enter && App::command_changes_packets(self.command_buffer.trim()) => Some(self.tasd.serialize()),
+            AppMode::Metadata if enter && self.metadata_pane.editing.is_some() => Some(self.tasd.serialize()),
+            AppMode::Picker if enter => Some(self.tasd.serialize()),
+            _ => None,
+        };
+        let in_attributions = self.mode == AppMode::Attributions;
+        let file_path = self.file_path.clone();
+
+        let result = match self.mode {
             AppMode::Normal => self.handle_normal_key_event(key_event),
             AppMode::Input => self.handle_input_key_event(key_event),
             AppMode::Help => self.handle_help_key_event(key_event),
             AppMode::Command => self.handle_command_key_event(key_event),
+            AppMode::Picker => self.handle_picker_key_event(key_event),
+            AppMode::Attributions => self.handle_attributions_key_event(key_event),
+            AppMode::Popup => self.handle_popup_key_event(key_event),
+            AppMode::Sizes => self.handle_sizes_key_event(key_event),
+            AppMode::Browser => self.handle_browser_key_event(key_event),
+            AppMode::Finder => self.handle_finder_key_event(key_event),
+            AppMode::Search => self.handle_search_key_event(key_event),
+            AppMode::Metadata => self.handle_metadata_key_event(key_event),
+            AppMode::Packets => self.handle_packets_key_event(key_event),
+        };
+
+        // Everything done in the attribution editor is one undo step, taken when it closes
+        if in_attributions && self.mode != AppMode::Attributions {
+            snapshot = self.attribution_editor.before.take();
+        }
+
+
+        // Other keys only move around, so there is nothing to compare
+        if snapshot.is_none() && !std::mem::take(&mut self.inputs_edited) {
+            return result;
+        }
+
+        // Commands and editors don't record their own undo steps; compare the whole file to see if they changed it
+        if let Some(before) = snapshot {
+            let fingerprint = App::fingerprint(&self.tasd.serialize());
+
+            // A command that switched tabs left the other file with its own history
+            if App::fingerprint(&before) != fingerprint && self.file_path == file_path {
+                self.history.record(vec![Change::Packets(before)]);
+            }
+            // Commands may have added or removed packets anywhere
+            self.input_index = InputIndex::build(&self.tasd.packets);
         }
+        self.modified = self.history.generation != self.saved_generation;
+        if let Some(diff) = &mut self.diff {
+            diff.update(&self.tasd.packets, self.config.align_diff);
+        }
+        result
+    }
+
+    /// Whether a command can change packets, and so needs a copy of the file to undo it with
+    ///
+    /// Saving is left out on purpose: the checksums, optimization and signature it writes are
+    /// part of saving, not an edit to undo, and recording them would leave the file unsaved.
+    fn command_changes_packets(cmd: &str) -> bool {
+        const CHANGING: &[&str] = &[
+            "fetch-meta ", "attach ", "note ", "category ", "license ", "stamp", "addpacket ", "rmpacket ", "rollback ",
+        ];
+        matches!(cmd, "hash embed" | "optimize" | "fixframes") || CHANGING.iter().any(|prefix| cmd.starts_with(prefix))
     }
 
     fn handle_normal_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
@@ -291,8 +1372,82 @@ impl App {
             }
         }
 
+        // `"+` only applies to the yank or paste right after it
+        if !matches!(key_event.code, KeyCode::Char('y' | 'p')) && self.pending_operator != Some('"') {
+            self.clipboard_next = false;
+        }
+
+        // Second key of `dd`/`yy`, `]c`/`[c`, `gg`/`gt`/`gT` or `"+`; any other key cancels the operator
+        if let Some(operator) = self.pending_operator.take() {
+            match (operator, key_event.code) {
+                ('"', KeyCode::Char('+' | '*')) => {
+                    self.clipboard_next = true;
+                    return Ok(());
+                }
+                (']' | '[', KeyCode::Char('c')) => {
+                    self.next_difference(operator == ']');
+                    return Ok(());
+                }
+                (']' | '[', KeyCode::Char('h')) => {
+                    self.next_dense_section(operator == ']');
+                    return Ok(());
+                }
+                // `]b`/`[b` wait for a button, pending as `}`/`{`
+                (']' | '[', KeyCode::Char('b')) => {
+                    self.pending_operator = Some(if operator == ']' { '}' } else { '{' });
+                    return Ok(());
+                }
+                ('}' | '{', code) => {
+                    match App::glyph_of(code) {
+                        Some(glyph) => self.next_button_change(operator == '}', &glyph),
+                        None => self.number_buffer = None,
+                    }
+                    return Ok(());
+                }
+                ('g', KeyCode::Char('g')) => {
+                    self.cursor.jump_to(0);
+                    self.update_input_window();
+                    return Ok(());
+                }
+                ('m', KeyCode::Char(letter)) if letter.is_ascii_alphabetic() => {
+                    self.set_mark(letter);
+                    return Ok(());
+                }
+                ('\'', KeyCode::Char(letter)) => {
+                    self.jump_to_mark(letter);
+                    return Ok(());
+                }
+                // `Ctrl+w` is pending as `W`
+                ('W', KeyCode::Char('w' | 'W' | 'j' | 'k' | 'p') | KeyCode::Up | KeyCode::Down) => {
+                    self.focus_other_pane();
+                    return Ok(());
+                }
+                ('W', KeyCode::Char('s')) => {
+                    self.split_panes();
+                    return Ok(());
+                }
+                ('W', KeyCode::Char('q' | 'c')) => {
+                    self.close_pane();
+                    return Ok(());
+                }
+                ('W', KeyCode::Char('o')) => {
+                    self.split = None;
+                    return Ok(());
+                }
+                ('g', KeyCode::Char('t' | 'T')) => {
+                    self.next_tab(key_event.code == KeyCode::Char('t'));
+                    return Ok(());
+                }
+                (_, KeyCode::Char(c)) if c == operator => {
+                    self.run_operator(operator);
+                    return Ok(());
+                }
+                _ => self.number_buffer = None,
+            }
+        }
+
         match key_event.code {
-            KeyCode::Char('q') => self.exit(),
+            KeyCode::Char('q') => self.quit(false),
 
             // Basic navigation
             KeyCode::Char('j') | KeyCode::Down => {
@@ -313,6 +1468,8 @@ impl App {
                 self.cursor.move_by((half_page * count) as isize);
                 self.update_input_window();
             }
+            KeyCode::Char('t') if key_event.modifiers.contains(KeyModifiers::CONTROL) => self.open_finder(),
+            KeyCode::Char('w') if key_event.modifiers.contains(KeyModifiers::CONTROL) => self.pending_operator = Some('W'),
             KeyCode::Char('u') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
                 let count = self.take_number_buffer();
                 let half_page = self.display.max_visible_inputs / 2;
@@ -351,18 +1508,18 @@ impl App {
                 if self.number_buffer.is_some() {
                     // Go to specific line if number is specified
                     let line = self.take_number_buffer();
-                    self.cursor.jump_to(line.saturating_sub(1)); // Convert from 1-indexed to 0-indexed
+                    self.jump_to_frame(line.saturating_sub(1)); // Convert from 1-indexed to 0-indexed
+                    self.update_input_window();
                 } else {
-                    // Otherwise go to first line
-                    self.cursor.jump_to(0);
+                    // Otherwise wait for `gg` (first line) or `gt`/`gT` (tabs)
+                    self.pending_operator = Some('g');
                 }
-                self.update_input_window();
             }
             KeyCode::Char('G') => {
                 if self.number_buffer.is_some() {
                     // Go to specific line if number is specified
                     let line = self.take_number_buffer();
-                    self.cursor.jump_to(line.saturating_sub(1)); // Convert from 1-indexed to 0-indexed
+                    self.jump_to_frame(line.saturating_sub(1)); // Convert from 1-indexed to 0-indexed
                 } else {
                     // Otherwise go to last line
                     self.cursor.jump_to(self.cursor.total_inputs.saturating_sub(1));
@@ -386,20 +1543,125 @@ impl App {
                 self.center_cursor();
             }
 
-            // Command mode
-            KeyCode::Char(':') => {
-                self.command_buffer.clear();
-                self.mode = AppMode::Command;
+            // Edit the buttons of the current frame
+            KeyCode::Char('i') if self.config.readonly => self.status_message = Some(READ_ONLY.to_string()),
+            KeyCode::Char('i') => match self.select_port() {
+                Some(_) => self.mode = AppMode::Input,
+                None => self.status_message = Some("No ports to edit".to_string()),
+            },
+
+            // Frame editing
+            KeyCode::Char('d') | KeyCode::Char('y') if key_event.modifiers.is_empty() => {
+                if let KeyCode::Char(operator) = key_event.code {
+                    self.pending_operator = Some(operator);
+                }
+            }
+            KeyCode::Char('o') | KeyCode::Char('O') => {
+                let count = self.take_number_buffer();
+                self.perform(Operation::Insert { after: key_event.code == KeyCode::Char('o'), count });
             }
+            // Ctrl+p pastes before, since P opens the packet browser
+            KeyCode::Char('"') => self.pending_operator = Some('"'),
+
+            // Marks
+            KeyCode::Char('m') | KeyCode::Char('\'') => {
+                if let KeyCode::Char(key) = key_event.code {
+                    self.pending_operator = Some(key);
+                }
+            }
+            KeyCode::Char('p') => {
+                let count = self.take_number_buffer();
+                let after = !key_event.modifiers.contains(KeyModifiers::CONTROL);
+                let clipboard = std::mem::take(&mut self.clipboard_next);
+                self.perform(Operation::Paste { after, count, clipboard });
+            }
+
+            // Repeat the last edit, with a new count if one is given
+            KeyCode::Char('.') => match self.last_operation {
+                Some(operation) => {
+                    let operation = match self.number_buffer.take() {
+                        Some(count) => operation.with_count(count),
+                        None => operation,
+                    };
+                    self.perform(operation);
+                }
+                None => self.status_message = Some("No edit to repeat".to_string()),
+            },
+
+            // Undo/redo
+            KeyCode::Char('r') if key_event.modifiers.contains(KeyModifiers::CONTROL) => self.redo(),
+            KeyCode::Char('u') if !key_event.modifiers.contains(KeyModifiers::CONTROL) => self.undo(),
+
+            // Command mode
+            KeyCode::Char(':') => {
+                self.command_buffer.clear();
+                self.mode = AppMode::Command;
+            }
+
+            // Packet browser
+            KeyCode::Char('P') => {
+                self.packet_list = Some(PacketList::new(&self.tasd.packets));
+                self.mode = AppMode::Packets;
+            }
+
+            // Metadata pane
+            KeyCode::Tab => {
+                self.metadata_pane.editing = None;
+                self.mode = AppMode::Metadata;
+            }
+
+            // Search
+            KeyCode::Char('/') => {
+                self.command_buffer.clear();
+                self.mode = AppMode::Search;
+            }
+            KeyCode::Char('n') if key_event.modifiers.is_empty() => self.next_match(true),
+            KeyCode::Char('N') => self.next_match(false),
+            KeyCode::Char('}') | KeyCode::Char('{') => self.next_input_change(key_event.code == KeyCode::Char('}')),
+            KeyCode::Char(']') | KeyCode::Char('[') => {
+                if let KeyCode::Char(bracket) = key_event.code {
+                    self.pending_operator = Some(bracket);
+                }
+            }
+
+            KeyCode::Enter => self.show_note(),
 
             // Help & debug
             KeyCode::Char('?') => {
+                self.help_scroll = 0;
                 self.mode = AppMode::Help;
             }
             KeyCode::Char('D') => {
                 self.display.show_debug = !self.display.show_debug;
             }
 
+            // Port column visibility
+            KeyCode::Char('v') => {
+                match self.number_buffer.take().and_then(|port| u8::try_from(port).ok()) {
+                    Some(port) if self.ports.contains(&port) => {
+                        if !self.display.hidden_ports.remove(&port) {
+                            self.display.hidden_ports.insert(port);
+                        }
+                    }
+                    Some(port) => self.status_message = Some(format!("No port {}", port)),
+                    None => self.status_message = Some("Usage: <port>v".to_string()),
+                }
+            }
+            KeyCode::Char('V') => {
+                self.display.hidden_ports.clear();
+            }
+
+            // Horizontal scrolling of port columns
+            KeyCode::Char('l') | KeyCode::Right => {
+                let count = self.take_number_buffer();
+                let last = self.visible_ports().len().saturating_sub(1);
+                self.display.column_offset = (self.display.column_offset + count).min(last);
+            }
+            KeyCode::Char('h') | KeyCode::Left => {
+                let count = self.take_number_buffer();
+                self.display.column_offset = self.display.column_offset.saturating_sub(count);
+            }
+
             // Cancel number buffer
             KeyCode::Esc => {
                 self.number_buffer = None;
@@ -413,22 +1675,453 @@ impl App {
     fn handle_input_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
         use crossterm::event::KeyCode;
 
-        match key_event.code {
+        // Buttons are toggled by their glyph, with the arrow keys standing in for the D-pad
+        let glyph = match key_event.code {
             KeyCode::Esc => {
                 self.mode = AppMode::Normal;
+                return Ok(());
             }
-            _ => {}
+            KeyCode::Char('j') => {
+                self.cursor.move_by(1);
+                self.update_input_window();
+                return Ok(());
+            }
+            KeyCode::Char('k') => {
+                self.cursor.move_by(-1);
+                self.update_input_window();
+                return Ok(());
+            }
+            KeyCode::Tab => {
+                let ports = self.visible_ports();
+                let next = ports.iter().position(|port| *port == self.edit_port).map_or(0, |i| i + 1);
+                if let Some(port) = ports.get(next % ports.len().max(1)) {
+                    self.edit_port = *port;
+                }
+                return Ok(());
+            }
+            KeyCode::Char(digit @ '1'..='8') => {
+                if let Err(e) = self.set_active_port(digit as u8 - b'0') {
+                    self.status_message = Some(e.to_string());
+                }
+                return Ok(());
+            }
+            code => match App::glyph_of(code) {
+                Some(glyph) => glyph,
+                None => return Ok(()),
+            },
+        };
+
+        let controller = self.controller(self.edit_port);
+        let Some(button) = controller.buttons().iter().find(|button| button.glyph.eq_ignore_ascii_case(&glyph)) else {
+            return Ok(());
+        };
+        self.perform(Operation::Toggle { port: self.edit_port, button: *button, count: 1 });
+        Ok(())
+    }
+
+    /// Button glyph typed with a key, with the arrow keys standing in for the D-pad
+    fn glyph_of(code: crossterm::event::KeyCode) -> Option<String> {
+        use crossterm::event::KeyCode;
+
+        match code {
+            KeyCode::Up => Some("↑".to_string()),
+            KeyCode::Down => Some("↓".to_string()),
+            KeyCode::Left => Some("←".to_string()),
+            KeyCode::Right => Some("→".to_string()),
+            KeyCode::Char(c) => Some(c.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Flip one button of a port on `count` frames, stopping where the port's input ends
+    fn toggle_button(&mut self, port: u8, frame: usize, button: &Button, count: usize) -> Result<()> {
+        let size = self.controller(port).frame_size();
+        let mut changes = Vec::new();
+        for frame in frame..frame + count {
+            let offset = frame * size + button.byte;
+            let Some(byte) = self.read_inputs(port, offset, 1).first().copied() else {
+                break;
+            };
+            changes.push(Change::Inputs { port, offset, removed: vec![byte], inserted: vec![byte ^ button.mask] });
+        }
+        if changes.is_empty() {
+            return Err(eyre!("Port {} has no input on frame {}", port, frame));
         }
+        self.edit(changes)
+    }
+
+    /// Make an edit at the cursor, remembering it for `.`
+    fn perform(&mut self, operation: Operation) {
+        let frame = self.current_frame();
+        let result = match operation {
+            Operation::Toggle { port, button, count } => self.toggle_button(port, frame, &button, count).map(|()| None),
+            Operation::Delete { count } => self.delete_frames(frame, count).map(|deleted| {
+                self.update_input_window();
+                Some(format!("Deleted {} frame{}", deleted, if deleted == 1 { "" } else { "s" }))
+            }),
+            Operation::Insert { after, count } => {
+                let frame = frame + after as usize;
+                self.insert_frames(frame, count, None).map(|()| {
+                    self.jump_to_frame(frame);
+                    self.update_input_window();
+                    None
+                })
+            }
+            Operation::Paste { after, count, clipboard } => self.paste(frame + after as usize, count, clipboard).map(Some),
+        };
+        match result {
+            Ok(message) => {
+                self.last_operation = Some(operation);
+                self.status_message = message;
+            }
+            Err(e) => self.status_message = Some(e.to_string()),
+        }
+    }
+
+    /// Paste the register, or the system clipboard, `count` times before `frame`
+    fn paste(&mut self, frame: usize, count: usize, clipboard: bool) -> Result<String> {
+        let register = if clipboard {
+            self.clipboard_frames().map_err(|e| eyre!("Paste failed: {}", e))?
+        } else {
+            self.register.take().ok_or_else(|| eyre!("Nothing yanked (yy copies frames)"))?
+        };
+        let frames = register.frames * count;
+        let result = self.insert_frames(frame, frames, Some(&register));
+        if !clipboard {
+            self.register = Some(register);
+        }
+        result?;
+        self.jump_to_frame(frame);
+        self.update_input_window();
+        Ok(format!("Pasted {} frames", frames))
+    }
+
+    /// Fail while `readonly` is set, before anything is changed
+    fn check_writable(&self) -> Result<()> {
+        if self.config.readonly {
+            return Err(eyre!("{}", READ_ONLY));
+        }
+        Ok(())
+    }
+
+    /// Make an undoable edit
+    pub fn edit(&mut self, changes: Vec<Change>) -> Result<()> {
+        self.check_writable()?;
+        let undo = self.apply_changes(changes)?;
+        self.history.record(undo);
         Ok(())
     }
 
+    /// Apply a group of changes in order, returning the changes that revert them
+    fn apply_changes(&mut self, changes: Vec<Change>) -> Result<Vec<Change>> {
+        let frames_before = self.input_frames();
+        let mut inputs_changed = false;
+        let mut inverse = Vec::new();
+        for change in changes {
+            inverse.push(match change {
+                Change::Inputs { port, offset, removed, inserted } => {
+                    self.splice_inputs(port, offset, removed.len(), &inserted)?;
+                    inputs_changed = true;
+                    Change::Inputs { port, offset, removed: inserted, inserted: removed }
+                }
+                Change::Packets(data) => {
+                    let (_, tasd) = TASD::deserialize(&data).map_err(|e| eyre!("Failed to restore packets: {:?}", e))?;
+                    let before = self.tasd.serialize();
+                    self.reload(tasd);
+                    Change::Packets(before)
+                }
+            });
+        }
+        inverse.reverse();
+        self.inputs_edited = true;
+
+        // Keep a declared TotalFrames in step with inserted or deleted frames
+        let frames_after = self.input_frames();
+        if inputs_changed && frames_after != frames_before {
+            for packet in &mut self.tasd.packets {
+                if let Packet::TotalFrames(tf) = packet {
+                    tf.frames = (tf.frames as i64 + frames_after as i64 - frames_before as i64).max(0) as u32;
+                }
+            }
+        }
+
+        // Inserted or deleted frames change the movie length
+        self.recount();
+        let index = self.cursor.input_index;
+        self.cursor.jump_to(index);
+        Ok(inverse)
+    }
+
+    /// Rewrite TotalFrames to match the input chunks, describing the change
+    fn fix_frames(&mut self) -> String {
+        if let Err(e) = self.check_writable() {
+            return e.to_string();
+        }
+        if frame_count::declared(&self.tasd.packets) == Some(self.input_frames()) {
+            return format!("TotalFrames already matches the longest port ({})", self.input_frames());
+        }
+        let old = frame_count::fix(&mut self.tasd.packets, &self.controllers);
+
+        self.recount();
+        let index = self.cursor.input_index;
+        self.cursor.jump_to(index);
+        self.update_input_window();
+
+        let frames = frame_count::declared(&self.tasd.packets).unwrap_or(0);
+        match old {
+            Some(old) => format!("TotalFrames set to {} (was {})", frames, old),
+            None => format!("Added TotalFrames {}", frames),
+        }
+    }
+
+    /// Recount the frames of the movie and recheck them against TotalFrames
+    fn recount(&mut self) {
+        self.total_frames = App::count_inputs(&self.tasd, &self.controllers);
+        self.frame_report = frame_count::check(&self.tasd.packets, &self.controllers);
+        self.cursor.total_inputs = self.total_frames;
+        self.heatmap = None;
+    }
+
+    /// Frames of input in the longest port
+    fn input_frames(&self) -> usize {
+        self.ports.iter()
+            .map(|port| self.input_index.port_len(*port) / self.controller(*port).frame_size())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Delete frames from every port, returning how many the longest port lost
+    fn delete_frames(&mut self, frame: usize, count: usize) -> Result<usize> {
+        let mut changes = Vec::new();
+        let mut deleted = 0;
+        for port in self.ports.clone() {
+            let size = self.controller(port).frame_size();
+            let removed = self.read_inputs(port, frame * size, count * size);
+            if !removed.is_empty() {
+                deleted = deleted.max(removed.len() / size);
+                changes.push(Change::Inputs { port, offset: frame * size, removed, inserted: Vec::new() });
+            }
+        }
+        if changes.is_empty() {
+            return Err(eyre!("No input on frame {}", frame));
+        }
+        self.edit(changes)?;
+        Ok(deleted)
+    }
+
+    /// Insert frames into every port before `frame`, repeating a register's frames or left neutral
+    fn insert_frames(&mut self, frame: usize, count: usize, register: Option<&FrameRegister>) -> Result<()> {
+        let mut changes = Vec::new();
+        for port in self.ports.clone() {
+            let controller = self.controller(port);
+            let offset = frame * controller.frame_size();
+            // Ports that already ran out of input stay short
+            if offset > self.input_index.port_len(port) {
+                continue;
+            }
+            let size = count * controller.frame_size();
+            let inserted = match register.and_then(|register| register.port(port)) {
+                Some(bytes) if !bytes.is_empty() && size.is_multiple_of(bytes.len()) => bytes.repeat(size / bytes.len()),
+                _ => controller.neutral_frame().repeat(count),
+            };
+            changes.push(Change::Inputs { port, offset, removed: Vec::new(), inserted });
+        }
+        self.edit(changes)
+    }
+
+    /// Copy frames of every port into the register
+    fn yank_frames(&mut self, frame: usize, count: usize) -> usize {
+        let count = count.min(self.total_frames.saturating_sub(frame));
+        let ports = self.ports.iter()
+            .map(|port| {
+                let size = self.controller(*port).frame_size();
+                (*port, self.read_inputs(*port, frame * size, count * size))
+            })
+            .collect();
+        self.register = Some(FrameRegister { frames: count, ports });
+        count
+    }
+
+    /// Run a doubled operator key (`dd`, `yy`) on the frames from the cursor
+    fn run_operator(&mut self, operator: char) {
+        let count = self.take_number_buffer();
+        if operator == 'd' {
+            self.perform(Operation::Delete { count });
+            return;
+        }
+        let yanked = self.yank_frames(self.current_frame(), count);
+        let plural = if yanked == 1 { "" } else { "s" };
+        self.status_message = Some(if std::mem::take(&mut self.clipboard_next) {
+            match self.copy_register() {
+                Ok(()) => format!("Copied {} frame{} to the clipboard", yanked, plural),
+                Err(e) => e.to_string(),
+            }
+        } else {
+            format!("Yanked {} frame{}", yanked, plural)
+        });
+    }
+
+    /// Bookmark the current frame under a letter
+    fn set_mark(&mut self, letter: char) {
+        let frame = self.current_frame();
+        self.marks.insert(letter, frame);
+        self.status_message = Some(match marks::save(&self.file_path, &self.marks) {
+            Ok(()) => format!("Mark {} at frame {}", letter, frame),
+            Err(e) => format!("Mark {} set, but not saved: {}", letter, e),
+        });
+    }
+
+    /// Jump to the frame bookmarked under a letter
+    fn jump_to_mark(&mut self, letter: char) {
+        match self.marks.get(&letter) {
+            Some(frame) => {
+                self.jump_to_frame(*frame);
+                self.update_input_window();
+            }
+            None => self.status_message = Some(format!("Mark not set: {}", letter)),
+        }
+    }
+
+    /// Set, replace or remove (without text) a note, e.g. `:note 1234 "desync starts here"`; the frame defaults to the current one
+    fn set_note(&mut self, args: &str) -> String {
+        if let Err(e) = self.check_writable() {
+            return e.to_string();
+        }
+        let args = args.trim();
+        let (frame, text) = match args.split_once(' ').unwrap_or((args, "")) {
+            (frame, text) if frame.parse::<usize>().is_ok() => (frame.parse().unwrap_or_default(), text.trim()),
+            _ => (self.current_frame(), args),
+        };
+        let text = text.strip_prefix('"').and_then(|text| text.strip_suffix('"')).unwrap_or(text).trim();
+        if text.is_empty() {
+            notes::set_note(&mut self.tasd.packets, frame, None);
+            format!("Removed the note on frame {}", frame)
+        } else {
+            notes::set_note(&mut self.tasd.packets, frame, Some(text));
+            format!("Noted frame {}", frame)
+        }
+    }
+
+    /// Show the note on the current frame in a popup
+    fn show_note(&mut self) {
+        let frame = self.current_frame();
+        match notes::notes(&self.tasd.packets).remove(&frame) {
+            Some(text) => self.show_popup(&format!("Note on frame {}", frame), vec![text]),
+            None => self.status_message = Some(format!("No note on frame {} (:note TEXT adds one)", frame)),
+        }
+    }
+
+    /// Put the yanked frames on the system clipboard as text
+    fn copy_register(&mut self) -> Result<()> {
+        let register = self.register.as_ref().ok_or_else(|| eyre!("Nothing yanked"))?;
+        let ports: Vec<(u8, ControllerType, &[u8])> = register.ports.iter()
+            .map(|(port, bytes)| (*port, self.controller(*port), bytes.as_slice()))
+            .collect();
+        let text = clipboard::format_frames(register.frames, &ports);
+        self.clipboard.copy(text)
+    }
+
+    /// Frames read from the text on the system clipboard
+    fn clipboard_frames(&mut self) -> Result<FrameRegister> {
+        let text = self.clipboard.paste()?;
+        let ports: Vec<(u8, ControllerType)> = self.ports.iter().map(|port| (*port, self.controller(*port))).collect();
+        let (frames, ports) = clipboard::parse_frames(&text, &ports)?;
+        Ok(FrameRegister { frames, ports })
+    }
+
+    /// Replace `len` bytes of a port's input stream at `offset` with `bytes`, across chunk boundaries
+    fn splice_inputs(&mut self, port: u8, offset: usize, len: usize, bytes: &[u8]) -> Result<()> {
+        let mut chunks: Vec<&mut Vec<u8>> = self.tasd.packets.iter_mut()
+            .filter_map(|packet| match packet {
+                Packet::InputChunk(chunk) if chunk.port == port => Some(&mut chunk.inputs),
+                _ => None,
+            })
+            .collect();
+        let total: usize = chunks.iter().map(|inputs| inputs.len()).sum();
+        if offset + len > total {
+            return Err(eyre!("Port {} has no input at byte {}", port, offset + len));
+        }
+        if chunks.is_empty() {
+            self.tasd.packets.push(Packet::InputChunk(InputChunk { port, inputs: bytes.to_vec() }));
+            self.input_index = InputIndex::build(&self.tasd.packets);
+            return Ok(());
+        }
+
+        // Find the chunk holding the offset, or the end of the last chunk when appending
+        let mut start = offset;
+        let mut first = 0;
+        while first + 1 < chunks.len() && start >= chunks[first].len() {
+            start -= chunks[first].len();
+            first += 1;
+        }
+
+        let mut remaining = len;
+        let (mut index, mut local) = (first, start);
+        while remaining > 0 {
+            let take = remaining.min(chunks[index].len() - local);
+            chunks[index].drain(local..local + take);
+            remaining -= take;
+            index += 1;
+            local = 0;
+        }
+        chunks[first].splice(start..start, bytes.iter().copied());
+        self.input_index = InputIndex::build(&self.tasd.packets);
+        Ok(())
+    }
+
+    /// Undo the last edit
+    fn undo(&mut self) {
+        if self.config.readonly {
+            self.status_message = Some(READ_ONLY.to_string());
+            return;
+        }
+        self.status_message = Some(match self.history.undo.pop() {
+            Some(step) => match self.apply_changes(step.changes) {
+                Ok(redo) => {
+                    self.history.redo.push(Step { changes: redo, generation: self.history.generation });
+                    self.history.generation = step.generation;
+                    format!("Undone ({} more)", self.history.depth())
+                }
+                Err(e) => format!("Undo failed: {}", e),
+            },
+            None => "Already at oldest change".to_string(),
+        });
+    }
+
+    /// Redo the last undone edit
+    fn redo(&mut self) {
+        if self.config.readonly {
+            self.status_message = Some(READ_ONLY.to_string());
+            return;
+        }
+        self.status_message = Some(match self.history.redo.pop() {
+            Some(step) => match self.apply_changes(step.changes) {
+                Ok(undo) => {
+                    self.history.undo.push(Step { changes: undo, generation: self.history.generation });
+                    self.history.generation = step.generation;
+                    format!("Redone ({} more)", self.history.redo_depth())
+                }
+                Err(e) => format!("Redo failed: {}", e),
+            },
+            None => "Already at newest change".to_string(),
+        });
+    }
+
     fn handle_help_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
         use crossterm::event::KeyCode;
 
+        // Scrolling past the end is clamped when the help is drawn
+        let page = self.help_page.max(1);
         match key_event.code {
             KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('?') => {
                 self.mode = AppMode::Normal;
             }
+            KeyCode::Char('j') | KeyCode::Down => self.help_scroll += 1,
+            KeyCode::Char('k') | KeyCode::Up => self.help_scroll = self.help_scroll.saturating_sub(1),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.help_scroll += page,
+            KeyCode::PageUp => self.help_scroll = self.help_scroll.saturating_sub(page),
+            KeyCode::Char('g') | KeyCode::Home => self.help_scroll = 0,
+            KeyCode::Char('G') | KeyCode::End => self.help_scroll = usize::MAX,
             _ => {}
         }
         Ok(())
@@ -439,9 +2132,36 @@ impl App {
 
         match key_event.code {
             KeyCode::Enter => {
+                // Leave command mode first so commands can open other modes
+                self.mode = AppMode::Normal;
                 self.execute_command();
+            }
+            KeyCode::Esc => {
+                self.command_buffer.clear();
                 self.mode = AppMode::Normal;
             }
+            KeyCode::Backspace => {
+                self.command_buffer.pop();
+            }
+            KeyCode::Char(c) => {
+                self.command_buffer.push(c);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_search_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
+        use crossterm::event::KeyCode;
+
+        match key_event.code {
+            KeyCode::Enter => {
+                self.mode = AppMode::Normal;
+                let query = std::mem::take(&mut self.command_buffer);
+                if !query.trim().is_empty() {
+                    self.search(query.trim());
+                }
+            }
             KeyCode::Esc => {
                 self.command_buffer.clear();
                 self.mode = AppMode::Normal;
@@ -457,22 +2177,1114 @@ impl App {
         Ok(())
     }
 
+    fn handle_picker_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
+        use crossterm::event::KeyCode;
+
+        let Some(picker) = self.picker.as_mut() else {
+            self.mode = AppMode::Normal;
+            return Ok(());
+        };
+
+        match key_event.code {
+            KeyCode::Enter => {
+                let target = picker.target;
+                let selection = picker.selection();
+                self.picker = None;
+                self.mode = AppMode::Normal;
+                if let Some(value) = selection {
+                    self.apply_picker(target, value);
+                }
+            }
+            KeyCode::Esc => {
+                self.picker = None;
+                self.mode = AppMode::Normal;
+            }
+            KeyCode::Down => picker.next(),
+            KeyCode::Up => picker.prev(),
+            KeyCode::Char('n') if key_event.modifiers.contains(KeyModifiers::CONTROL) => picker.next(),
+            KeyCode::Char('p') if key_event.modifiers.contains(KeyModifiers::CONTROL) => picker.prev(),
+            KeyCode::Tab => picker.complete(),
+            KeyCode::Backspace => picker.pop(),
+            KeyCode::Char(c) => picker.push(c),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_attributions_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
+        use crossterm::event::KeyCode;
+
+        let count = attributions::indices(&self.tasd.packets).len();
+        let editor = &mut self.attribution_editor;
+
+        // Typing a name
+        if let Some(name) = editor.editing.as_mut() {
+            match key_event.code {
+                KeyCode::Enter => {
+                    let name = name.trim().to_string();
+                    editor.editing = None;
+                    let added = std::mem::take(&mut editor.adding);
+                    if name.is_empty() && added {
+                        attributions::remove(&mut self.tasd.packets, editor.selected);
+                        editor.selected = editor.selected.saturating_sub(1);
+                    } else {
+                        attributions::rename(&mut self.tasd.packets, editor.selected, name);
+                    }
+                }
+                KeyCode::Esc => {
+                    editor.editing = None;
+                    if std::mem::take(&mut editor.adding) {
+                        attributions::remove(&mut self.tasd.packets, editor.selected);
+                        editor.selected = editor.selected.saturating_sub(1);
+                    }
+                }
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                KeyCode::Char(c) => name.push(c),
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = AppMode::Normal,
+            KeyCode::Char('j') | KeyCode::Down => {
+                editor.selected = (editor.selected + 1).min(count.saturating_sub(1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                editor.selected = editor.selected.saturating_sub(1);
+            }
+            KeyCode::Char('a') | KeyCode::Char('o') => {
+                editor.selected = attributions::add(&mut self.tasd.packets, 1, String::new());
+                editor.editing = Some(String::new());
+                editor.adding = true;
+            }
+            KeyCode::Char('e') | KeyCode::Enter if count > 0 => {
+                let entries = attributions::entries(&self.tasd.packets);
+                editor.editing = entries.get(editor.selected).map(|(_, name)| name.clone());
+            }
+            KeyCode::Char('t') => attributions::cycle_type(&mut self.tasd.packets, editor.selected),
+            KeyCode::Char('d') | KeyCode::Char('x') => {
+                attributions::remove(&mut self.tasd.packets, editor.selected);
+                editor.selected = editor.selected.min(count.saturating_sub(2));
+            }
+            KeyCode::Char('J') => {
+                editor.selected = attributions::move_entry(&mut self.tasd.packets, editor.selected, true);
+            }
+            KeyCode::Char('K') => {
+                editor.selected = attributions::move_entry(&mut self.tasd.packets, editor.selected, false);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_metadata_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
+        use crossterm::event::KeyCode;
+
+        let fields = metadata::fields(&self.tasd.packets);
+        let pane = &mut self.metadata_pane;
+        pane.selected = pane.selected.min(fields.len().saturating_sub(1));
+
+        // Typing a value
+        if let Some(value) = pane.editing.as_mut() {
+            match key_event.code {
+                KeyCode::Enter => {
+                    let value = value.trim().to_string();
+                    pane.editing = None;
+                    if let Some(field) = fields.get(pane.selected) {
+                        metadata::set(&mut self.tasd.packets, field, value);
+                    }
+                }
+                KeyCode::Esc => pane.editing = None,
+                KeyCode::Backspace => {
+                    value.pop();
+                }
+                KeyCode::Char(c) => value.push(c),
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Tab | KeyCode::Char('q') => self.mode = AppMode::Normal,
+            KeyCode::Char('j') | KeyCode::Down => {
+                pane.selected = (pane.selected + 1).min(fields.len().saturating_sub(1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                pane.selected = pane.selected.saturating_sub(1);
+            }
+            KeyCode::Enter | KeyCode::Char('e') if self.config.readonly => {
+                self.status_message = Some(READ_ONLY.to_string());
+            }
+            KeyCode::Enter | KeyCode::Char('e') => {
+                pane.editing = fields.get(pane.selected).map(|field| field.value.clone());
+            }
+            KeyCode::Char('y') => {
+                if let Some(field) = fields.get(pane.selected) {
+                    self.status_message = Some(match self.clipboard.copy(field.value.clone()) {
+                        Ok(()) => format!("Copied {}", field.label),
+                        Err(e) => e.to_string(),
+                    });
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_popup_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
+        use crossterm::event::KeyCode;
+
+        let Some(popup) = self.popup.as_mut() else {
+            self.mode = AppMode::Normal;
+            return Ok(());
+        };
+
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter => {
+                self.popup = None;
+                self.mode = AppMode::Normal;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                popup.scroll = (popup.scroll + 1).min(popup.lines.len().saturating_sub(1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                popup.scroll = popup.scroll.saturating_sub(1);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_sizes_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
+        use crossterm::event::KeyCode;
+
+        if let KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter = key_event.code {
+            self.size_chart = None;
+            self.mode = AppMode::Normal;
+        }
+        Ok(())
+    }
+
+    fn handle_packets_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
+        use crossterm::event::KeyCode;
+
+        let Some(list) = self.packet_list.as_mut() else {
+            self.mode = AppMode::Normal;
+            return Ok(());
+        };
+        let control = key_event.modifiers.contains(KeyModifiers::CONTROL);
+
+        // The hex dump scrolls through one packet's bytes
+        if let Some(hex) = list.hex.as_mut() {
+            let page = hex.page.max(1) as isize;
+            match key_event.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter => list.hex = None,
+                KeyCode::Char('j') | KeyCode::Down => hex.scroll_by(1),
+                KeyCode::Char('k') | KeyCode::Up => hex.scroll_by(-1),
+                KeyCode::Char('g') | KeyCode::Home => hex.scroll = 0,
+                KeyCode::Char('G') | KeyCode::End => hex.scroll_by(isize::MAX),
+                KeyCode::Char('d') if control => hex.scroll_by(page / 2),
+                KeyCode::Char('u') if control => hex.scroll_by(-page / 2),
+                KeyCode::Char('f') if control => hex.scroll_by(page),
+                KeyCode::Char('b') if control => hex.scroll_by(-page),
+                KeyCode::PageDown => hex.scroll_by(page),
+                KeyCode::PageUp => hex.scroll_by(-page),
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        let page = list.page.max(1) as isize;
+        match key_event.code {
+            KeyCode::Enter => {
+                if let Some(packet) = self.tasd.packets.get(list.selected) {
+                    list.hex = Some(HexView::new(list.selected, packet));
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('P') => {
+                self.packet_list = None;
+                self.mode = AppMode::Normal;
+                return Ok(());
+            }
+            KeyCode::Char('j') | KeyCode::Down => list.move_by(1),
+            KeyCode::Char('k') | KeyCode::Up => list.move_by(-1),
+            KeyCode::Char('g') | KeyCode::Home => list.jump_to(0),
+            KeyCode::Char('G') | KeyCode::End => list.jump_to(usize::MAX),
+            KeyCode::Char('d') if control => list.move_by(page / 2),
+            KeyCode::Char('u') if control => list.move_by(-page / 2),
+            KeyCode::Char('f') if control => list.move_by(page),
+            KeyCode::Char('b') if control => list.move_by(-page),
+            KeyCode::PageDown => list.move_by(page),
+            KeyCode::PageUp => list.move_by(-page),
+            _ => {}
+        }
+        list.keep_visible();
+        Ok(())
+    }
+
+    fn handle_browser_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
+        use crossterm::event::KeyCode;
+
+        let Some(browser) = self.browser.as_mut() else {
+            self.mode = AppMode::Normal;
+            return Ok(());
+        };
+
+        // Typing a new name
+        if let Some(name) = browser.renaming.as_mut() {
+            match key_event.code {
+                KeyCode::Enter => {
+                    let name = name.trim().to_string();
+                    browser.renaming = None;
+                    self.status_message = Some(match browser.rename(&name) {
+                        Ok((old, new)) => {
+                            // Keep following the renamed file if it is open or listed
+                            for path in self.arg_list.iter_mut().filter(|path| **path == old) {
+                                *path = new.clone();
+                            }
+                            if self.file_path == old {
+                                self.file_path = new.clone();
+                            }
+                            format!("Renamed to {}", new.display())
+                        }
+                        Err(e) => format!("Rename failed: {}", e),
+                    });
+                }
+                KeyCode::Esc => browser.renaming = None,
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                KeyCode::Char(c) => name.push(c),
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        if browser.confirm_delete {
+            browser.confirm_delete = false;
+            if key_event.code == KeyCode::Char('y') {
+                self.status_message = Some(match browser.delete() {
+                    Ok(path) => format!("Deleted {}", path.display()),
+                    Err(e) => format!("Delete failed: {}", e),
+                });
+            }
+            return Ok(());
+        }
+
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.browser = None;
+                self.mode = AppMode::Normal;
+            }
+            KeyCode::Char('j') | KeyCode::Down => browser.move_by(1),
+            KeyCode::Char('k') | KeyCode::Up => browser.move_by(-1),
+            KeyCode::Char('g') | KeyCode::Home => browser.move_by(isize::MIN / 2),
+            KeyCode::Char('G') | KeyCode::End => browser.move_by(isize::MAX / 2),
+            KeyCode::Char('r' | 'd') | KeyCode::Delete if self.config.readonly => {
+                self.status_message = Some(READ_ONLY.to_string());
+            }
+            KeyCode::Char('r') => {
+                browser.renaming = browser.selected_path()
+                    .and_then(|path| path.file_name())
+                    .map(|name| name.to_string_lossy().to_string());
+            }
+            KeyCode::Char('d') | KeyCode::Delete if browser.selected_path().is_some() => {
+                browser.confirm_delete = true;
+            }
+            KeyCode::Enter => {
+                let Some(path) = browser.selected_path().cloned() else {
+                    return Ok(());
+                };
+                self.status_message = Some(match self.open_path(path, false) {
+                    Ok(summary) => {
+                        self.browser = None;
+                        self.mode = AppMode::Normal;
+                        summary
+                    }
+                    Err(e) => format!("Open failed: {}", e),
+                });
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_finder_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
+        use crossterm::event::KeyCode;
+
+        let Some(finder) = self.finder.as_mut() else {
+            self.mode = AppMode::Normal;
+            return Ok(());
+        };
+
+        match key_event.code {
+            KeyCode::Enter => {
+                let selection = finder.selection();
+                self.finder = None;
+                self.mode = AppMode::Normal;
+                if let Some(path) = selection {
+                    self.status_message = Some(match self.open_path(path, false) {
+                        Ok(summary) => summary,
+                        Err(e) => format!("Open failed: {}", e),
+                    });
+                }
+            }
+            KeyCode::Esc => {
+                self.finder = None;
+                self.mode = AppMode::Normal;
+            }
+            KeyCode::Down => finder.next(),
+            KeyCode::Up => finder.prev(),
+            KeyCode::Char('n') if key_event.modifiers.contains(KeyModifiers::CONTROL) => finder.next(),
+            KeyCode::Char('p') if key_event.modifiers.contains(KeyModifiers::CONTROL) => finder.prev(),
+            KeyCode::Backspace => finder.pop(),
+            KeyCode::Char(c) => finder.push(c),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Open the fuzzy finder over the TASD files below the working directory
+    fn open_finder(&mut self) {
+        let root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        self.finder = Some(Finder::new(&root, Arc::clone(&self.title_cache)));
+        self.mode = AppMode::Finder;
+    }
+
+    /// Open the directory browser, highlighting the open file if it is listed
+    pub fn open_browser(&mut self, dir: &Path) -> Result<()> {
+        let mut browser = Browser::open(dir)?;
+        browser.select_path(&self.file_path);
+        self.browser = Some(browser);
+        self.mode = AppMode::Browser;
+        Ok(())
+    }
+
+    /// Show a read-only text popup
+    pub fn show_popup(&mut self, title: &str, lines: Vec<String>) {
+        self.popup = Some(Popup {
+            title: title.to_string(),
+            lines,
+            scroll: 0,
+        });
+        self.mode = AppMode::Popup;
+    }
+
+    /// Open a picker popup
+    fn open_picker(&mut self, picker: Picker) {
+        if self.config.readonly {
+            self.status_message = Some(READ_ONLY.to_string());
+            return;
+        }
+        self.picker = Some(picker);
+        self.mode = AppMode::Picker;
+    }
+
+    /// Store the value chosen in a picker
+    fn apply_picker(&mut self, target: PickerTarget, value: String) {
+        if self.config.readonly {
+            self.status_message = Some(READ_ONLY.to_string());
+            return;
+        }
+        match target {
+            PickerTarget::License => {
+                self.replace_packet(Packet::MovieLicense(MovieLicense { license: value.clone() }));
+                self.status_message = Some(format!("License: {}", value));
+            }
+            PickerTarget::Category => {
+                self.replace_packet(Packet::Category(Category { category: value.clone() }));
+                self.status_message = Some(format!("Category: {}", value));
+            }
+        }
+    }
+
+    /// Fill metadata packets from a TASVideos publication or submission
+    fn fetch_metadata(&mut self, id: &str) -> Result<String> {
+        self.check_writable()?;
+        let metadata = tasvideos::fetch(id)?;
+        let mut updated = Vec::new();
+
+        if let Some(title) = metadata.game_title {
+            self.replace_packet(Packet::GameTitle(GameTitle { title }));
+            updated.push("title");
+        }
+        if let Some(category) = metadata.category {
+            self.replace_packet(Packet::Category(Category { category }));
+            updated.push("category");
+        }
+        if let Some(rerecords) = metadata.rerecords {
+            self.replace_packet(Packet::TotalRerecords(TotalRerecords { rerecords }));
+            updated.push("rerecords");
+        }
+        self.replace_packet(Packet::SourceLink(SourceLink { link: metadata.source_link }));
+        updated.push("source link");
+
+        // Add authors that aren't already credited
+        let existing = attributions::entries(&self.tasd.packets);
+        let mut added = 0;
+        for author in metadata.authors {
+            if !existing.iter().any(|(code, name)| *code == 1 && *name == author) {
+                attributions::add(&mut self.tasd.packets, 1, author);
+                added += 1;
+            }
+        }
+        if added > 0 {
+            updated.push("authors");
+        }
+
+        Ok(format!("Updated {}", updated.join(", ")))
+    }
+
+    /// Mark the movie as verified, crediting the configured verifier
+    fn stamp_verified(&mut self, notes: &str) -> Result<String> {
+        self.check_writable()?;
+        let verifier = self.config.verifier.clone()
+            .ok_or_else(|| eyre!("Set a verifier name first (:set verifier NAME)"))?;
+
+        self.replace_packet(Packet::Verified(Verified { verified: true }));
+
+        let credited = attributions::entries(&self.tasd.packets).iter()
+            .any(|(code, name)| *code == 2 && *name == verifier);
+        if !credited {
+            attributions::add(&mut self.tasd.packets, 2, verifier.clone());
+        }
+
+        let mut comment = format!("Verified by {} on {}", verifier, timing::today());
+        if !notes.is_empty() {
+            comment.push_str(&format!(": {}", notes));
+        }
+        self.tasd.packets.push(Packet::Comment(Comment { comment }));
+
+        Ok(format!("Stamped as verified by {}", verifier))
+    }
+
+    /// Add a packet described as `TYPE VALUE` or `TYPE field=value...`, e.g. `:addpacket Comment first try`
+    fn add_packet(&mut self, args: &str) -> Result<String> {
+        self.check_writable()?;
+        let (kind, value) = args.trim().split_once(' ').unwrap_or((args.trim(), ""));
+        let packet = tree::parse_packet(kind, value)?;
+
+        // Input goes last; anything else after the packets like it, or ahead of the input
+        let kind = std::mem::discriminant(&packet);
+        let input = matches!(packet, Packet::InputChunk(_) | Packet::InputMoment(_) | Packet::Transition(_) | Packet::LagFrameChunk(_));
+        let position = match self.tasd.packets.iter().rposition(|p| std::mem::discriminant(p) == kind) {
+            Some(last) => last + 1,
+            None if input => self.tasd.packets.len(),
+            None => self.tasd.packets.iter()
+                .position(|p| matches!(p, Packet::PortController(_) | Packet::InputChunk(_) | Packet::InputMoment(_) | Packet::Transition(_)))
+                .unwrap_or(self.tasd.packets.len()),
+        };
+        let summary = format!("Added packet {}: {} {}", position, report::packet_kind(&packet), report::describe_packet(&packet));
+        let refresh = App::shapes_inputs(&packet);
+        self.tasd.packets.insert(position, packet);
+        if refresh {
+            let tasd = std::mem::replace(&mut self.tasd, TASD::new());
+            self.reload(tasd);
+        }
+        Ok(summary)
+    }
+
+    /// Remove the packet at an index of the packet browser
+    fn remove_packet(&mut self, index: &str) -> Result<String> {
+        self.check_writable()?;
+        let index: usize = index.trim().parse().map_err(|_| eyre!("Usage: :rmpacket INDEX (see P for indices)"))?;
+        if index >= self.tasd.packets.len() {
+            return Err(eyre!("No packet {} (the file has {})", index, self.tasd.packets.len()));
+        }
+        let packet = self.tasd.packets.remove(index);
+        if App::shapes_inputs(&packet) {
+            let tasd = std::mem::replace(&mut self.tasd, TASD::new());
+            self.reload(tasd);
+        }
+        Ok(format!("Removed packet {}: {} {}", index, report::packet_kind(&packet), report::describe_packet(&packet)))
+    }
+
+    /// Whether adding or removing a packet changes the ports, controllers or frame count
+    fn shapes_inputs(packet: &Packet) -> bool {
+        matches!(
+            packet,
+            Packet::ConsoleType(_) | Packet::PortController(_) | Packet::InputChunk(_) | Packet::InputMoment(_) | Packet::TotalFrames(_)
+        )
+    }
+
+    /// Replace the first packet of the same type, or append it if there is none
+    pub fn replace_packet(&mut self, packet: Packet) {
+        let kind = std::mem::discriminant(&packet);
+        match self.tasd.packets.iter_mut().find(|p| std::mem::discriminant(*p) == kind) {
+            Some(existing) => *existing = packet,
+            None => self.tasd.packets.push(packet),
+        }
+    }
+
     /// Execute a command
     fn execute_command(&mut self) {
-        let cmd = self.command_buffer.trim();
+        let cmd = self.command_buffer.trim().to_string();
 
         // Parse commands similar to vim
         if cmd == "q" || cmd == "quit" {
-            self.exit();
-        } else if let Some(line_num) = cmd.parse::<usize>().ok() {
+            self.quit(false);
+        } else if cmd == "q!" || cmd == "quit!" {
+            self.quit(true);
+        } else if cmd == "wq" || cmd == "x" {
+            let path = self.file_path.clone();
+            match self.write_file(&path) {
+                Ok(_) => self.quit(false),
+                Err(e) => self.status_message = Some(format!("Save failed: {}", e)),
+            }
+        } else if let Ok(line_num) = cmd.parse::<usize>() {
             // Go to specific line number (1-indexed)
-            self.cursor.jump_to(line_num.saturating_sub(1));
+            self.jump_to_frame(line_num.saturating_sub(1));
             self.update_input_window();
+        } else if cmd == "w" || cmd.starts_with("w ") {
+            // Save, optionally to a different path
+            let path = cmd[1..].trim();
+            let path = if path.is_empty() { self.file_path.clone() } else { PathBuf::from(path) };
+            if let Err(e) = self.save_in_background(path) {
+                self.status_message = Some(format!("Save failed: {}", e));
+            }
+        } else if (cmd == "authors" || cmd == "attributions") && self.config.readonly {
+            self.status_message = Some(READ_ONLY.to_string());
+        } else if cmd == "authors" || cmd == "attributions" {
+            self.attribution_editor = AttributionEditor::new();
+            self.attribution_editor.before = Some(self.tasd.serialize());
+            self.mode = AppMode::Attributions;
+        } else if let Some(id) = cmd.strip_prefix("fetch-meta ") {
+            self.status_message = Some(match self.fetch_metadata(id) {
+                Ok(summary) => summary,
+                Err(e) => format!("Fetch failed: {}", e),
+            });
+        } else if let Some(args) = cmd.strip_prefix("export-range ") {
+            self.status_message = Some(match self.export_range(args) {
+                Ok(summary) => summary,
+                Err(e) => format!("Export failed: {}", e),
+            });
+        } else if let Some(args) = cmd.strip_prefix("export ") {
+            if let Err(e) = self.export(args) {
+                self.status_message = Some(format!("Export failed: {}", e));
+            }
+        } else if let Some(path) = cmd.strip_prefix("attach ") {
+            let path = PathBuf::from(path.trim());
+            self.status_message = Some(match self.check_writable().and_then(|()| attachments::embed_file(&path)) {
+                Ok(packet) => {
+                    self.tasd.packets.push(packet);
+                    format!("Attached {}", path.display())
+                }
+                Err(e) => format!("Attach failed: {}", e),
+            });
+        } else if let Some(rest) = cmd.strip_prefix("extract ") {
+            self.status_message = Some(match rest.trim().split_once(' ') {
+                Some((index, path)) => match index.parse::<usize>() {
+                    Ok(index) => match attachments::extract(&self.tasd.packets, index, Path::new(path.trim())) {
+                        Ok(size) => format!("Wrote {} bytes to {}", size, path.trim()),
+                        Err(e) => format!("Extract failed: {}", e),
+                    },
+                    Err(_) => format!("Invalid attachment index: {}", index),
+                },
+                None => "Usage: :extract <index> <path>".to_string(),
+            });
+        } else if cmd == "latches" {
+            match latches::report(&self.tasd.packets, self.total_frames) {
+                Some(lines) => self.show_popup("Latch timing", lines),
+                None => self.status_message = Some("No latch filter, latch train or lag frame packets".to_string()),
+            }
+        } else if cmd == "note" {
+            self.show_note();
+        } else if let Some(args) = cmd.strip_prefix("note ") {
+            self.status_message = Some(self.set_note(args));
+        } else if cmd == "notes" {
+            let lines: Vec<String> = notes::notes(&self.tasd.packets).into_iter()
+                .map(|(frame, text)| format!("{:>8}  {}", frame, text))
+                .collect();
+            if lines.is_empty() {
+                self.status_message = Some("No notes (:note FRAME TEXT adds one)".to_string());
+            } else {
+                self.show_popup("Notes", lines);
+            }
+        } else if cmd == "split" || cmd == "sp" {
+            self.split_panes();
+        } else if let Some(frame) = cmd.strip_prefix("split ").or_else(|| cmd.strip_prefix("sp ")) {
+            // The new pane starts at the given frame
+            match frame.trim().parse::<usize>() {
+                Ok(frame) => {
+                    self.split_panes();
+                    self.jump_to_frame(frame);
+                    self.update_input_window();
+                }
+                Err(_) => self.status_message = Some(format!("Invalid frame number: {}", frame.trim())),
+            }
+        } else if cmd == "only" {
+            self.split = None;
+        } else if cmd == "close" {
+            self.close_pane();
+        } else if cmd == "marks" {
+            let framerate = self.framerate();
+            let lines: Vec<String> = self.marks.iter()
+                .map(|(letter, frame)| format!("{}  {:>8}  {}", letter, frame, timing::format_timestamp(*frame, framerate)))
+                .collect();
+            if lines.is_empty() {
+                self.status_message = Some("No marks set (m<letter> sets one)".to_string());
+            } else {
+                self.show_popup("Marks", lines);
+            }
+        } else if cmd == "errors" {
+            let lines: Vec<String> = self.parse_errors.iter()
+                .map(|d| {
+                    let bytes: Vec<String> = d.bytes.iter().map(|byte| format!("{:02X}", byte)).collect();
+                    let more = if d.length > d.bytes.len() { " .." } else { "" };
+                    format!("{:#08x}  {:>8} bytes  {:<28} {}{}", d.offset, d.length, d.reason, bytes.join(" "), more)
+                })
+                .collect();
+            if lines.is_empty() {
+                self.status_message = Some("No parse errors".to_string());
+            } else {
+                self.show_popup("Parse errors", lines);
+            }
+        } else if let Some(args) = cmd.strip_prefix("controller ") {
+            self.status_message = Some(match self.set_controller(args) {
+                Ok(summary) => summary,
+                Err(e) => format!("Controller failed: {}", e),
+            });
+        } else if cmd == "port" {
+            let port = self.select_port().map_or("none".to_string(), |port| port.to_string());
+            self.status_message = Some(format!("Active port: {}", port));
+        } else if let Some(port) = cmd.strip_prefix("port ") {
+            self.status_message = Some(match port.trim().parse() {
+                Ok(port) => match self.set_active_port(port) {
+                    Ok(()) => format!("Active port: {}", port),
+                    Err(e) => e.to_string(),
+                },
+                Err(_) => "Usage: :port N".to_string(),
+            });
+        } else if cmd == "ports" || cmd == "ports all" {
+            self.display.hidden_ports.clear();
+        } else if let Some(list) = cmd.strip_prefix("ports ") {
+            let shown: Result<Vec<u8>, _> = list.split(',').map(|port| port.trim().parse::<u8>()).collect();
+            match shown {
+                Ok(shown) if shown.iter().all(|port| self.ports.contains(port)) => {
+                    self.display.hidden_ports = self.ports.iter().filter(|port| !shown.contains(port)).copied().collect();
+                    self.display.column_offset = 0;
+                }
+                Ok(shown) => {
+                    let missing: Vec<String> = shown.iter().filter(|port| !self.ports.contains(port)).map(u8::to_string).collect();
+                    self.status_message = Some(format!("No port {}", missing.join(", ")));
+                }
+                Err(_) => self.status_message = Some("Usage: :ports 1,3 (or :ports all)".to_string()),
+            }
+        } else if cmd == "hash" {
+            match std::fs::read(&self.file_path) {
+                Ok(data) => {
+                    let mut lines = checksum::report(&data, &self.tasd.packets, &self.ports);
+                    if self.modified {
+                        lines.insert(1, "(the file digest doesn't include unsaved changes)".to_string());
+                    }
+                    self.show_popup("SHA-256", lines);
+                }
+                Err(e) => self.status_message = Some(format!("Failed to read {}: {}", self.file_path.display(), e)),
+            }
+        } else if cmd == "hash embed" && self.config.readonly {
+            self.status_message = Some(READ_ONLY.to_string());
+        } else if cmd == "hash embed" {
+            checksum::update_checksums(&mut self.tasd, &self.ports);
+            self.integrity = checksum::verify(&self.tasd.packets);
+            self.status_message = Some(format!("Stored input checksums for {} ports (:w to save)", self.ports.len()));
+        } else if cmd == "attachments" {
+            let lines = attachments::list(&self.tasd.packets)
+                .iter()
+                .enumerate()
+                .map(|(i, a)| format!("{:>3}  {:<6} {:>10} bytes  {}", i, a.kind, a.size, a.name))
+                .collect::<Vec<String>>();
+            if lines.is_empty() {
+                self.status_message = Some("No embedded attachments".to_string());
+            } else {
+                self.show_popup("Attachments", lines);
+            }
+        } else if cmd == "category" {
+            self.open_picker(Picker::new("Category", catalog::CATEGORIES, PickerTarget::Category));
+        } else if let Some(category) = cmd.strip_prefix("category ") {
+            self.apply_picker(PickerTarget::Category, category.trim().to_string());
+        } else if cmd == "license" {
+            self.open_picker(Picker::new("Movie License", catalog::LICENSES, PickerTarget::License));
+        } else if let Some(license) = cmd.strip_prefix("license ") {
+            self.apply_picker(PickerTarget::License, license.trim().to_string());
+        } else if cmd == "filter" {
+            if self.filter.is_some() {
+                self.clear_filter();
+                self.status_message = Some("Filter cleared".to_string());
+            }
+        } else if let Some(expr) = cmd.strip_prefix("filter ") {
+            self.status_message = Some(match self.set_filter(expr.trim()) {
+                Ok(count) => format!("{} matching frames", count),
+                Err(e) => format!("Filter failed: {}", e),
+            });
+        } else if let Some(expr) = cmd.strip_prefix("count ") {
+            self.status_message = Some(match self.matching_frames(expr.trim()) {
+                Ok(frames) => format!("{} of {} frames match {}", frames.len(), self.total_frames, expr.trim()),
+                Err(e) => format!("Count failed: {}", e),
+            });
+        } else if cmd == "highlight" {
+            self.highlight = None;
+        } else if let Some(expr) = cmd.strip_prefix("highlight ") {
+            self.status_message = Some(match self.matching_frames(expr.trim()) {
+                Ok(frames) => {
+                    let count = frames.len();
+                    self.highlight = Some(Filter { expr: expr.trim().to_string(), frames });
+                    format!("Highlighting {} frames", count)
+                }
+                Err(e) => format!("Highlight failed: {}", e),
+            });
+        } else if cmd == "order" || cmd.starts_with("order ") {
+            self.status_message = Some(match self.set_port_order(&cmd[5..]) {
+                Ok(()) => format!("Port order: {:?}", self.visible_ports()),
+                Err(e) => format!("Order failed: {}", e),
+            });
+        } else if cmd == "stamp" || cmd.starts_with("stamp ") {
+            self.status_message = Some(match self.stamp_verified(cmd[5..].trim()) {
+                Ok(summary) => summary,
+                Err(e) => format!("Stamp failed: {}", e),
+            });
+        } else if cmd == "optimize" {
+            self.status_message = Some(self.optimize());
+        } else if cmd == "fixframes" {
+            self.status_message = Some(self.fix_frames());
+        } else if cmd == "n" || cmd == "next" || cmd == "N" || cmd == "prev" {
+            let forward = cmd == "n" || cmd == "next";
+            let index = if forward { self.arg_index + 1 } else { self.arg_index.wrapping_sub(1) };
+            self.status_message = Some(if index >= self.arg_list.len() {
+                format!("Already at the {} file", if forward { "last" } else { "first" })
+            } else {
+                match self.open_arg(index, false) {
+                    Ok(summary) => summary,
+                    Err(e) => format!("Open failed: {}", e),
+                }
+            });
+        } else if cmd == "tabnext" || cmd == "tabn" || cmd == "tabprevious" || cmd == "tabprev" || cmd == "tabp" {
+            self.next_tab(cmd == "tabnext" || cmd == "tabn");
+        } else if cmd == "e!" || cmd == "edit!" {
+            // Reload the shown file, dropping unsaved changes
+            let path = self.file_path.clone();
+            self.status_message = Some(match self.open_path(path, true) {
+                Ok(summary) => summary,
+                Err(e) => format!("Open failed: {}", e),
+            });
+        } else if let Some((force, path)) = cmd.strip_prefix("e! ").or_else(|| cmd.strip_prefix("edit! ")).map(|path| (true, path))
+            .or_else(|| cmd.strip_prefix("e ").or_else(|| cmd.strip_prefix("edit ")).map(|path| (false, path))) {
+            let path = PathBuf::from(path.trim());
+            self.status_message = Some(match self.open_path(path, force) {
+                Ok(summary) => summary,
+                Err(e) => format!("Open failed: {}", e),
+            });
+        } else if cmd == "browse" || cmd.starts_with("browse ") {
+            let dir = match cmd[6..].trim() {
+                "" => self.file_path.parent()
+                    .filter(|dir| !dir.as_os_str().is_empty())
+                    .unwrap_or(Path::new("."))
+                    .to_path_buf(),
+                dir => PathBuf::from(dir),
+            };
+            if let Err(e) = self.open_browser(&dir) {
+                self.status_message = Some(format!("Browse failed: {}", e));
+            }
+        } else if cmd == "args" {
+            let lines = self.arg_list.iter()
+                .enumerate()
+                .map(|(i, path)| format!("{} {}", if i == self.arg_index { ">" } else { " " }, path.display()))
+                .collect();
+            self.show_popup("Files", lines);
+        } else if let Some(time) = cmd.strip_prefix("time ") {
+            match timing::parse_timestamp(time) {
+                Some(seconds) => {
+                    let frame = timing::frame_at(seconds, self.framerate());
+                    self.jump_to_frame(frame.min(self.total_frames.saturating_sub(1)));
+                    self.update_input_window();
+                    if frame >= self.total_frames {
+                        self.status_message = Some(format!("{} is past the end of the movie", time.trim()));
+                    }
+                }
+                None => self.status_message = Some(format!("Invalid time: {} (use 1:23.456)", time.trim())),
+            }
+        } else if let Some(args) = cmd.strip_prefix("addpacket ") {
+            self.status_message = Some(match self.add_packet(args) {
+                Ok(summary) => summary,
+                Err(e) => format!("addpacket failed: {}", e),
+            });
+        } else if let Some(index) = cmd.strip_prefix("rmpacket ") {
+            self.status_message = Some(match self.remove_packet(index) {
+                Ok(summary) => summary,
+                Err(e) => format!("rmpacket failed: {}", e),
+            });
+        } else if cmd == "stats" {
+            self.show_popup("Input Statistics", stats::report(&self.tasd.packets));
+        } else if cmd == "report structure" {
+            self.show_popup("File Structure", report::structure(&self.tasd.packets));
+        } else if cmd == "report sizes" {
+            self.size_chart = Some(report::bytes_by_kind(&self.tasd.packets));
+            self.mode = AppMode::Sizes;
+        } else if let Some(name) = cmd.strip_prefix("checkpoint ") {
+            let name = name.trim();
+            self.checkpoints.retain(|c| c.name != name);
+            self.checkpoints.push(Checkpoint::new(name, &self.tasd));
+            self.status_message = Some(format!("Checkpoint {} saved", name));
+        } else if cmd == "checkpoints" {
+            let lines = self.checkpoints.iter()
+                .map(|c| format!("{:<24} {:>10} bytes", c.name, c.size()))
+                .collect::<Vec<String>>();
+            if lines.is_empty() {
+                self.status_message = Some("No checkpoints".to_string());
+            } else {
+                self.show_popup("Checkpoints", lines);
+            }
+        } else if let Some(name) = cmd.strip_prefix("checkpoint-diff ") {
+            match self.checkpoints.iter().find(|c| c.name == name.trim()).map(Checkpoint::restore) {
+                Some(Ok(old)) => {
+                    let lines = checkpoint::diff(
+                        &old.packets,
+                        &self.tasd.packets,
+                        |port| self.controller(port).frame_size(),
+                        self.config.align_diff,
+                    );
+                    if lines.is_empty() {
+                        self.status_message = Some(format!("No changes since {}", name.trim()));
+                    } else {
+                        self.show_popup(&format!("Changes since {}", name.trim()), lines);
+                    }
+                }
+                Some(Err(e)) => self.status_message = Some(e.to_string()),
+                None => self.status_message = Some(format!("No checkpoint named {}", name.trim())),
+            }
+        } else if cmd == "diffoff" {
+            self.diff = None;
+            self.status_message = Some("Diff closed".to_string());
+        } else if let Some(path) = cmd.strip_prefix("diff ") {
+            self.status_message = Some(match self.open_diff(path.trim()) {
+                Ok(0) => format!("No differences from {}", path.trim()),
+                Ok(count) => format!("{} frames differ from {} (]c/[c to visit, :diffoff to close)", count, path.trim()),
+                Err(e) => format!("Diff failed: {}", e),
+            });
+        } else if cmd.starts_with("rollback ") && self.config.readonly {
+            self.status_message = Some(READ_ONLY.to_string());
+        } else if let Some(name) = cmd.strip_prefix("rollback ") {
+            self.status_message = Some(match self.checkpoints.iter().find(|c| c.name == name.trim()).map(Checkpoint::restore) {
+                Some(Ok(tasd)) => {
+                    self.reload(tasd);
+                    format!("Rolled back to {}", name.trim())
+                }
+                Some(Err(e)) => e.to_string(),
+                None => format!("No checkpoint named {}", name.trim()),
+            });
+        } else if let Some(option) = cmd.strip_prefix("set ") {
+            self.set_option(option.trim().to_string());
+        } else if !cmd.is_empty() {
+            self.status_message = Some(format!("Not a command: {}", cmd));
         }
 
         self.command_buffer.clear();
     }
 
+    /// Write the open file in another format in the background, e.g. `:export fm2 movie.fm2`
+    fn export(&mut self, args: &str) -> Result<()> {
+        let (name, path) = args.trim().split_once(' ')
+            .ok_or_else(|| eyre!("Usage: :export FORMAT PATH"))?;
+        let format = <convert::Format as clap::ValueEnum>::from_str(name, true)
+            .map_err(|_| eyre!("Unknown format: {}", name))?;
+        let path = PathBuf::from(path.trim());
+
+        // The worker gets its own copy of the file, so editing can go on meanwhile
+        if self.worker.writing(&path) {
+            return Err(eyre!("{} is still being written", path.display()));
+        }
+        let data = self.tasd.serialize();
+        self.worker.spawn(format!("Exporting {}", path.display()), path.clone(), move || {
+            let (_, tasd) = TASD::deserialize(&data).map_err(|e| eyre!("Failed to parse TASD file: {:?}", e))?;
+            let data = convert::encode(&tasd, format)?;
+            worker::write_atomic(&path, &data)?;
+            Ok(Done::Message(format!("Exported {} bytes to {}", data.len(), path.display())))
+        });
+        Ok(())
+    }
+
+    /// Write frames `START..=END` (as numbered in the input table) to a new TASD, e.g. `:export-range 1000 2000 out.tasd`
+    fn export_range(&self, args: &str) -> Result<String> {
+        let usage = || eyre!("Usage: :export-range START END PATH");
+        let mut args = args.split_whitespace();
+        let start: usize = args.next().and_then(|n| n.parse().ok()).ok_or_else(usage)?;
+        let end: usize = args.next().and_then(|n| n.parse().ok()).ok_or_else(usage)?;
+        let path = PathBuf::from(args.next().ok_or_else(usage)?);
+        if start > end {
+            return Err(eyre!("Range starts after it ends ({} > {})", start, end));
+        }
+        if start >= self.total_frames {
+            return Err(eyre!("The movie has {} frames", self.total_frames));
+        }
+        let end = end.min(self.total_frames - 1);
+
+        let mut tasd = TASD::new();
+        tasd.packets = segment::extract(&self.tasd.packets, &self.controllers, start, end);
+        if self.config.checksums {
+            checksum::update_checksums(&mut tasd, &self.ports);
+        }
+        let data = compression::compress(tasd.serialize(), Compression::from_path(&path), self.config.compress_level)?;
+        worker::write_atomic(&path, &data)?;
+        Ok(format!("Wrote frames {}-{} ({} bytes) to {}", start, end, data.len(), path.display()))
+    }
+
+    /// Run the optimization pass, describing what it changed
+    fn optimize(&mut self) -> String {
+        if let Err(e) = self.check_writable() {
+            return e.to_string();
+        }
+        let before = self.tasd.serialize().len();
+        let result = optimize::optimize(&mut self.tasd.packets);
+        let saved = before.saturating_sub(self.tasd.serialize().len());
+        format!(
+            "Merged {} chunks, removed {} duplicate packets, saved {} bytes",
+            result.merged_chunks,
+            result.removed_duplicates,
+            saved,
+        )
+    }
+
+    /// Serialize the TASD data and write it to disk, returning the byte count
+    pub fn write_file(&mut self, path: &Path) -> Result<usize> {
+        // A background save still writing the file would land over this one
+        for (label, result) in self.worker.wait_for(path) {
+            self.finish_task(label, result);
+        }
+        let data = self.prepare_save()?;
+        let generation = self.history.generation;
+        let data = compression::compress(data, Compression::from_path(path), self.config.compress_level)?;
+        worker::write_atomic(path, &data)?;
+        self.mark_saved(path, generation);
+        Ok(data.len())
+    }
+
+    /// Compress and write the file on the worker, reporting back through `poll_tasks`
+    fn save_in_background(&mut self, path: PathBuf) -> Result<()> {
+        // Two saves racing on one file could finish in either order, leaving the older edits on disk
+        if self.worker.writing(&path) {
+            return Err(eyre!("{} is still being written (try again when it's done)", path.display()));
+        }
+        let data = self.prepare_save()?;
+        let generation = self.history.generation;
+        let level = self.config.compress_level;
+        self.worker.spawn(format!("Saving {}", path.display()), path.clone(), move || {
+            let data = compression::compress(data, Compression::from_path(&path), level)?;
+            worker::write_atomic(&path, &data)?;
+            Ok(Done::Saved { path, generation, size: data.len() })
+        });
+        Ok(())
+    }
+
+    /// Apply the save-time options (optimize, checksums, signature) and serialize the file
+    fn prepare_save(&mut self) -> Result<Vec<u8>> {
+        self.check_writable()?;
+        if self.config.optimize {
+            optimize::optimize(&mut self.tasd.packets);
+            // Merged chunks move the input around
+            self.input_index = InputIndex::build(&self.tasd.packets);
+        }
+
+        if self.config.checksums {
+            checksum::update_checksums(&mut self.tasd, &self.ports);
+            self.integrity = checksum::verify(&self.tasd.packets);
+        }
+
+        // Sign last, so the signature covers everything else that is written
+        if let Some(key) = &self.config.sign_key {
+            signature::sign(&mut self.tasd, &signature::load_key(key)?);
+        }
+
+        Ok(self.tasd.serialize())
+    }
+
+    /// Record that the file as it was at this history generation is now on disk at `path`
+    fn mark_saved(&mut self, path: &Path, generation: u64) {
+        // Writing a copy elsewhere leaves the open file unsaved
+        if path == self.file_path {
+            self.saved_generation = generation;
+            // Edits made while a background save ran are still unsaved
+            self.modified = self.history.generation != generation;
+        } else if let Some(document) = self.parked.get_mut(path) {
+            document.saved_generation = generation;
+            document.modified = document.history.generation != generation;
+        }
+    }
+
+    /// Report the background tasks that have finished
+    pub fn poll_tasks(&mut self) {
+        if let Some(finder) = &mut self.finder {
+            finder.poll();
+        }
+        for (label, result) in self.worker.poll() {
+            self.finish_task(label, result);
+        }
+    }
+
+    fn finish_task(&mut self, label: String, result: Result<Done>) {
+        self.status_message = Some(match result {
+            Ok(Done::Saved { path, generation, size }) => {
+                self.mark_saved(&path, generation);
+                format!("Wrote {} bytes to {}", size, path.display())
+            }
+            Ok(Done::Loaded(loaded)) => match self.show_loaded(*loaded) {
+                Ok(summary) => summary,
+                Err(e) => format!("Open failed: {}", e),
+            },
+            Ok(Done::Message(message)) => message,
+            Err(e) => format!("{} failed: {}", label, e),
+        });
+    }
+
+    /// Apply a `:set name value` or `:set name=value` option
+    fn set_option(&mut self, option: String) {
+        let (name, value) = option.split_once(['=', ' ']).unwrap_or((&option, ""));
+
+        self.status_message = Some(match self.config.set(name.trim(), value.trim()) {
+            Ok(()) => match name.trim() {
+                "renderer" => {
+                    self.renderers = Registry::new(&self.config);
+                    "renderer updated".to_string()
+                }
+                "framerate" | "fps" => format!("Framerate: {:.4} fps", self.framerate()),
+                // The layout is kept for future sessions too
+                "columns" => match Config::save("columns", value.trim()) {
+                    Ok(()) => "columns updated and saved to the config file".to_string(),
+                    Err(e) => format!("columns updated, but not saved: {}", e),
+                },
+                name => format!("{} updated", name),
+            },
+            Err(e) => e.to_string(),
+        });
+    }
+
+    /// Controller attached to a port, defaulting to a standard NES controller
+    pub fn controller(&self, port: u8) -> ControllerType {
+        self.controllers.get(&port).copied().unwrap_or(ControllerType::NesStandard)
+    }
+
+    /// Exit, refusing while there are unsaved changes unless forced
+    fn quit(&mut self, force: bool) {
+        // Let pending saves land first, so they count and aren't cut short
+        for (label, result) in self.worker.wait() {
+            self.finish_task(label, result);
+        }
+        let unsaved_tab = self.parked.iter()
+            .filter(|(_, document)| document.modified)
+            .find_map(|(path, _)| self.arg_list.iter().position(|arg| arg == path));
+        if self.modified && !force {
+            self.status_message = Some("No write since last change (:wq to save, :q! to discard)".to_string());
+        } else if let (Some(index), false) = (unsaved_tab, force) {
+            self.status_message = Some(format!(
+                "No write since last change in \"{}\" (gt to switch tabs, :q! to discard)",
+                self.arg_list[index].display()
+            ));
+        } else {
+            self.exit();
+        }
+    }
+
     fn exit(&mut self) {
         self.exit = true;
     }
@@ -0,0 +1,68 @@
+use std::path::Path;
+use color_eyre::{eyre::eyre, Result};
+use tasd_lib::{MovieFile, Packet};
+
+/// A file embedded in the TASD
+pub struct Attachment {
+    /// Position of the carrying packet in the packet list
+    pub packet_index: usize,
+    /// Kind of embedded data
+    pub kind: &'static str,
+    /// Stored file name
+    pub name: String,
+    /// Payload size in bytes
+    pub size: usize,
+}
+
+/// Every embedded file, in packet order
+///
+/// MovieFile packets carry source movies; MemoryInit packets carry SRAM and
+/// savestate images.
+pub fn list(packets: &[Packet]) -> Vec<Attachment> {
+    packets.iter()
+        .enumerate()
+        .filter_map(|(packet_index, packet)| match packet {
+            Packet::MovieFile(mf) => Some(Attachment {
+                packet_index,
+                kind: "Movie",
+                name: mf.name.clone(),
+                size: mf.data.len(),
+            }),
+            Packet::MemoryInit(mi) => Some(Attachment {
+                packet_index,
+                kind: "Memory",
+                name: mi.name.clone(),
+                size: mi.data.len(),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Read a file from disk and wrap it in a MovieFile packet
+pub fn embed_file(path: &Path) -> Result<Packet> {
+    let data = std::fs::read(path)?;
+    let name = path.file_name()
+        .ok_or_else(|| eyre!("Not a file: {}", path.display()))?
+        .to_string_lossy()
+        .to_string();
+
+    Ok(Packet::MovieFile(MovieFile { name, data }))
+}
+
+/// Write the attachment at a list index to disk, returning the byte count
+pub fn extract(packets: &[Packet], index: usize, path: &Path) -> Result<usize> {
+    let attachment = list(packets)
+        .into_iter()
+        .nth(index)
+        .ok_or_else(|| eyre!("No attachment #{}", index))?;
+
+    let data = match &packets[attachment.packet_index] {
+        Packet::MovieFile(mf) => &mf.data,
+        Packet::MemoryInit(mi) => &mi.data,
+        _ => unreachable!("attachments only come from MovieFile and MemoryInit packets"),
+    };
+
+    std::fs::write(path, data)?;
+    Ok(data.len())
+}
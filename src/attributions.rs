@@ -0,0 +1,110 @@
+use tasd_lib::{Attribution, AttributionType, Packet};
+
+/// Attribution type codes, in the order `t` cycles through them
+const TYPE_CYCLE: [u8; 5] = [1, 2, 3, 4, 0xFF];
+
+/// Display name for an attribution type code
+pub fn type_name(code: u8) -> &'static str {
+    match code {
+        1 => "Author",
+        2 => "Verifier",
+        3 => "File Creator",
+        4 => "File Editor",
+        _ => "Other",
+    }
+}
+
+/// State of the attribution list editor
+pub struct AttributionEditor {
+    /// Selected entry (index among Attribution packets)
+    pub selected: usize,
+    /// Name being typed, when editing the selected entry
+    pub editing: Option<String>,
+    /// The entry being edited was just added, so cancelling (or leaving it blank) removes it
+    pub adding: bool,
+    /// The file as it was when the editor opened, recorded as one undo step when it closes
+    pub before: Option<Vec<u8>>,
+}
+
+impl AttributionEditor {
+    pub fn new() -> Self {
+        Self {
+            selected: 0,
+            editing: None,
+            adding: false,
+            before: None,
+        }
+    }
+}
+
+/// Positions of every Attribution packet in the packet list
+pub fn indices(packets: &[Packet]) -> Vec<usize> {
+    packets.iter()
+        .enumerate()
+        .filter(|(_, packet)| matches!(packet, Packet::Attribution(_)))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Type code and name of every attribution, in file order
+pub fn entries(packets: &[Packet]) -> Vec<(u8, String)> {
+    packets.iter()
+        .filter_map(|packet| match packet {
+            Packet::Attribution(at) => Some((at.attribution_type as u8, at.name.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Add an attribution after the last existing one and return its list index
+pub fn add(packets: &mut Vec<Packet>, code: u8, name: String) -> usize {
+    let existing = indices(packets);
+    let position = existing.last().map(|i| i + 1).unwrap_or(packets.len());
+    let attribution_type = AttributionType::try_from(code).unwrap_or(AttributionType::Author);
+
+    packets.insert(position, Packet::Attribution(Attribution { attribution_type, name }));
+    existing.len()
+}
+
+/// Remove the attribution at a list index
+pub fn remove(packets: &mut Vec<Packet>, index: usize) {
+    if let Some(position) = indices(packets).get(index) {
+        packets.remove(*position);
+    }
+}
+
+/// Rename the attribution at a list index
+pub fn rename(packets: &mut [Packet], index: usize, name: String) {
+    if let Some(position) = indices(packets).get(index) {
+        if let Packet::Attribution(at) = &mut packets[*position] {
+            at.name = name;
+        }
+    }
+}
+
+/// Advance the attribution at a list index to the next type
+pub fn cycle_type(packets: &mut [Packet], index: usize) {
+    if let Some(position) = indices(packets).get(index) {
+        if let Packet::Attribution(at) = &mut packets[*position] {
+            let current = TYPE_CYCLE.iter().position(|code| *code == at.attribution_type as u8).unwrap_or(0);
+            let next = TYPE_CYCLE[(current + 1) % TYPE_CYCLE.len()];
+            if let Ok(attribution_type) = AttributionType::try_from(next) {
+                at.attribution_type = attribution_type;
+            }
+        }
+    }
+}
+
+/// Swap the attribution at a list index with its neighbour, returning the new index
+pub fn move_entry(packets: &mut [Packet], index: usize, down: bool) -> usize {
+    let positions = indices(packets);
+    let other = if down { index + 1 } else { index.wrapping_sub(1) };
+
+    match (positions.get(index), positions.get(other)) {
+        (Some(a), Some(b)) => {
+            packets.swap(*a, *b);
+            other
+        }
+        _ => index,
+    }
+}
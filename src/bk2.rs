@@ -0,0 +1,242 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Cursor, Read};
+use color_eyre::{eyre::eyre, Result};
+use tasd_lib::{
+    Attribution, AttributionType, Console, ConsoleRegion, ConsoleType, EmulatorName, EmulatorVersion, GameTitle,
+    InputChunk, Packet, PortController, TotalFrames, TotalRerecords, Transition, TransitionType, VideoSignal, TASD,
+};
+use crate::controller::{ControllerType, N64Accessory};
+
+/// Transition index type counting frames
+const INDEX_FRAME: u8 = 1;
+
+/// How one BizHawk platform's controllers are stored as TASD input
+struct Layout {
+    console: Console,
+    /// PortController code of the controllers
+    code: u16,
+    frame_size: usize,
+    /// Whether a cleared bit means pressed
+    active_low: bool,
+    /// BizHawk button name (without the player prefix), byte and mask
+    buttons: &'static [(&'static str, usize, u8)],
+    /// BizHawk axis name and the byte holding its signed value
+    axes: &'static [(&'static str, usize)],
+}
+
+const NES: Layout = Layout {
+    console: Console::Nes,
+    code: 0x0101,
+    frame_size: 1,
+    active_low: true,
+    buttons: &[
+        ("A", 0, 0x01), ("B", 0, 0x02), ("Select", 0, 0x04), ("Start", 0, 0x08),
+        ("Up", 0, 0x10), ("Down", 0, 0x20), ("Left", 0, 0x40), ("Right", 0, 0x80),
+    ],
+    axes: &[],
+};
+
+const SNES: Layout = Layout {
+    console: Console::Snes,
+    code: 0x0201,
+    frame_size: 2,
+    active_low: true,
+    buttons: &[
+        ("B", 0, 0x01), ("Y", 0, 0x02), ("Select", 0, 0x04), ("Start", 0, 0x08),
+        ("Up", 0, 0x10), ("Down", 0, 0x20), ("Left", 0, 0x40), ("Right", 0, 0x80),
+        ("A", 1, 0x01), ("X", 1, 0x02), ("L", 1, 0x04), ("R", 1, 0x08),
+    ],
+    axes: &[],
+};
+
+const N64: Layout = Layout {
+    console: Console::N64,
+    code: 0x0301,
+    frame_size: 4,
+    active_low: false,
+    buttons: &[
+        ("DPad R", 0, 0x01), ("DPad L", 0, 0x02), ("DPad D", 0, 0x04), ("DPad U", 0, 0x08),
+        ("Start", 0, 0x10), ("Z", 0, 0x20), ("B", 0, 0x40), ("A", 0, 0x80),
+        ("C Right", 1, 0x01), ("C Left", 1, 0x02), ("C Down", 1, 0x04), ("C Up", 1, 0x08),
+        ("R", 1, 0x10), ("L", 1, 0x20),
+    ],
+    axes: &[("X Axis", 2), ("Y Axis", 3)],
+};
+
+/// Game Boy buttons, in the layout used for `.vbm` movies
+const GB_BUTTONS: &[(&str, usize, u8)] = &[
+    ("A", 0, 0x01), ("B", 0, 0x02), ("Select", 0, 0x04), ("Start", 0, 0x08),
+    ("Right", 0, 0x10), ("Left", 0, 0x20), ("Up", 0, 0x40), ("Down", 0, 0x80),
+    ("R", 1, 0x01), ("L", 1, 0x02),
+];
+
+const GB: Layout = Layout { console: Console::Gb, code: 0x0501, frame_size: 1, active_low: false, buttons: GB_BUTTONS, axes: &[] };
+const GBC: Layout = Layout { console: Console::Gbc, code: 0x0601, frame_size: 1, active_low: false, buttons: GB_BUTTONS, axes: &[] };
+const GBA: Layout = Layout { console: Console::Gba, code: 0x0701, frame_size: 2, active_low: false, buttons: GB_BUTTONS, axes: &[] };
+
+/// Read a file from the movie archive
+fn read_entry(archive: &mut zip::ZipArchive<Cursor<&[u8]>>, name: &str) -> Result<String> {
+    let mut entry = archive.by_name(name).map_err(|_| eyre!("BK2 movie has no {}", name))?;
+    let mut text = String::new();
+    entry.read_to_string(&mut text)?;
+    Ok(text)
+}
+
+/// Split a log key name like `P2 Up` into its player and button
+fn player_button(name: &str) -> (u8, &str) {
+    name.strip_prefix('P')
+        .and_then(|rest| rest.split_once(' '))
+        .and_then(|(player, button)| Some((player.parse().ok()?, button)))
+        .unwrap_or((1, name))
+}
+
+/// Convert a BizHawk `.bk2` movie into TASD packets
+///
+/// The input log is mapped through its `LogKey` line, which names every column of a frame,
+/// so movies with any controller order or extra columns are read correctly.
+pub fn import(data: &[u8]) -> Result<TASD> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(data))
+        .map_err(|e| eyre!("Not a BizHawk movie (not a zip archive: {})", e))?;
+
+    let header_text = read_entry(&mut archive, "Header.txt")?;
+    let header: HashMap<&str, &str> = header_text.lines()
+        .filter_map(|line| line.trim().split_once(' '))
+        .collect();
+    let flag = |key: &str| header.get(key).is_some_and(|value| matches!(value.trim(), "True" | "true" | "1"));
+
+    if flag("StartsFromSavestate") || flag("StartsFromSaveRam") {
+        return Err(eyre!("Movies that start from a savestate or SaveRAM cannot be converted"));
+    }
+    let platform = header.get("Platform").copied().unwrap_or_default().trim();
+    let layout = match platform {
+        "NES" => &NES,
+        "SNES" => &SNES,
+        "N64" => &N64,
+        "GB" if flag("IsCGBMode") => &GBC,
+        "GB" => &GB,
+        "GBC" => &GBC,
+        "GBA" => &GBA,
+        other => return Err(eyre!("Unsupported BK2 platform '{}' (NES, SNES, N64, GB, GBC and GBA are supported)", other)),
+    };
+
+    let log = read_entry(&mut archive, "Input Log.txt")?;
+    let log_key = log.lines()
+        .find_map(|line| line.trim().strip_prefix("LogKey:"))
+        .ok_or_else(|| eyre!("BK2 input log has no LogKey line"))?;
+    // Columns are grouped by '#', with the names of each group separated by '|'
+    let groups: Vec<Vec<&str>> = log_key.split('#')
+        .filter(|group| !group.is_empty())
+        .map(|group| group.split('|').filter(|name| !name.is_empty()).collect())
+        .collect();
+
+    let mut ports: BTreeMap<u8, Vec<u8>> = BTreeMap::new();
+    let mut transitions = Vec::new();
+    let neutral = if layout.active_low { 0xFF } else { 0x00 };
+    let mut frames = 0;
+
+    for line in log.lines().map(str::trim).filter(|line| line.starts_with('|')) {
+        let fields: Vec<&str> = line.trim_matches('|').split('|').collect();
+        let mut frame: BTreeMap<u8, Vec<u8>> = BTreeMap::new();
+
+        for (names, field) in groups.iter().zip(fields) {
+            // Analog values come first, separated by commas, followed by one character per button
+            let mut parts: Vec<&str> = field.split(',').collect();
+            let buttons = parts.pop().unwrap_or_default();
+            let (axis_names, button_names) = names.split_at(parts.len().min(names.len()));
+
+            for (name, value) in axis_names.iter().zip(&parts) {
+                let (player, axis) = player_button(name);
+                if let Some((_, byte)) = layout.axes.iter().find(|(axis_name, _)| *axis_name == axis) {
+                    let value: i32 = value.trim().parse().map_err(|_| eyre!("Invalid {} value '{}'", name, value.trim()))?;
+                    let input = frame.entry(player).or_insert_with(|| vec![neutral; layout.frame_size]);
+                    input[*byte] = value.clamp(-128, 127) as i8 as u8;
+                }
+            }
+
+            for (name, state) in button_names.iter().zip(buttons.chars()) {
+                let pressed = state != '.' && state != ' ';
+                match (*name, pressed) {
+                    ("Reset", true) => transitions.push((frames, TransitionType::SoftReset)),
+                    ("Power", true) => transitions.push((frames, TransitionType::PowerReset)),
+                    _ => {}
+                }
+
+                let (player, button) = player_button(name);
+                let found = layout.buttons.iter().find(|(button_name, _, _)| *button_name == button);
+                // L and R only have room on the GBA
+                if let Some((_, byte, mask)) = found.filter(|(_, byte, _)| *byte < layout.frame_size) {
+                    let input = frame.entry(player).or_insert_with(|| vec![neutral; layout.frame_size]);
+                    if pressed != layout.active_low {
+                        input[*byte] |= mask;
+                    } else {
+                        input[*byte] &= !mask;
+                    }
+                }
+            }
+        }
+
+        // Players missing from this frame keep their columns aligned with released buttons
+        for player in frame.keys() {
+            ports.entry(*player).or_insert_with(|| vec![neutral; frames * layout.frame_size]);
+        }
+        for (player, inputs) in &mut ports {
+            match frame.remove(player) {
+                Some(input) => inputs.extend(input),
+                None => inputs.extend(vec![neutral; layout.frame_size]),
+            }
+        }
+        frames += 1;
+    }
+
+    if ports.is_empty() {
+        return Err(eyre!("BK2 input log has no {} controller input", platform));
+    }
+
+    let mut tasd = TASD::new();
+    let packets = &mut tasd.packets;
+    packets.push(Packet::ConsoleType(ConsoleType { console: layout.console, name: String::new() }));
+    packets.push(Packet::ConsoleRegion(ConsoleRegion {
+        video_signal: if flag("PAL") { VideoSignal::Pal } else { VideoSignal::Ntsc },
+    }));
+    if let Some(title) = header.get("GameName").map(|name| name.trim()).filter(|name| !name.is_empty()) {
+        packets.push(Packet::GameTitle(GameTitle { title: title.to_string() }));
+    }
+    for author in header.get("Author").copied().unwrap_or_default().split(',') {
+        if !author.trim().is_empty() {
+            packets.push(Packet::Attribution(Attribution {
+                attribution_type: AttributionType::Author,
+                name: author.trim().to_string(),
+            }));
+        }
+    }
+    packets.push(Packet::EmulatorName(EmulatorName { name: "BizHawk".to_string() }));
+    if let Some(version) = header.get("emuVersion") {
+        let version = version.trim().trim_start_matches("Version ");
+        packets.push(Packet::EmulatorVersion(EmulatorVersion {
+            version: match header.get("Core") {
+                Some(core) => format!("{} ({} core)", version, core.trim()),
+                None => version.to_string(),
+            },
+        }));
+    }
+    if let Some(rerecords) = header.get("rerecordCount").and_then(|count| count.trim().parse().ok()) {
+        packets.push(Packet::TotalRerecords(TotalRerecords { rerecords }));
+    }
+
+    for port in ports.keys() {
+        let code = match layout.console {
+            Console::N64 => ControllerType::N64(N64Accessory::None).code(),
+            _ => layout.code,
+        };
+        packets.push(Packet::PortController(PortController { port: *port, controller_type: code }));
+    }
+    packets.push(Packet::TotalFrames(TotalFrames { frames: frames as u32 }));
+    for (frame, transition_type) in transitions {
+        packets.push(Packet::Transition(Transition { index_type: INDEX_FRAME, index: frame as u64, transition_type }));
+    }
+    for (port, inputs) in ports {
+        packets.push(Packet::InputChunk(InputChunk { port, inputs }));
+    }
+
+    Ok(tasd)
+}
@@ -0,0 +1,105 @@
+use std::path::{Path, PathBuf};
+use color_eyre::{eyre::eyre, Result};
+use crate::inventory::{self, Summary};
+
+/// Metadata of the highlighted file
+pub struct Preview {
+    /// File size in bytes
+    pub size: u64,
+    /// Parsed metadata, or `None` if the file is not readable
+    pub summary: Option<Summary>,
+}
+
+/// State of the directory browser
+pub struct Browser {
+    /// Directory being listed
+    pub dir: PathBuf,
+    /// TASD files in the directory, sorted by name
+    pub entries: Vec<PathBuf>,
+    /// Index of the highlighted entry
+    pub selected: usize,
+    /// Preview of the highlighted entry
+    pub preview: Option<Preview>,
+    /// New name being typed, while renaming
+    pub renaming: Option<String>,
+    /// Waiting for `y` to delete the highlighted entry
+    pub confirm_delete: bool,
+}
+
+impl Browser {
+    pub fn open(dir: &Path) -> Result<Self> {
+        let mut browser = Self {
+            dir: dir.to_path_buf(),
+            entries: Vec::new(),
+            selected: 0,
+            preview: None,
+            renaming: None,
+            confirm_delete: false,
+        };
+        browser.refresh()?;
+        Ok(browser)
+    }
+
+    /// Re-read the directory, keeping the selection in range
+    pub fn refresh(&mut self) -> Result<()> {
+        self.entries = inventory::tasd_files(&self.dir)?;
+        self.selected = self.selected.min(self.entries.len().saturating_sub(1));
+        self.load_preview();
+        Ok(())
+    }
+
+    /// Path of the highlighted entry
+    pub fn selected_path(&self) -> Option<&PathBuf> {
+        self.entries.get(self.selected)
+    }
+
+    /// Highlight the entry showing this path, if it is listed
+    pub fn select_path(&mut self, path: &Path) {
+        if let Some(index) = self.entries.iter().position(|entry| entry == path) {
+            self.selected = index;
+            self.load_preview();
+        }
+    }
+
+    /// Move the highlight, clamped to the list
+    pub fn move_by(&mut self, steps: isize) {
+        let last = self.entries.len().saturating_sub(1) as isize;
+        let selected = (self.selected as isize + steps).clamp(0, last) as usize;
+        if selected != self.selected {
+            self.selected = selected;
+            self.load_preview();
+        }
+    }
+
+    fn load_preview(&mut self) {
+        self.preview = self.selected_path().map(|path| Preview {
+            size: std::fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+            summary: crate::load_tasd(path).ok().map(|tasd| inventory::summarize(&tasd)),
+        });
+    }
+
+    /// Rename the highlighted entry within the directory, returning the old and new paths
+    pub fn rename(&mut self, name: &str) -> Result<(PathBuf, PathBuf)> {
+        let old = self.selected_path().cloned().ok_or_else(|| eyre!("No file selected"))?;
+        if name.is_empty() || name.contains(std::path::MAIN_SEPARATOR) || name.contains('/') {
+            return Err(eyre!("Invalid file name '{}'", name));
+        }
+        let new = self.dir.join(name);
+        if new.exists() {
+            return Err(eyre!("{} already exists", new.display()));
+        }
+
+        std::fs::rename(&old, &new)?;
+        self.refresh()?;
+        self.select_path(&new);
+        Ok((old, new))
+    }
+
+    /// Delete the highlighted entry, returning its path
+    pub fn delete(&mut self) -> Result<PathBuf> {
+        let path = self.selected_path().cloned().ok_or_else(|| eyre!("No file selected"))?;
+        std::fs::remove_file(&path)?;
+        self.refresh()?;
+        Ok(path)
+    }
+}
@@ -0,0 +1,77 @@
+/// SPDX identifiers and full names offered by the license picker
+pub const LICENSES: &[(&str, &str)] = &[
+    ("CC0-1.0", "Creative Commons Zero v1.0 Universal"),
+    ("CC-BY-4.0", "Creative Commons Attribution 4.0 International"),
+    ("CC-BY-SA-4.0", "Creative Commons Attribution Share Alike 4.0 International"),
+    ("CC-BY-NC-4.0", "Creative Commons Attribution Non Commercial 4.0 International"),
+    ("CC-BY-NC-SA-4.0", "Creative Commons Attribution Non Commercial Share Alike 4.0 International"),
+    ("CC-BY-ND-4.0", "Creative Commons Attribution No Derivatives 4.0 International"),
+    ("CC-BY-NC-ND-4.0", "Creative Commons Attribution Non Commercial No Derivatives 4.0 International"),
+    ("CC-BY-3.0", "Creative Commons Attribution 3.0 Unported"),
+    ("CC-BY-SA-3.0", "Creative Commons Attribution Share Alike 3.0 Unported"),
+    ("CC-PDDC", "Creative Commons Public Domain Dedication and Certification"),
+    ("Unlicense", "The Unlicense"),
+    ("WTFPL", "Do What The F*ck You Want To Public License"),
+    ("0BSD", "BSD Zero Clause License"),
+    ("MIT", "MIT License"),
+    ("ISC", "ISC License"),
+    ("BSD-2-Clause", "BSD 2-Clause \"Simplified\" License"),
+    ("BSD-3-Clause", "BSD 3-Clause \"New\" or \"Revised\" License"),
+    ("Apache-2.0", "Apache License 2.0"),
+    ("MPL-2.0", "Mozilla Public License 2.0"),
+    ("GPL-2.0-only", "GNU General Public License v2.0 only"),
+    ("GPL-2.0-or-later", "GNU General Public License v2.0 or later"),
+    ("GPL-3.0-only", "GNU General Public License v3.0 only"),
+    ("GPL-3.0-or-later", "GNU General Public License v3.0 or later"),
+    ("LGPL-3.0-only", "GNU Lesser General Public License v3.0 only"),
+    ("AGPL-3.0-only", "GNU Affero General Public License v3.0 only"),
+    ("ODbL-1.0", "Open Data Commons Open Database License v1.0"),
+    ("PDDL-1.0", "Open Data Commons Public Domain Dedication & License 1.0"),
+];
+
+/// Common TASVideos branch and category names offered by the category picker
+pub const CATEGORIES: &[(&str, &str)] = &[
+    ("any%", "Fastest completion by any means"),
+    ("100%", "Full completion"),
+    ("warps", "Uses in-game warps"),
+    ("warpless", "Avoids in-game warps"),
+    ("glitched", "Major glitches allowed"),
+    ("glitchless", "No major glitches"),
+    ("game end glitch", "Skips to the ending via a glitch"),
+    ("arbitrary code execution", "Executes injected code"),
+    ("low%", "Minimal item collection"),
+    ("pacifist", "Avoids killing enemies"),
+    ("all levels", "Visits every level"),
+    ("all bosses", "Defeats every boss"),
+    ("best ending", "Reaches the best ending"),
+    ("bad ending", "Reaches the bad ending"),
+    ("hard", "Hardest difficulty"),
+    ("easy", "Easiest difficulty"),
+    ("2 players", "Two-player movie"),
+    ("maximum score", "Maximizes in-game score"),
+    ("minimum score", "Minimizes in-game score"),
+    ("playaround", "Entertainment-focused demonstration"),
+    ("baseline", "Baseline verification of a published movie"),
+    ("console verification", "Run played back on original hardware"),
+];
+
+/// ConsoleType codes and their display names
+pub const CONSOLES: &[(u8, &str)] = &[
+    (1, "NES"),
+    (2, "SNES"),
+    (3, "N64"),
+    (4, "GameCube"),
+    (5, "Game Boy"),
+    (6, "Game Boy Color"),
+    (7, "Game Boy Advance"),
+    (8, "Sega Genesis"),
+    (9, "Atari 2600"),
+];
+
+/// Display name of a ConsoleType code
+pub fn console_name(code: u8) -> &'static str {
+    CONSOLES.iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, name)| *name)
+        .unwrap_or("Unknown")
+}
@@ -0,0 +1,133 @@
+use std::collections::BTreeSet;
+use color_eyre::{eyre::eyre, Result};
+use tasd_lib::{Packet, Serializable, TASD};
+
+use crate::diff::{self, Edit};
+
+/// A named snapshot of the file taken during the editing session
+pub struct Checkpoint {
+    /// Name given with `:checkpoint`
+    pub name: String,
+    /// Serialized file at the time of the checkpoint
+    data: Vec<u8>,
+}
+
+impl Checkpoint {
+    pub fn new(name: &str, tasd: &TASD) -> Self {
+        Self {
+            name: name.to_string(),
+            data: tasd.serialize(),
+        }
+    }
+
+    /// Size of the snapshot in bytes
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Decode the snapshot back into a TASD
+    pub fn restore(&self) -> Result<TASD> {
+        let (_, tasd) = TASD::deserialize(&self.data)
+            .map_err(|e| eyre!("Failed to restore checkpoint {}: {:?}", self.name, e))?;
+        Ok(tasd)
+    }
+}
+
+/// Concatenated input bytes of a port
+pub fn port_inputs(packets: &[Packet], port: u8) -> Vec<u8> {
+    let mut inputs = Vec::new();
+    for packet in packets {
+        if let Packet::InputChunk(chunk) = packet {
+            if chunk.port == port {
+                inputs.extend_from_slice(&chunk.inputs);
+            }
+        }
+    }
+    inputs
+}
+
+/// Short one-line description of a non-input packet
+fn summarize(packet: &Packet) -> String {
+    let text = format!("{:?}", packet);
+    match text.char_indices().nth(80) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text,
+    }
+}
+
+/// Describe what changed between two packet lists, frame sizes given per port.
+/// With `align_content`, frames are matched by content so insertions don't shift everything after them.
+pub fn diff(old: &[Packet], new: &[Packet], frame_size: impl Fn(u8) -> usize, align_content: bool) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    let ports: BTreeSet<u8> = old.iter().chain(new.iter())
+        .filter_map(|packet| match packet {
+            Packet::InputChunk(chunk) => Some(chunk.port),
+            _ => None,
+        })
+        .collect();
+
+    for port in ports {
+        let size = frame_size(port).max(1);
+        let old_inputs = port_inputs(old, port);
+        let new_inputs = port_inputs(new, port);
+        if old_inputs == new_inputs {
+            continue;
+        }
+
+        let old_frames: Vec<&[u8]> = old_inputs.chunks(size).collect();
+        let new_frames: Vec<&[u8]> = new_inputs.chunks(size).collect();
+
+        if align_content {
+            let edits = diff::align(&old_frames, &new_frames);
+            let inserted = edits.iter().filter(|e| **e == Edit::Insert).count();
+            let deleted = edits.iter().filter(|e| **e == Edit::Delete).count();
+            // Position in the new movie of the first edit
+            let first = edits.iter().take_while(|e| **e == Edit::Keep).count();
+            lines.push(format!(
+                "Port {}: {} frames inserted, {} deleted, first at {} ({} -> {} frames)",
+                port,
+                inserted,
+                deleted,
+                first + 1,
+                old_frames.len(),
+                new_frames.len(),
+            ));
+            continue;
+        }
+
+        let changed: Vec<usize> = (0..old_frames.len().max(new_frames.len()))
+            .filter(|i| old_frames.get(*i) != new_frames.get(*i))
+            .collect();
+        lines.push(format!(
+            "Port {}: {} frames differ, first at {} ({} -> {} frames)",
+            port,
+            changed.len(),
+            changed.first().map(|i| i + 1).unwrap_or(0),
+            old_frames.len(),
+            new_frames.len(),
+        ));
+    }
+
+    // Compare everything else packet by packet
+    let others = |packets: &[Packet]| -> Vec<String> {
+        packets.iter()
+            .filter(|p| !matches!(p, Packet::InputChunk(_)))
+            .map(|p| format!("{:?}", p))
+            .collect()
+    };
+    let old_others = others(old);
+    let new_others = others(new);
+    for packet in old.iter().filter(|p| !matches!(p, Packet::InputChunk(_))) {
+        if !new_others.contains(&format!("{:?}", packet)) {
+            lines.push(format!("- {}", summarize(packet)));
+        }
+    }
+    for packet in new.iter().filter(|p| !matches!(p, Packet::InputChunk(_))) {
+        if !old_others.contains(&format!("{:?}", packet)) {
+            lines.push(format!("+ {}", summarize(packet)));
+        }
+    }
+
+    lines
+}
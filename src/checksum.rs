@@ -0,0 +1,106 @@
+use sha2::{Digest, Sha256};
+use tasd_lib::{Comment, Packet, TASD};
+
+/// Prefix marking Comment packets that carry an input checksum
+///
+/// Checksums are stored as `tasd-cli:input-sha256 port=<n> <hex digest>`, so
+/// other tools treat them as ordinary comments.
+const PREFIX: &str = "tasd-cli:input-sha256";
+
+/// Outcome of checking stored checksums against the input data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Integrity {
+    /// The file carries no input checksums
+    Unchecked,
+    /// Every stored checksum matches (number of ports checked)
+    Verified(usize),
+    /// These ports no longer match their stored checksum
+    Mismatch(Vec<u8>),
+}
+
+/// Compute the SHA-256 digest of a port's concatenated input chunks
+pub fn port_digest(packets: &[Packet], port: u8) -> String {
+    let mut hasher = Sha256::new();
+    for packet in packets {
+        if let Packet::InputChunk(chunk) = packet {
+            if chunk.port == port {
+                hasher.update(&chunk.inputs);
+            }
+        }
+    }
+
+    hex(&hasher.finalize())
+}
+
+/// Compute the SHA-256 digest of a whole file as stored
+pub fn file_digest(data: &[u8]) -> String {
+    hex(&Sha256::digest(data))
+}
+
+fn hex(digest: &[u8]) -> String {
+    digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Digests of a file and of each port's input, then the state of any stored checksums
+pub fn report(data: &[u8], packets: &[Packet], ports: &[u8]) -> Vec<String> {
+    let mut lines = vec![format!("File    {}", file_digest(data))];
+    for port in ports {
+        lines.push(format!("Port {}  {}", port, port_digest(packets, *port)));
+    }
+    lines.push(match verify(packets) {
+        Integrity::Unchecked => "No checksums stored".to_string(),
+        Integrity::Verified(count) => format!("Stored checksums match ({} ports)", count),
+        Integrity::Mismatch(ports) => format!("Stored checksums MISMATCH on ports {:?}", ports),
+    });
+    lines
+}
+
+/// Parse a checksum comment into its port and digest
+fn parse_comment(comment: &str) -> Option<(u8, &str)> {
+    let rest = comment.strip_prefix(PREFIX)?.trim();
+    let (port, digest) = rest.split_once(' ')?;
+    let port = port.strip_prefix("port=")?.parse().ok()?;
+    Some((port, digest.trim()))
+}
+
+/// Whether a packet is a checksum written by this tool
+pub fn is_checksum_packet(packet: &Packet) -> bool {
+    matches!(packet, Packet::Comment(c) if c.comment.starts_with(PREFIX))
+}
+
+/// Replace any stored checksums with fresh ones for the given ports
+pub fn update_checksums(tasd: &mut TASD, ports: &[u8]) {
+    tasd.packets.retain(|packet| !is_checksum_packet(packet));
+
+    for port in ports {
+        let digest = port_digest(&tasd.packets, *port);
+        tasd.packets.push(Packet::Comment(Comment {
+            comment: format!("{} port={} {}", PREFIX, port, digest),
+        }));
+    }
+}
+
+/// Check every stored checksum against the current input data
+pub fn verify(packets: &[Packet]) -> Integrity {
+    let mut checked = 0;
+    let mut mismatched = Vec::new();
+
+    for packet in packets {
+        if let Packet::Comment(c) = packet {
+            if let Some((port, digest)) = parse_comment(&c.comment) {
+                checked += 1;
+                if port_digest(packets, port) != digest {
+                    mismatched.push(port);
+                }
+            }
+        }
+    }
+
+    if !mismatched.is_empty() {
+        Integrity::Mismatch(mismatched)
+    } else if checked > 0 {
+        Integrity::Verified(checked)
+    } else {
+        Integrity::Unchecked
+    }
+}
@@ -0,0 +1,131 @@
+use color_eyre::{eyre::eyre, Result};
+
+use crate::controller::ControllerType;
+
+/// The system clipboard, connected on first use and kept open so copied text outlives the copy
+#[derive(Default)]
+pub struct SystemClipboard {
+    inner: Option<arboard::Clipboard>,
+}
+
+impl SystemClipboard {
+    fn connect(&mut self) -> Result<&mut arboard::Clipboard> {
+        if self.inner.is_none() {
+            self.inner = Some(arboard::Clipboard::new().map_err(|e| eyre!("No clipboard: {}", e))?);
+        }
+        Ok(self.inner.as_mut().unwrap())
+    }
+
+    pub fn copy(&mut self, text: String) -> Result<()> {
+        self.connect()?.set_text(text).map_err(|e| eyre!("Copy failed: {}", e))
+    }
+
+    pub fn paste(&mut self) -> Result<String> {
+        self.connect()?.get_text().map_err(|e| eyre!("Clipboard holds no text: {}", e))
+    }
+}
+
+/// Input bytes of each port, in the order of the text
+pub type PortBytes = Vec<(u8, Vec<u8>)>;
+
+/// Whether every bit of a frame is a button, so the mnemonic alone describes it
+fn buttons_only(controller: ControllerType) -> bool {
+    let size = controller.frame_size();
+    size > 0 && (0..size).all(|byte| {
+        controller.buttons().iter()
+            .filter(|button| button.byte == byte)
+            .fold(0, |mask, button| mask | button.mask) == 0xFF
+    })
+}
+
+/// One frame of one port: a glyph per button (`.` when released), then the raw bytes in hex
+/// when the buttons don't cover the whole frame
+fn format_field(controller: ControllerType, frame: &[u8]) -> String {
+    let mnemonic: String = controller.buttons().iter()
+        .map(|button| if controller.is_pressed(button, frame) { button.glyph } else { "." })
+        .collect();
+    if buttons_only(controller) {
+        return mnemonic;
+    }
+    let hex: Vec<String> = frame.iter().map(|byte| format!("{:02X}", byte)).collect();
+    format!("{} {}", mnemonic, hex.join(" ")).trim().to_string()
+}
+
+/// Read one frame of one port back from `format_field`, preferring the raw bytes when present
+fn parse_field(controller: ControllerType, field: &str) -> Result<Vec<u8>> {
+    let size = controller.frame_size();
+    let buttons = controller.buttons();
+    let tokens: Vec<&str> = field.split_whitespace().collect();
+    let is_hex = |tokens: &[&str]| tokens.len() == size && tokens.iter().all(|t| t.len() == 2 && u8::from_str_radix(t, 16).is_ok());
+    let (mnemonic, hex) = match tokens.as_slice() {
+        [mnemonic, hex @ ..] if is_hex(hex) && !buttons.is_empty() => (Some(*mnemonic), hex),
+        hex if is_hex(hex) && !buttons_only(controller) => (None, hex),
+        // Analog values are left neutral when only the buttons are given
+        [mnemonic] if !buttons.is_empty() => (Some(*mnemonic), &[][..]),
+        _ => return Err(eyre!("Not a {} frame: {}", controller.name(), field.trim())),
+    };
+
+    if !hex.is_empty() {
+        return Ok(hex.iter().map(|byte| u8::from_str_radix(byte, 16).unwrap_or_default()).collect());
+    }
+    let Some(mnemonic) = mnemonic.filter(|mnemonic| mnemonic.chars().count() == buttons.len()) else {
+        return Err(eyre!("Expected {} buttons for a {}: {}", buttons.len(), controller.name(), field.trim()));
+    };
+    let mut frame = controller.neutral_frame();
+    for (button, glyph) in buttons.iter().zip(mnemonic.chars()) {
+        if glyph != '.' {
+            frame[button.byte] ^= button.mask;
+        }
+    }
+    Ok(frame)
+}
+
+/// Frames as text, one line per frame with a `|`-separated field per port, under a header naming the ports
+pub fn format_frames(frames: usize, ports: &[(u8, ControllerType, &[u8])]) -> String {
+    let header: Vec<String> = ports.iter().map(|(port, _, _)| format!("Port {}", port)).collect();
+    let mut lines = vec![header.join(" | ")];
+    for frame in 0..frames {
+        let fields: Vec<String> = ports.iter()
+            .map(|(_, controller, bytes)| {
+                let size = controller.frame_size();
+                let input = bytes.get(frame * size..(frame + 1) * size).unwrap_or(&[]);
+                format_field(*controller, input)
+            })
+            .collect();
+        lines.push(fields.join(" | "));
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Read frames written by `format_frames`; without a header, fields go to the ports in order
+pub fn parse_frames(text: &str, ports: &[(u8, ControllerType)]) -> Result<(usize, PortBytes)> {
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty()).peekable();
+
+    let mut order: Vec<(u8, ControllerType)> = ports.to_vec();
+    if let Some(header) = lines.next_if(|line| line.trim_start().starts_with("Port")) {
+        order = header.split('|')
+            .map(|name| {
+                let port: u8 = name.trim().trim_start_matches("Port").trim().parse()
+                    .map_err(|_| eyre!("Invalid port in header: {}", name.trim()))?;
+                ports.iter().find(|(p, _)| *p == port).copied().ok_or_else(|| eyre!("No port {}", port))
+            })
+            .collect::<Result<_>>()?;
+    }
+
+    let mut result: PortBytes = order.iter().map(|(port, _)| (*port, Vec::new())).collect();
+    let mut frames = 0;
+    for (number, line) in lines.enumerate() {
+        let fields: Vec<&str> = line.split('|').collect();
+        if fields.len() != order.len() {
+            return Err(eyre!("Line {}: expected {} ports, got {}", number + 1, order.len(), fields.len()));
+        }
+        for ((_, controller), (field, (_, bytes))) in order.iter().zip(fields.iter().zip(result.iter_mut())) {
+            bytes.extend(parse_field(*controller, field).map_err(|e| eyre!("Line {}: {}", number + 1, e))?);
+        }
+        frames += 1;
+    }
+    if frames == 0 {
+        return Err(eyre!("No frames in the clipboard"));
+    }
+    Ok((frames, result))
+}
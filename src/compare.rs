@@ -0,0 +1,166 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use color_eyre::Result;
+use tasd_lib::{Packet, TASD};
+
+use crate::checkpoint::port_inputs;
+use crate::controller::{self, ControllerType};
+use crate::diff::{self, Edit};
+use crate::input_index::InputIndex;
+
+/// A frame that differs between two movies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Difference {
+    /// Frame of the first movie the difference is shown at
+    pub at: usize,
+    /// Frame in the first movie, or None if it only exists in the second
+    pub a: Option<usize>,
+    /// Frame in the second movie, or None if it only exists in the first
+    pub b: Option<usize>,
+}
+
+/// Frame-by-frame differences of one port
+pub struct PortDiff {
+    pub port: u8,
+    /// Frames of the port in each movie
+    pub frames: (usize, usize),
+    /// Differences in the order of the first movie
+    pub differences: Vec<Difference>,
+    /// Frames of the first movie from which the second is shifted by an amount, when aligned by content
+    shifts: Vec<(usize, isize)>,
+}
+
+impl PortDiff {
+    /// Whether a frame of the first movie is shown as different
+    pub fn differs(&self, frame: usize) -> bool {
+        self.differences.binary_search_by_key(&frame, |d| d.at).is_ok()
+    }
+
+    /// Frame of the second movie lined up with a frame of the first
+    pub fn other_frame(&self, frame: usize) -> Option<usize> {
+        let first = self.differences.partition_point(|d| d.at < frame);
+        if let Some(d) = self.differences[first..].iter().take_while(|d| d.at == frame).find(|d| d.a == Some(frame)) {
+            return d.b;
+        }
+        let shift = self.shifts[..self.shifts.partition_point(|(start, _)| *start <= frame)]
+            .last()
+            .map_or(0, |(_, shift)| *shift);
+        frame.checked_add_signed(shift).filter(|other| *other < self.frames.1)
+    }
+}
+
+/// Line up the input of every port of two movies and list the frames that differ.
+/// With `align_content`, frames are matched by content so insertions don't shift everything after them.
+pub fn compare(a: &[Packet], b: &[Packet], align_content: bool) -> Vec<PortDiff> {
+    let ports: BTreeSet<u8> = a.iter().chain(b.iter())
+        .filter_map(|packet| match packet {
+            Packet::InputChunk(chunk) => Some(chunk.port),
+            _ => None,
+        })
+        .collect();
+
+    let mut result = Vec::new();
+    for port in ports {
+        let a_inputs = port_inputs(a, port);
+        let b_inputs = port_inputs(b, port);
+        let a_frames: Vec<&[u8]> = a_inputs.chunks(controller::detect_controller(a, port).frame_size().max(1)).collect();
+        let b_frames: Vec<&[u8]> = b_inputs.chunks(controller::detect_controller(b, port).frame_size().max(1)).collect();
+        // Frames past the end of the first movie are shown on its last frame
+        let last = a_frames.len().saturating_sub(1);
+
+        let mut differences = Vec::new();
+        let mut shifts = Vec::new();
+        if align_content {
+            let (mut i, mut j) = (0, 0);
+            for edit in diff::align(&a_frames, &b_frames) {
+                match edit {
+                    Edit::Keep => {
+                        let shift = j as isize - i as isize;
+                        if shifts.last().map_or(0, |(_, last)| *last) != shift {
+                            shifts.push((i, shift));
+                        }
+                        i += 1;
+                        j += 1;
+                    }
+                    Edit::Delete => {
+                        differences.push(Difference { at: i, a: Some(i), b: None });
+                        i += 1;
+                    }
+                    Edit::Insert => {
+                        differences.push(Difference { at: i.min(last), a: None, b: Some(j) });
+                        j += 1;
+                    }
+                }
+            }
+        } else {
+            for i in 0..a_frames.len().max(b_frames.len()) {
+                if a_frames.get(i) != b_frames.get(i) {
+                    differences.push(Difference {
+                        at: i.min(last),
+                        a: (i < a_frames.len()).then_some(i),
+                        b: (i < b_frames.len()).then_some(i),
+                    });
+                }
+            }
+        }
+
+        result.push(PortDiff { port, frames: (a_frames.len(), b_frames.len()), differences, shifts });
+    }
+    result
+}
+
+/// A movie opened with `:diff`, compared against the open file
+pub struct FileDiff {
+    /// Name shown in the table header
+    pub name: String,
+    pub tasd: TASD,
+    pub index: InputIndex,
+    pub controllers: HashMap<u8, ControllerType>,
+    pub ports: Vec<PortDiff>,
+    /// Frames of the open file with a difference on any port, sorted
+    pub frames: Vec<usize>,
+}
+
+impl FileDiff {
+    pub fn open(path: &Path) -> Result<Self> {
+        let tasd = crate::load_tasd(path)?;
+        let ports: BTreeSet<u8> = tasd.packets.iter()
+            .filter_map(|packet| match packet {
+                Packet::InputChunk(chunk) => Some(chunk.port),
+                _ => None,
+            })
+            .collect();
+        let controllers = ports.into_iter()
+            .map(|port| (port, controller::detect_controller(&tasd.packets, port)))
+            .collect();
+        Ok(Self {
+            name: path.file_name().unwrap_or(path.as_os_str()).to_string_lossy().to_string(),
+            index: InputIndex::build(&tasd.packets),
+            tasd,
+            controllers,
+            ports: Vec::new(),
+            frames: Vec::new(),
+        })
+    }
+
+    /// Compare again after the open file changed
+    pub fn update(&mut self, packets: &[Packet], align_content: bool) {
+        self.ports = compare(packets, &self.tasd.packets, align_content);
+        self.frames = self.ports.iter()
+            .flat_map(|diff| diff.differences.iter().map(|d| d.at))
+            .collect::<BTreeSet<usize>>()
+            .into_iter()
+            .collect();
+    }
+
+    pub fn port(&self, port: u8) -> Option<&PortDiff> {
+        self.ports.iter().find(|diff| diff.port == port)
+    }
+
+    /// Input of the second movie lined up with a frame of the open file
+    pub fn other_input(&self, port: u8, frame: usize) -> Option<Vec<u8>> {
+        let other = self.port(port)?.other_frame(frame)?;
+        let size = self.controllers.get(&port).copied().unwrap_or(ControllerType::NesStandard).frame_size();
+        self.index.frame(&self.tasd.packets, port, other, size)
+    }
+}
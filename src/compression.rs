@@ -0,0 +1,89 @@
+use std::io::{Read, Write};
+use std::path::Path;
+use color_eyre::{eyre::eyre, Result};
+
+/// Compression formats recognised when opening files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+    Xz,
+}
+
+impl Compression {
+    /// Identify the format from the leading magic bytes
+    pub fn detect(data: &[u8]) -> Self {
+        if data.starts_with(&[0x1F, 0x8B]) {
+            Compression::Gzip
+        } else if data.starts_with(&[0x28, 0xB5, 0x2F, 0xFD]) {
+            Compression::Zstd
+        } else if data.starts_with(&[0xFD, b'7', b'z', b'X', b'Z', 0x00]) {
+            Compression::Xz
+        } else {
+            Compression::None
+        }
+    }
+
+    /// Pick the format implied by a file extension (`.gz`, `.zst`, `.xz`)
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("gz") => Compression::Gzip,
+            Some("zst") => Compression::Zstd,
+            Some("xz") => Compression::Xz,
+            _ => Compression::None,
+        }
+    }
+}
+
+/// Compress data in the given format, using the format's default level if none is set
+pub fn compress(data: Vec<u8>, format: Compression, level: Option<i32>) -> Result<Vec<u8>> {
+    match format {
+        Compression::None => Ok(data),
+        Compression::Gzip => {
+            let level = level.unwrap_or(6);
+            if !(0..=9).contains(&level) {
+                return Err(eyre!("gzip level must be between 0 and 9"));
+            }
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::new(level as u32));
+            encoder.write_all(&data)?;
+            Ok(encoder.finish()?)
+        }
+        Compression::Zstd => {
+            let level = level.unwrap_or(zstd::DEFAULT_COMPRESSION_LEVEL);
+            if !zstd::compression_level_range().contains(&level) {
+                return Err(eyre!("zstd level must be between 1 and 22"));
+            }
+            Ok(zstd::encode_all(data.as_slice(), level)?)
+        }
+        Compression::Xz => {
+            let level = level.unwrap_or(6);
+            if !(0..=9).contains(&level) {
+                return Err(eyre!("xz level must be between 0 and 9"));
+            }
+            let mut encoder = xz2::write::XzEncoder::new(Vec::new(), level as u32);
+            encoder.write_all(&data)?;
+            Ok(encoder.finish()?)
+        }
+    }
+}
+
+/// Decompress file contents if they carry a known compression header
+pub fn decompress(data: Vec<u8>) -> Result<Vec<u8>> {
+    let mut output = Vec::new();
+
+    match Compression::detect(&data) {
+        Compression::None => return Ok(data),
+        Compression::Gzip => {
+            flate2::read::GzDecoder::new(data.as_slice()).read_to_end(&mut output)?;
+        }
+        Compression::Zstd => {
+            zstd::stream::read::Decoder::new(data.as_slice())?.read_to_end(&mut output)?;
+        }
+        Compression::Xz => {
+            xz2::read::XzDecoder::new(data.as_slice()).read_to_end(&mut output)?;
+        }
+    }
+
+    Ok(output)
+}
@@ -0,0 +1,200 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use color_eyre::{eyre::eyre, Result};
+use crate::controller::N64Accessory;
+
+/// A column of the input table, as named in `:set columns=`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    /// Frame number
+    Frame,
+    /// Lag frames and resets
+    Events,
+    /// Every port not listed on its own
+    Ports,
+    /// One port's input
+    Port(u8),
+    /// Raw bytes of the shown ports
+    Hex,
+    /// Time of the frame
+    Timestamp,
+}
+
+impl Column {
+    fn parse(name: &str) -> Result<Self> {
+        Ok(match name {
+            "frame" => Column::Frame,
+            "events" => Column::Events,
+            "ports" => Column::Ports,
+            "hex" => Column::Hex,
+            "timestamp" | "time" => Column::Timestamp,
+            _ => match name.strip_prefix("port").map(str::parse) {
+                Some(Ok(port)) => Column::Port(port),
+                _ => return Err(eyre!("Unknown column: {} (frame, events, ports, portN, hex or timestamp)", name)),
+            },
+        })
+    }
+}
+
+/// User configuration, loaded from `~/.config/tasd-cli/config`
+///
+/// The file holds one `name = value` setting per line, using the same names
+/// as the `:set` command. Lines starting with `#` are comments.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    /// Framerate override used for time conversion (None = derive from console/region)
+    pub framerate: Option<f64>,
+    /// Store input checksums in the file when saving
+    pub checksums: bool,
+    /// Match frames by content instead of index when diffing
+    pub align_diff: bool,
+    /// Merge chunks and drop repeated declarations when saving
+    pub optimize: bool,
+    /// Remember `:order` port arrangements per file
+    pub remember_order: bool,
+    /// Name credited by `:stamp` when verifying a movie
+    pub verifier: Option<String>,
+    /// Secret key used to sign the file on save (from `tasd-cli keygen`)
+    pub sign_key: Option<PathBuf>,
+    /// Compression level for `.gz`/`.zst`/`.xz` saves (None = format default)
+    pub compress_level: Option<i32>,
+    /// Command that renders packets for the sidebar (see `plugins::CommandRenderer`)
+    pub renderer: Option<String>,
+    /// Show a column marking lag frames and resets in the input table
+    pub events: bool,
+    /// Show a strip next to the input table shading how many buttons are held over the whole movie
+    pub heatmap: bool,
+    /// Open damaged files without asking, skipping the packets that can't be parsed
+    pub lenient: bool,
+    /// Block every edit and saving over the open file
+    pub readonly: bool,
+    /// Columns of the input table, in order (None = frame number and every port)
+    pub columns: Option<Vec<Column>>,
+    /// N64 accessories plugged into the playback hardware, by port; files declaring others get a warning
+    pub accessories: BTreeMap<u8, N64Accessory>,
+}
+
+impl Config {
+    /// Location of the config file
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("tasd-cli").join("config"))
+    }
+
+    /// Load the config file, falling back to defaults if it doesn't exist
+    pub fn load() -> Result<Self> {
+        let mut config = Config::default();
+
+        let Some(path) = Config::path() else {
+            return Ok(config);
+        };
+        if !path.exists() {
+            return Ok(config);
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+        for (line_num, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (name, value) = line.split_once('=').unwrap_or((line, ""));
+            config.set(name.trim(), value.trim())
+                .map_err(|e| eyre!("{}:{}: {}", path.display(), line_num + 1, e))?;
+        }
+
+        Ok(config)
+    }
+
+    /// Write a setting to the config file, replacing the line that set it before
+    pub fn save(name: &str, value: &str) -> Result<()> {
+        let path = Config::path().ok_or_else(|| eyre!("No config directory"))?;
+        let content = std::fs::read_to_string(&path).unwrap_or_default();
+
+        let setting = format!("{} = {}", name, value);
+        let mut replaced = false;
+        let mut lines: Vec<String> = content.lines()
+            .map(|line| match line.split_once('=') {
+                Some((key, _)) if key.trim() == name && !line.trim_start().starts_with('#') => {
+                    replaced = true;
+                    setting.clone()
+                }
+                _ => line.to_string(),
+            })
+            .collect();
+        if !replaced {
+            lines.push(setting);
+        }
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, lines.join("\n") + "\n")?;
+        Ok(())
+    }
+
+    /// Apply a single `name = value` setting
+    pub fn set(&mut self, name: &str, value: &str) -> Result<()> {
+        match name {
+            "framerate" | "fps" => {
+                if value.is_empty() || value == "auto" {
+                    self.framerate = None;
+                } else {
+                    let framerate: f64 = value.parse()
+                        .map_err(|_| eyre!("Invalid framerate: {}", value))?;
+                    if !framerate.is_finite() || framerate <= 0.0 {
+                        return Err(eyre!("Framerate must be positive: {}", value));
+                    }
+                    self.framerate = Some(framerate);
+                }
+            }
+            "checksums" => self.checksums = parse_bool(value)?,
+            "aligndiff" => self.align_diff = parse_bool(value)?,
+            "optimize" => self.optimize = parse_bool(value)?,
+            "rememberorder" => self.remember_order = parse_bool(value)?,
+            "events" => self.events = parse_bool(value)?,
+            "heatmap" => self.heatmap = parse_bool(value)?,
+            "lenient" => self.lenient = parse_bool(value)?,
+            "readonly" => self.readonly = parse_bool(value)?,
+            "signkey" => self.sign_key = Some(PathBuf::from(value)).filter(|_| !value.is_empty()),
+            "renderer" => self.renderer = Some(value.to_string()).filter(|command| !command.is_empty()),
+            "verifier" => self.verifier = Some(value.to_string()).filter(|name| !name.is_empty()),
+            "columns" => {
+                self.columns = if value.is_empty() || value == "default" {
+                    None
+                } else {
+                    Some(value.split(',').map(|name| Column::parse(name.trim())).collect::<Result<_>>()?)
+                };
+            }
+            "accessories" => {
+                let mut accessories = BTreeMap::new();
+                for pair in value.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+                    let parsed = pair.split_once('=')
+                        .and_then(|(port, name)| Some((port.trim().parse().ok()?, N64Accessory::parse(name.trim())?)));
+                    let (port, accessory) = parsed
+                        .ok_or_else(|| eyre!("Expected PORT=none|rumble|cpak|tpak, got '{}'", pair))?;
+                    accessories.insert(port, accessory);
+                }
+                self.accessories = accessories;
+            }
+            "compresslevel" => {
+                self.compress_level = if value.is_empty() || value == "default" {
+                    None
+                } else {
+                    Some(value.parse().map_err(|_| eyre!("Invalid compression level: {}", value))?)
+                };
+            }
+            _ => return Err(eyre!("Unknown option: {}", name)),
+        }
+        Ok(())
+    }
+}
+
+/// Parse an on/off style value (an empty value means "on")
+fn parse_bool(value: &str) -> Result<bool> {
+    match value {
+        "" | "on" | "true" | "yes" | "1" => Ok(true),
+        "off" | "false" | "no" | "0" => Ok(false),
+        _ => Err(eyre!("Expected on or off: {}", value)),
+    }
+}
@@ -0,0 +1,637 @@
+use tasd_lib::{Console, Packet};
+
+/// Controller types declared by PortController packets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControllerType {
+    /// Standard NES controller (1 byte per frame)
+    NesStandard,
+    /// NES Zapper light gun (1 byte per frame)
+    NesZapper,
+    /// Arkanoid "Vaus" paddle (2 bytes per frame). It has no PortController code, so a port is
+    /// only read as one when chosen with `:controller`, keeping the code the file declares
+    NesArkanoid(u16),
+    /// Standard SNES controller (2 bytes per frame)
+    SnesStandard,
+    /// SNES Mouse (4 bytes per frame)
+    SnesMouse,
+    /// SNES Super Scope (3 bytes per frame)
+    SnesSuperScope,
+    /// N64 controller with an optional accessory (4 bytes per frame)
+    N64(N64Accessory),
+    /// GameCube controller (8 bytes per frame)
+    GameCube,
+    /// Game Boy buttons (1 byte per frame)
+    GameBoy,
+    /// Game Boy Color buttons (1 byte per frame)
+    GameBoyColor,
+    /// Game Boy Advance buttons, with L and R (2 bytes per frame)
+    GameBoyAdvance,
+    /// Sega Genesis 3-button pad (1 byte per frame)
+    Genesis3,
+    /// Sega Genesis 6-button pad (2 bytes per frame)
+    Genesis6,
+    /// Any controller type this tool doesn't decode yet
+    Other(u16),
+}
+
+/// Accessory inserted into an N64 controller's expansion slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum N64Accessory {
+    None,
+    RumblePak,
+    ControllerPak,
+    TransferPak,
+}
+
+impl N64Accessory {
+    pub fn name(&self) -> &'static str {
+        match self {
+            N64Accessory::None => "None",
+            N64Accessory::RumblePak => "Rumble Pak",
+            N64Accessory::ControllerPak => "Controller Pak",
+            N64Accessory::TransferPak => "Transfer Pak",
+        }
+    }
+
+    /// Accessory named in `:set accessories`, like `rumble` or `cpak`
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(N64Accessory::None),
+            "rumble" => Some(N64Accessory::RumblePak),
+            "cpak" | "controller" => Some(N64Accessory::ControllerPak),
+            "tpak" | "transfer" => Some(N64Accessory::TransferPak),
+            _ => None,
+        }
+    }
+}
+
+/// A single button bit within a controller frame
+#[derive(Debug, Clone, Copy)]
+pub struct Button {
+    /// Name used in help text and detail views
+    pub name: &'static str,
+    /// Glyph shown in the input table
+    pub glyph: &'static str,
+    /// Byte offset within the frame
+    pub byte: usize,
+    /// Bit mask within that byte
+    pub mask: u8,
+}
+
+const NES_BUTTONS: &[Button] = &[
+    Button { name: "Up", glyph: "↑", byte: 0, mask: 0x10 },
+    Button { name: "Down", glyph: "↓", byte: 0, mask: 0x20 },
+    Button { name: "Left", glyph: "←", byte: 0, mask: 0x40 },
+    Button { name: "Right", glyph: "→", byte: 0, mask: 0x80 },
+    Button { name: "A", glyph: "A", byte: 0, mask: 0x01 },
+    Button { name: "B", glyph: "B", byte: 0, mask: 0x02 },
+    Button { name: "Select", glyph: "S", byte: 0, mask: 0x04 },
+    Button { name: "Start", glyph: "T", byte: 0, mask: 0x08 },
+];
+
+const ZAPPER_BUTTONS: &[Button] = &[
+    Button { name: "Trigger", glyph: "T", byte: 0, mask: 0x10 },
+    Button { name: "Light", glyph: "L", byte: 0, mask: 0x08 },
+];
+
+const ARKANOID_BUTTONS: &[Button] = &[
+    Button { name: "Fire", glyph: "F", byte: 1, mask: 0x01 },
+];
+
+/// SNES buttons in the order the console shifts them out, B first
+const SNES_BUTTONS: &[Button] = &[
+    Button { name: "Up", glyph: "↑", byte: 0, mask: 0x10 },
+    Button { name: "Down", glyph: "↓", byte: 0, mask: 0x20 },
+    Button { name: "Left", glyph: "←", byte: 0, mask: 0x40 },
+    Button { name: "Right", glyph: "→", byte: 0, mask: 0x80 },
+    Button { name: "A", glyph: "A", byte: 1, mask: 0x01 },
+    Button { name: "B", glyph: "B", byte: 0, mask: 0x01 },
+    Button { name: "X", glyph: "X", byte: 1, mask: 0x02 },
+    Button { name: "Y", glyph: "Y", byte: 0, mask: 0x02 },
+    Button { name: "L", glyph: "L", byte: 1, mask: 0x04 },
+    Button { name: "R", glyph: "R", byte: 1, mask: 0x08 },
+    Button { name: "Select", glyph: "S", byte: 0, mask: 0x04 },
+    Button { name: "Start", glyph: "T", byte: 0, mask: 0x08 },
+];
+
+const SNES_MOUSE_BUTTONS: &[Button] = &[
+    Button { name: "Left", glyph: "L", byte: 1, mask: 0x02 },
+    Button { name: "Right", glyph: "R", byte: 1, mask: 0x01 },
+];
+
+const SUPER_SCOPE_BUTTONS: &[Button] = &[
+    Button { name: "Fire", glyph: "F", byte: 2, mask: 0x01 },
+    Button { name: "Cursor", glyph: "C", byte: 2, mask: 0x02 },
+    Button { name: "Turbo", glyph: "T", byte: 2, mask: 0x04 },
+    Button { name: "Pause", glyph: "P", byte: 2, mask: 0x08 },
+];
+
+/// N64 buttons, in the layout shared with Mupen64 movies; C buttons use ASCII arrows
+const N64_BUTTONS: &[Button] = &[
+    Button { name: "Up", glyph: "↑", byte: 0, mask: 0x08 },
+    Button { name: "Down", glyph: "↓", byte: 0, mask: 0x04 },
+    Button { name: "Left", glyph: "←", byte: 0, mask: 0x02 },
+    Button { name: "Right", glyph: "→", byte: 0, mask: 0x01 },
+    Button { name: "A", glyph: "A", byte: 0, mask: 0x80 },
+    Button { name: "B", glyph: "B", byte: 0, mask: 0x40 },
+    Button { name: "Z", glyph: "Z", byte: 0, mask: 0x20 },
+    Button { name: "Start", glyph: "S", byte: 0, mask: 0x10 },
+    Button { name: "L", glyph: "L", byte: 1, mask: 0x20 },
+    Button { name: "R", glyph: "R", byte: 1, mask: 0x10 },
+    Button { name: "C-Up", glyph: "^", byte: 1, mask: 0x08 },
+    Button { name: "C-Down", glyph: "v", byte: 1, mask: 0x04 },
+    Button { name: "C-Left", glyph: "<", byte: 1, mask: 0x02 },
+    Button { name: "C-Right", glyph: ">", byte: 1, mask: 0x01 },
+];
+
+const GAMECUBE_BUTTONS: &[Button] = &[
+    Button { name: "A", glyph: "A", byte: 0, mask: 0x01 },
+    Button { name: "B", glyph: "B", byte: 0, mask: 0x02 },
+    Button { name: "X", glyph: "X", byte: 0, mask: 0x04 },
+    Button { name: "Y", glyph: "Y", byte: 0, mask: 0x08 },
+    Button { name: "Start", glyph: "S", byte: 0, mask: 0x10 },
+    Button { name: "Z", glyph: "Z", byte: 1, mask: 0x10 },
+    Button { name: "L", glyph: "L", byte: 1, mask: 0x40 },
+    Button { name: "R", glyph: "R", byte: 1, mask: 0x20 },
+    Button { name: "Up", glyph: "↑", byte: 1, mask: 0x08 },
+    Button { name: "Down", glyph: "↓", byte: 1, mask: 0x04 },
+    Button { name: "Left", glyph: "←", byte: 1, mask: 0x01 },
+    Button { name: "Right", glyph: "→", byte: 1, mask: 0x02 },
+];
+
+/// Game Boy buttons, in the layout shared with `.vbm` movies; the GBA adds L and R in a second byte
+const GB_BUTTONS: &[Button] = &[
+    Button { name: "Up", glyph: "↑", byte: 0, mask: 0x40 },
+    Button { name: "Down", glyph: "↓", byte: 0, mask: 0x80 },
+    Button { name: "Left", glyph: "←", byte: 0, mask: 0x20 },
+    Button { name: "Right", glyph: "→", byte: 0, mask: 0x10 },
+    Button { name: "A", glyph: "A", byte: 0, mask: 0x01 },
+    Button { name: "B", glyph: "B", byte: 0, mask: 0x02 },
+    Button { name: "Select", glyph: "S", byte: 0, mask: 0x04 },
+    Button { name: "Start", glyph: "T", byte: 0, mask: 0x08 },
+];
+
+const GBA_BUTTONS: &[Button] = &[
+    Button { name: "Up", glyph: "↑", byte: 0, mask: 0x40 },
+    Button { name: "Down", glyph: "↓", byte: 0, mask: 0x80 },
+    Button { name: "Left", glyph: "←", byte: 0, mask: 0x20 },
+    Button { name: "Right", glyph: "→", byte: 0, mask: 0x10 },
+    Button { name: "A", glyph: "A", byte: 0, mask: 0x01 },
+    Button { name: "B", glyph: "B", byte: 0, mask: 0x02 },
+    Button { name: "L", glyph: "L", byte: 1, mask: 0x02 },
+    Button { name: "R", glyph: "R", byte: 1, mask: 0x01 },
+    Button { name: "Select", glyph: "S", byte: 0, mask: 0x04 },
+    Button { name: "Start", glyph: "T", byte: 0, mask: 0x08 },
+];
+
+/// Genesis 3-button pad; the 6-button pad adds X, Y, Z and Mode in a second byte
+const GENESIS3_BUTTONS: &[Button] = &[
+    Button { name: "Up", glyph: "↑", byte: 0, mask: 0x01 },
+    Button { name: "Down", glyph: "↓", byte: 0, mask: 0x02 },
+    Button { name: "Left", glyph: "←", byte: 0, mask: 0x04 },
+    Button { name: "Right", glyph: "→", byte: 0, mask: 0x08 },
+    Button { name: "A", glyph: "A", byte: 0, mask: 0x10 },
+    Button { name: "B", glyph: "B", byte: 0, mask: 0x20 },
+    Button { name: "C", glyph: "C", byte: 0, mask: 0x40 },
+    Button { name: "Start", glyph: "T", byte: 0, mask: 0x80 },
+];
+
+const GENESIS6_BUTTONS: &[Button] = &[
+    Button { name: "Up", glyph: "↑", byte: 0, mask: 0x01 },
+    Button { name: "Down", glyph: "↓", byte: 0, mask: 0x02 },
+    Button { name: "Left", glyph: "←", byte: 0, mask: 0x04 },
+    Button { name: "Right", glyph: "→", byte: 0, mask: 0x08 },
+    Button { name: "A", glyph: "A", byte: 0, mask: 0x10 },
+    Button { name: "B", glyph: "B", byte: 0, mask: 0x20 },
+    Button { name: "C", glyph: "C", byte: 0, mask: 0x40 },
+    Button { name: "X", glyph: "X", byte: 1, mask: 0x01 },
+    Button { name: "Y", glyph: "Y", byte: 1, mask: 0x02 },
+    Button { name: "Z", glyph: "Z", byte: 1, mask: 0x04 },
+    Button { name: "Mode", glyph: "M", byte: 1, mask: 0x08 },
+    Button { name: "Start", glyph: "T", byte: 0, mask: 0x80 },
+];
+
+impl ControllerType {
+    /// Map a PortController type code to a controller type
+    pub fn from_code(code: u16) -> Self {
+        match code {
+            0x0101 => ControllerType::NesStandard,
+            0x0103 => ControllerType::NesZapper,
+            0x0201 => ControllerType::SnesStandard,
+            0x0203 => ControllerType::SnesMouse,
+            0x0204 => ControllerType::SnesSuperScope,
+            0x0301 => ControllerType::N64(N64Accessory::None),
+            0x0302 => ControllerType::N64(N64Accessory::RumblePak),
+            0x0303 => ControllerType::N64(N64Accessory::ControllerPak),
+            0x0304 => ControllerType::N64(N64Accessory::TransferPak),
+            0x0401 => ControllerType::GameCube,
+            0x0501 => ControllerType::GameBoy,
+            0x0601 => ControllerType::GameBoyColor,
+            0x0701 => ControllerType::GameBoyAdvance,
+            0x0801 => ControllerType::Genesis3,
+            0x0802 => ControllerType::Genesis6,
+            other => ControllerType::Other(other),
+        }
+    }
+
+    /// PortController type code of this controller type
+    pub fn code(&self) -> u16 {
+        match self {
+            ControllerType::NesStandard => 0x0101,
+            ControllerType::NesZapper => 0x0103,
+            ControllerType::NesArkanoid(code) => *code,
+            ControllerType::SnesStandard => 0x0201,
+            ControllerType::SnesMouse => 0x0203,
+            ControllerType::SnesSuperScope => 0x0204,
+            ControllerType::N64(N64Accessory::None) => 0x0301,
+            ControllerType::N64(N64Accessory::RumblePak) => 0x0302,
+            ControllerType::N64(N64Accessory::ControllerPak) => 0x0303,
+            ControllerType::N64(N64Accessory::TransferPak) => 0x0304,
+            ControllerType::GameCube => 0x0401,
+            ControllerType::GameBoy => 0x0501,
+            ControllerType::GameBoyColor => 0x0601,
+            ControllerType::GameBoyAdvance => 0x0701,
+            ControllerType::Genesis3 => 0x0801,
+            ControllerType::Genesis6 => 0x0802,
+            ControllerType::Other(code) => *code,
+        }
+    }
+
+    /// Human-readable controller name
+    pub fn name(&self) -> String {
+        match self {
+            ControllerType::NesStandard => "NES Controller".to_string(),
+            ControllerType::NesZapper => "NES Zapper".to_string(),
+            ControllerType::NesArkanoid(_) => "Arkanoid Paddle".to_string(),
+            ControllerType::SnesStandard => "SNES Controller".to_string(),
+            ControllerType::SnesMouse => "SNES Mouse".to_string(),
+            ControllerType::SnesSuperScope => "Super Scope".to_string(),
+            ControllerType::N64(N64Accessory::None) => "N64 Controller".to_string(),
+            ControllerType::N64(accessory) => format!("N64 Controller + {}", accessory.name()),
+            ControllerType::GameCube => "GameCube Controller".to_string(),
+            ControllerType::GameBoy => "Game Boy".to_string(),
+            ControllerType::GameBoyColor => "Game Boy Color".to_string(),
+            ControllerType::GameBoyAdvance => "Game Boy Advance".to_string(),
+            ControllerType::Genesis3 => "Genesis 3-Button Pad".to_string(),
+            ControllerType::Genesis6 => "Genesis 6-Button Pad".to_string(),
+            ControllerType::Other(code) => format!("Unknown (0x{:04X})", code),
+        }
+    }
+
+    /// Number of input bytes making up one frame
+    pub fn frame_size(&self) -> usize {
+        match self {
+            ControllerType::NesStandard => 1,
+            ControllerType::NesZapper => 1,
+            ControllerType::NesArkanoid(_) => 2,
+            ControllerType::SnesStandard => 2,
+            ControllerType::SnesMouse => 4,
+            ControllerType::SnesSuperScope => 3,
+            ControllerType::N64(_) => 4,
+            ControllerType::GameCube => 8,
+            ControllerType::GameBoy | ControllerType::GameBoyColor => 1,
+            ControllerType::GameBoyAdvance => 2,
+            ControllerType::Genesis3 => 1,
+            ControllerType::Genesis6 => 2,
+            ControllerType::Other(_) => 1,
+        }
+    }
+
+    /// Accessory declared for N64 controllers
+    pub fn accessory(&self) -> Option<N64Accessory> {
+        match self {
+            ControllerType::N64(accessory) => Some(*accessory),
+            _ => None,
+        }
+    }
+
+    /// Digital buttons reported by this controller
+    pub fn buttons(&self) -> &'static [Button] {
+        match self {
+            ControllerType::NesStandard => NES_BUTTONS,
+            ControllerType::NesZapper => ZAPPER_BUTTONS,
+            ControllerType::NesArkanoid(_) => ARKANOID_BUTTONS,
+            ControllerType::SnesStandard => SNES_BUTTONS,
+            ControllerType::SnesMouse => SNES_MOUSE_BUTTONS,
+            ControllerType::SnesSuperScope => SUPER_SCOPE_BUTTONS,
+            ControllerType::N64(_) => N64_BUTTONS,
+            ControllerType::GameCube => GAMECUBE_BUTTONS,
+            ControllerType::GameBoy | ControllerType::GameBoyColor => GB_BUTTONS,
+            ControllerType::GameBoyAdvance => GBA_BUTTONS,
+            ControllerType::Genesis3 => GENESIS3_BUTTONS,
+            ControllerType::Genesis6 => GENESIS6_BUTTONS,
+            ControllerType::Other(_) => &[],
+        }
+    }
+
+    /// Whether a cleared bit means "pressed" (shift-register controllers)
+    pub fn active_low(&self) -> bool {
+        match self {
+            ControllerType::NesStandard | ControllerType::SnesStandard | ControllerType::SnesMouse => true,
+            ControllerType::NesZapper
+            | ControllerType::NesArkanoid(_)
+            | ControllerType::SnesSuperScope
+            | ControllerType::N64(_)
+            | ControllerType::GameCube
+            | ControllerType::GameBoy
+            | ControllerType::GameBoyColor
+            | ControllerType::GameBoyAdvance
+            | ControllerType::Genesis3
+            | ControllerType::Genesis6
+            | ControllerType::Other(_) => false,
+        }
+    }
+
+    /// A frame with nothing pressed and any sticks centered
+    pub fn neutral_frame(&self) -> Vec<u8> {
+        match self {
+            ControllerType::GameCube => vec![0, 0, 128, 128, 128, 128, 0, 0],
+            _ if self.active_low() => vec![0xFF; self.frame_size()],
+            _ => vec![0; self.frame_size()],
+        }
+    }
+
+    /// Check whether a button is held in the given frame
+    pub fn is_pressed(&self, button: &Button, frame: &[u8]) -> bool {
+        match frame.get(button.byte) {
+            Some(byte) => (byte & button.mask != 0) != self.active_low(),
+            None => false,
+        }
+    }
+
+    /// Format one frame as a compact, fixed-width table cell
+    pub fn format_frame(&self, frame: &[u8]) -> String {
+        match self {
+            ControllerType::SnesMouse => {
+                let mouse = SnesMouse::decode(frame);
+                format!(
+                    "{} {} dx{:+4} dy{:+4}",
+                    if mouse.left { "L" } else { "·" },
+                    if mouse.right { "R" } else { "·" },
+                    mouse.dx,
+                    mouse.dy
+                )
+            }
+            ControllerType::SnesSuperScope => {
+                let scope = SuperScope::decode(frame);
+                let buttons = self.format_buttons(frame);
+                format!("{} ({:3},{:3})", buttons, scope.x, scope.y)
+            }
+            ControllerType::NesArkanoid(_) => {
+                let paddle = ArkanoidPaddle::decode(frame);
+                format!("{} pos {:3}", self.format_buttons(frame), paddle.position)
+            }
+            ControllerType::N64(_) => {
+                let pad = N64Pad::decode(frame);
+                format!("{} {} {:+4} {:+4}", self.format_buttons(frame), pad.direction(), pad.stick.0, pad.stick.1)
+            }
+            ControllerType::GameCube => {
+                let pad = GameCubePad::decode(frame);
+                format!(
+                    "{} St({:+4},{:+4}) C({:+4},{:+4}) L:{:3} R:{:3}",
+                    self.format_buttons(frame),
+                    pad.stick.0,
+                    pad.stick.1,
+                    pad.c_stick.0,
+                    pad.c_stick.1,
+                    pad.l_analog,
+                    pad.r_analog
+                )
+            }
+            _ if self.buttons().is_empty() => format_hex(frame),
+            _ => self.format_buttons(frame),
+        }
+    }
+
+    /// Format the digital buttons as a row of glyphs
+    pub fn format_buttons(&self, frame: &[u8]) -> String {
+        self.buttons()
+            .iter()
+            .map(|button| if self.is_pressed(button, frame) { button.glyph } else { "·" })
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
+
+    /// Placeholder cell for frames with no data
+    pub fn empty_frame(&self) -> String {
+        if self.buttons().is_empty() {
+            "--".to_string()
+        } else {
+            self.format_buttons(&[])
+        }
+    }
+
+    /// Detailed, multi-line description of one frame
+    pub fn describe_frame(&self, frame: &[u8]) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        match self {
+            ControllerType::SnesMouse => {
+                let mouse = SnesMouse::decode(frame);
+                lines.push(format!("Delta X: {:+}", mouse.dx));
+                lines.push(format!("Delta Y: {:+}", mouse.dy));
+                lines.push(format!("Sensitivity: {}", mouse.sensitivity));
+            }
+            ControllerType::SnesSuperScope => {
+                let scope = SuperScope::decode(frame);
+                lines.push(format!("Position: ({}, {})", scope.x, scope.y));
+            }
+            ControllerType::N64(_) => {
+                let pad = N64Pad::decode(frame);
+                lines.push(format!("Stick: ({:+}, {:+}) {}", pad.stick.0, pad.stick.1, pad.direction()));
+            }
+            ControllerType::GameCube => {
+                let pad = GameCubePad::decode(frame);
+                lines.push(format!("Stick: ({:+}, {:+})", pad.stick.0, pad.stick.1));
+                lines.push(format!("C-Stick: ({:+}, {:+})", pad.c_stick.0, pad.c_stick.1));
+                lines.push(format!("Triggers: L {} R {}", pad.l_analog, pad.r_analog));
+            }
+            ControllerType::NesArkanoid(_) => {
+                let paddle = ArkanoidPaddle::decode(frame);
+                lines.push(format!("Position: {} / 255", paddle.position));
+                lines.push(format!("[{}]", paddle.gauge(16)));
+            }
+            _ => {}
+        }
+
+        let pressed: Vec<&str> = self.buttons()
+            .iter()
+            .filter(|button| self.is_pressed(button, frame))
+            .map(|button| button.name)
+            .collect();
+        if !self.buttons().is_empty() {
+            lines.push(format!(
+                "Pressed: {}",
+                if pressed.is_empty() { "none".to_string() } else { pressed.join(" ") }
+            ));
+        }
+        lines.push(format!("Raw: {}", format_hex(frame)));
+
+        lines
+    }
+}
+
+/// Decoded Arkanoid paddle report
+///
+/// The first byte holds the potentiometer reading and the second byte carries
+/// the fire button. Zapper frames need no struct: they are just two button
+/// bits (trigger pulled, light sensed) stored as set-means-active.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ArkanoidPaddle {
+    pub position: u8,
+}
+
+impl ArkanoidPaddle {
+    pub fn decode(frame: &[u8]) -> Self {
+        Self {
+            position: frame.first().copied().unwrap_or(0),
+        }
+    }
+
+    /// Render the knob position as a fixed-width gauge
+    pub fn gauge(&self, width: usize) -> String {
+        let filled = self.position as usize * width / 255;
+        format!("{}{}", "#".repeat(filled), "·".repeat(width - filled))
+    }
+}
+
+/// Decoded N64 controller report
+///
+/// Bytes 0-1 hold the digital buttons and bytes 2-3 the signed stick X and Y,
+/// with positive Y pointing up.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct N64Pad {
+    pub stick: (i8, i8),
+}
+
+impl N64Pad {
+    pub fn decode(frame: &[u8]) -> Self {
+        let axis = |i: usize| frame.get(i).map(|v| *v as i8).unwrap_or(0);
+        Self { stick: (axis(2), axis(3)) }
+    }
+
+    /// Arrow pointing where the stick is held, or a dot inside the dead zone
+    pub fn direction(&self) -> &'static str {
+        const DEAD_ZONE: f64 = 8.0;
+        let (x, y) = (self.stick.0 as f64, self.stick.1 as f64);
+        if x.hypot(y) < DEAD_ZONE {
+            return "·";
+        }
+        // Eight sectors of 45 degrees, counter-clockwise from the right
+        let sector = ((y.atan2(x).to_degrees() + 360.0 + 22.5) / 45.0) as usize % 8;
+        ["→", "↗", "↑", "↖", "←", "↙", "↓", "↘"][sector]
+    }
+}
+
+/// Decoded GameCube controller report
+///
+/// Bytes 0-1 hold the digital buttons, followed by the main stick, C-stick
+/// and the two analog trigger values. Stick axes are centered on 128 and are
+/// stored here relative to center, with positive Y pointing up.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GameCubePad {
+    pub stick: (i16, i16),
+    pub c_stick: (i16, i16),
+    pub l_analog: u8,
+    pub r_analog: u8,
+}
+
+impl GameCubePad {
+    pub fn decode(frame: &[u8]) -> Self {
+        let byte = |i: usize| frame.get(i).copied().unwrap_or(0);
+        let axis = |i: usize| frame.get(i).map(|v| *v as i16 - 128).unwrap_or(0);
+
+        Self {
+            stick: (axis(2), axis(3)),
+            c_stick: (axis(4), axis(5)),
+            l_analog: byte(6),
+            r_analog: byte(7),
+        }
+    }
+}
+
+/// Decoded SNES Mouse report
+///
+/// The mouse shifts out 32 bits: a 16-bit header carrying the buttons and
+/// sensitivity, followed by sign-magnitude Y and X deltas. Like the standard
+/// controller the line is active low, so bits are inverted before decoding.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SnesMouse {
+    pub left: bool,
+    pub right: bool,
+    pub sensitivity: u8,
+    pub dx: i16,
+    pub dy: i16,
+}
+
+impl SnesMouse {
+    pub fn decode(frame: &[u8]) -> Self {
+        let byte = |i: usize| !frame.get(i).copied().unwrap_or(0xFF);
+        let signed = |value: u8| {
+            let magnitude = (value & 0x7F) as i16;
+            if value & 0x80 != 0 { -magnitude } else { magnitude }
+        };
+
+        Self {
+            left: byte(1) & 0x02 != 0,
+            right: byte(1) & 0x01 != 0,
+            sensitivity: (byte(1) >> 2) & 0x03,
+            dy: signed(byte(2)),
+            dx: signed(byte(3)),
+        }
+    }
+}
+
+/// Decoded Super Scope report: screen position followed by a button byte
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SuperScope {
+    pub x: u8,
+    pub y: u8,
+}
+
+impl SuperScope {
+    pub fn decode(frame: &[u8]) -> Self {
+        Self {
+            x: frame.first().copied().unwrap_or(0),
+            y: frame.get(1).copied().unwrap_or(0),
+        }
+    }
+}
+
+/// Format bytes as space-separated hex
+pub fn format_hex(data: &[u8]) -> String {
+    data.iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Standard controller of a console, for ports without a PortController packet
+fn console_default(console: Console) -> ControllerType {
+    match console {
+        Console::Snes => ControllerType::SnesStandard,
+        Console::N64 => ControllerType::N64(N64Accessory::None),
+        Console::Gc => ControllerType::GameCube,
+        Console::Gb => ControllerType::GameBoy,
+        Console::Gbc => ControllerType::GameBoyColor,
+        Console::Gba => ControllerType::GameBoyAdvance,
+        Console::Genesis => ControllerType::Genesis3,
+        _ => ControllerType::NesStandard,
+    }
+}
+
+/// Find the controller declared for a port, falling back to the console's standard controller
+pub fn detect_controller(packets: &[Packet], port: u8) -> ControllerType {
+    packets.iter()
+        .find_map(|packet| match packet {
+            Packet::PortController(controller) if controller.port == port => {
+                Some(ControllerType::from_code(controller.controller_type))
+            }
+            _ => None,
+        })
+        .or_else(|| packets.iter().find_map(|packet| match packet {
+            Packet::ConsoleType(console) => Some(console_default(console.console)),
+            _ => None,
+        }))
+        .unwrap_or(ControllerType::NesStandard)
+}
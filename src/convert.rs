@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use color_eyre::{eyre::eyre, Result};
+use rayon::prelude::*;
+use tasd_lib::{Serializable, TASD};
+use crate::bk2;
+use crate::compression::{self, Compression};
+use crate::dtm;
+use crate::fm2;
+use crate::lsmv;
+use crate::m64;
+use crate::overlay;
+use crate::replay;
+use crate::smv;
+use crate::tree::{self, TextFormat};
+use crate::vbm;
+use crate::worker;
+
+/// Formats `convert` can read and write, chosen by file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// TASD, optionally compressed
+    Tasd,
+    /// Every packet as editable text (see `export`)
+    Yaml,
+    /// Every packet as JSON, with the same fields as yaml
+    Json,
+    /// FCEUX movie (export only)
+    Fm2,
+    /// BizHawk movie (import only)
+    Bk2,
+    /// Mupen64 movie
+    M64,
+    /// Dolphin movie (GameCube controllers only)
+    Dtm,
+    /// Snes9x movie
+    Smv,
+    /// lsnes movie (export only)
+    Lsmv,
+    /// VisualBoyAdvance movie (Game Boy, Game Boy Color and GBA)
+    Vbm,
+    /// Raw NES replay for console replay devices (export only)
+    R08,
+    /// Raw SNES replay for console replay devices (export only)
+    R16m,
+    /// Pressed buttons per frame and port as JSON, for input display overlays (export only)
+    OverlayJson,
+    /// Pressed buttons per frame and port as CSV, for input display overlays (export only)
+    OverlayCsv,
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path.extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "yaml" | "yml" => Format::Yaml,
+            "json" => Format::Json,
+            "fm2" => Format::Fm2,
+            "bk2" => Format::Bk2,
+            "m64" => Format::M64,
+            "dtm" => Format::Dtm,
+            "smv" => Format::Smv,
+            "lsmv" => Format::Lsmv,
+            "vbm" => Format::Vbm,
+            "r08" => Format::R08,
+            "r16m" => Format::R16m,
+            _ => Format::Tasd,
+        }
+    }
+
+    /// File extension of movies written in this format
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Tasd => "tasd",
+            Format::Yaml => "yaml",
+            Format::Json => "json",
+            Format::Fm2 => "fm2",
+            Format::Bk2 => "bk2",
+            Format::M64 => "m64",
+            Format::Dtm => "dtm",
+            Format::Smv => "smv",
+            Format::Lsmv => "lsmv",
+            Format::Vbm => "vbm",
+            Format::R08 => "r08",
+            Format::R16m => "r16m",
+            Format::OverlayJson => "overlay.json",
+            Format::OverlayCsv => "csv",
+        }
+    }
+}
+
+/// Read a movie in any supported format as TASD, chosen by file extension
+pub fn read(path: &Path) -> Result<TASD> {
+    read_as(path, Format::from_path(path))
+}
+
+/// Read a movie in the given format as TASD
+pub fn read_as(path: &Path, format: Format) -> Result<TASD> {
+    match format {
+        Format::Tasd => crate::load_tasd(path),
+        Format::Yaml => tree::read(&std::fs::read_to_string(path)?, TextFormat::Yaml),
+        Format::Json => tree::read(&std::fs::read_to_string(path)?, TextFormat::Json),
+        Format::Fm2 => Err(eyre!("FM2 movies can be written but not read")),
+        Format::Bk2 => bk2::import(&std::fs::read(path)?),
+        Format::M64 => m64::import(&std::fs::read(path)?),
+        Format::Dtm => dtm::import(&std::fs::read(path)?),
+        Format::Smv => smv::import(&std::fs::read(path)?),
+        Format::Lsmv => Err(eyre!("lsnes movies can be written but not read")),
+        Format::Vbm => vbm::import(&std::fs::read(path)?),
+        Format::R08 | Format::R16m => Err(eyre!("Raw replays can be written but not read")),
+        Format::OverlayJson | Format::OverlayCsv => Err(eyre!("Overlay data can be written but not read")),
+    }
+}
+
+/// Encode a TASD file in another format (TASD itself is written uncompressed)
+pub fn encode(tasd: &TASD, format: Format) -> Result<Vec<u8>> {
+    match format {
+        Format::Tasd => Ok(tasd.serialize()),
+        Format::Yaml => Ok(tree::write(tasd, TextFormat::Yaml)?.into_bytes()),
+        Format::Json => Ok(tree::write(tasd, TextFormat::Json)?.into_bytes()),
+        Format::Fm2 => fm2::export(tasd),
+        Format::Bk2 => Err(eyre!("BK2 movies can be read but not written")),
+        Format::M64 => m64::export(tasd),
+        Format::Dtm => dtm::export(tasd),
+        Format::Smv => smv::export(tasd),
+        Format::Lsmv => lsmv::export(tasd),
+        Format::Vbm => vbm::export(tasd),
+        Format::R08 => replay::export_r08(tasd),
+        Format::R16m => replay::export_r16m(tasd),
+        Format::OverlayJson => overlay::export_json(tasd),
+        Format::OverlayCsv => overlay::export_csv(tasd),
+    }
+}
+
+/// Write a TASD file in the given format, returning the number of bytes written
+pub fn write_as(tasd: &TASD, path: &Path, format: Format) -> Result<usize> {
+    let data = match format {
+        Format::Tasd => compression::compress(tasd.serialize(), Compression::from_path(path), None)?,
+        format => encode(tasd, format)?,
+    };
+    worker::write_atomic(path, &data)?;
+    Ok(data.len())
+}
+
+/// Outcome of converting one file of a batch: where it was written and how many bytes
+pub type BatchResult = Result<(PathBuf, usize)>;
+
+/// Convert each movie into `out_dir`, named after it with the format's extension, on `jobs`
+/// threads (0 for one per core); a failed file doesn't stop the others
+pub fn batch(inputs: &[PathBuf], format: Format, out_dir: &Path, jobs: usize) -> Result<Vec<BatchResult>> {
+    // Movies from different directories can share a name, and would overwrite each other's output
+    let names: Vec<String> = inputs.iter().map(|input| batch_name(input, format)).collect();
+    let mut seen: HashMap<String, &Path> = HashMap::new();
+    for (input, name) in inputs.iter().zip(&names) {
+        // Compared without case, since some file systems ignore it
+        if let Some(other) = seen.insert(name.to_lowercase(), input) {
+            return Err(eyre!(
+                "{} and {} would both be written to {}; convert them separately",
+                other.display(), input.display(), out_dir.join(name).display(),
+            ));
+        }
+    }
+
+    std::fs::create_dir_all(out_dir)?;
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(jobs).build()?;
+    Ok(pool.install(|| {
+        inputs.par_iter().zip(&names)
+            .map(|(input, name)| {
+                let output = out_dir.join(name);
+                let tasd = read(input)?;
+                let size = write_as(&tasd, &output, format)?;
+                Ok((output, size))
+            })
+            .collect()
+    }))
+}
+
+/// Output file name for a batch conversion, dropping a compressed `.tasd.gz` double extension too
+fn batch_name(input: &Path, format: Format) -> String {
+    let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+    let stem = stem.strip_suffix(".tasd").unwrap_or(&stem);
+    format!("{}.{}", stem, format.extension())
+}
@@ -0,0 +1,94 @@
+/// One step of an edit script turning one sequence into another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+    /// Item present in both sequences
+    Keep,
+    /// Item only present in the new sequence
+    Insert,
+    /// Item only present in the old sequence
+    Delete,
+}
+
+/// Align two sequences by content (Myers' shortest edit script, in linear space)
+pub fn align<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Edit> {
+    let mut edits = Vec::with_capacity(old.len().max(new.len()));
+    // Sized for the whole sequences; the halves searched later need less
+    let size = 2 * (old.len() + new.len()).div_ceil(2) + 3;
+    let mut forward = vec![0isize; size];
+    let mut backward = vec![0isize; size];
+    align_into(old, new, &mut forward, &mut backward, &mut edits);
+    edits
+}
+
+/// Append the edits turning `a` into `b`, splitting the search at a point on a shortest path
+fn align_into<T: PartialEq>(a: &[T], b: &[T], forward: &mut [isize], backward: &mut [isize], edits: &mut Vec<Edit>) {
+    // Common prefix and suffix don't need the full search
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a, b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    edits.extend(std::iter::repeat_n(Edit::Keep, prefix));
+    if a.is_empty() {
+        edits.extend(std::iter::repeat_n(Edit::Insert, b.len()));
+    } else if b.is_empty() {
+        edits.extend(std::iter::repeat_n(Edit::Delete, a.len()));
+    } else {
+        let (x, y) = middle_snake(a, b, forward, backward);
+        align_into(&a[..x], &b[..y], forward, backward, edits);
+        align_into(&a[x..], &b[y..], forward, backward, edits);
+    }
+    edits.extend(std::iter::repeat_n(Edit::Keep, suffix));
+}
+
+/// A point on a shortest edit path, found by searching from both ends until the paths meet.
+/// `a` and `b` must be non-empty with no common prefix or suffix, so the point splits the work.
+fn middle_snake<T: PartialEq>(a: &[T], b: &[T], forward: &mut [isize], backward: &mut [isize]) -> (usize, usize) {
+    let n = a.len() as isize;
+    let m = b.len() as isize;
+    let delta = n - m;
+    let odd = delta % 2 != 0;
+    let max = (n + m + 1) / 2;
+    let offset = max + 1;
+    let at = |k: isize| (k + offset) as usize;
+    forward[at(1)] = 0;
+    backward[at(1)] = 0;
+
+    for d in 0..=max {
+        // Furthest reaching paths from the start, diagonal k holding the points with x - y = k
+        for k in (-d..=d).step_by(2) {
+            let x0 = if k == -d || (k != d && forward[at(k - 1)] < forward[at(k + 1)]) { forward[at(k + 1)] } else { forward[at(k - 1)] + 1 };
+            let y0 = x0 - k;
+            let (mut x, mut y) = (x0, y0);
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[at(k)] = x;
+            // The backward path on the same diagonal has taken d - 1 steps
+            let reverse_k = delta - k;
+            if odd && reverse_k.abs() < d && x + backward[at(reverse_k)] >= n {
+                return (x0 as usize, y0 as usize);
+            }
+        }
+
+        // Furthest reaching paths from the end, counted from the end of both sequences
+        for k in (-d..=d).step_by(2) {
+            let x0 = if k == -d || (k != d && backward[at(k - 1)] < backward[at(k + 1)]) { backward[at(k + 1)] } else { backward[at(k - 1)] + 1 };
+            let y0 = x0 - k;
+            let (mut x, mut y) = (x0, y0);
+            while x < n && y < m && a[(n - 1 - x) as usize] == b[(m - 1 - y) as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[at(k)] = x;
+            let forward_k = delta - k;
+            if !odd && forward_k.abs() <= d && forward[at(forward_k)] + x >= n {
+                return ((n - x0) as usize, (m - y0) as usize);
+            }
+        }
+    }
+    unreachable!("the forward and backward paths always meet")
+}
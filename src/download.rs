@@ -0,0 +1,71 @@
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use color_eyre::{eyre::eyre, Result};
+
+/// Width of the progress bar in characters
+const BAR_WIDTH: usize = 40;
+
+/// Whether a file argument is an HTTP(S) URL rather than a local path
+pub fn is_url(arg: &str) -> bool {
+    arg.starts_with("http://") || arg.starts_with("https://")
+}
+
+/// Download a URL into the cache directory and return the local path
+pub fn download_to_cache(url: &str) -> Result<PathBuf> {
+    let cache_dir = dirs::cache_dir()
+        .ok_or_else(|| eyre!("No cache directory available"))?
+        .join("tasd-cli");
+    std::fs::create_dir_all(&cache_dir)?;
+
+    // Name the file after the last path segment, ignoring any query string
+    let name = url.split(['?', '#']).next().unwrap_or(url)
+        .rsplit('/')
+        .find(|segment| !segment.is_empty())
+        .filter(|segment| !segment.contains(':'))
+        .unwrap_or("download.tasd");
+    let path = cache_dir.join(name);
+
+    let mut response = ureq::get(url)
+        .call()
+        .map_err(|e| eyre!("Download of {} failed: {}", url, e))?;
+    let total: Option<u64> = response.headers()
+        .get("content-length")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok());
+
+    let mut reader = response.body_mut().with_config().limit(u64::MAX).reader();
+    let mut file = std::fs::File::create(&path)?;
+    let mut buffer = [0u8; 64 * 1024];
+    let mut downloaded = 0u64;
+
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        file.write_all(&buffer[..read])?;
+        downloaded += read as u64;
+        print_progress(downloaded, total);
+    }
+    eprintln!();
+
+    Ok(path)
+}
+
+/// Draw a single-line progress bar on stderr
+fn print_progress(downloaded: u64, total: Option<u64>) {
+    match total {
+        Some(total) if total > 0 => {
+            let filled = (downloaded.min(total) * BAR_WIDTH as u64 / total) as usize;
+            eprint!(
+                "\rDownloading [{}{}] {:3}% ({} / {} bytes)",
+                "#".repeat(filled),
+                " ".repeat(BAR_WIDTH - filled),
+                downloaded * 100 / total,
+                downloaded,
+                total
+            );
+        }
+        _ => eprint!("\rDownloading {} bytes", downloaded),
+    }
+}
@@ -0,0 +1,206 @@
+use color_eyre::{eyre::eyre, Result};
+use tasd_lib::{
+    Attribution, AttributionType, Console, ConsoleType, EmulatorName, InputChunk, Packet, PortController,
+    RomName, TotalFrames, TotalRerecords, TASD,
+};
+use crate::controller::ControllerType;
+
+/// Dolphin movie signature, "DTM" followed by an EOF byte
+const SIGNATURE: &[u8; 4] = b"DTM\x1A";
+/// Offset of the controller data, which follows the fixed-size header
+const HEADER_LEN: usize = 0x100;
+/// Bytes per GameCube controller per input poll
+const SAMPLE_LEN: usize = 8;
+/// Button bits set on connected controllers by newer Dolphin versions
+const CONNECTED: u8 = 0x40;
+
+/// Byte index and mask of a button bit
+type Bit = (usize, u8);
+
+/// Where each GameCube button sits in a DTM sample and in TASD input
+const BUTTONS: &[(Bit, Bit)] = &[
+    ((0, 0x01), (0, 0x10)), // Start
+    ((0, 0x02), (0, 0x01)), // A
+    ((0, 0x04), (0, 0x02)), // B
+    ((0, 0x08), (0, 0x04)), // X
+    ((0, 0x10), (0, 0x08)), // Y
+    ((0, 0x20), (1, 0x10)), // Z
+    ((0, 0x40), (1, 0x08)), // Up
+    ((0, 0x80), (1, 0x04)), // Down
+    ((1, 0x01), (1, 0x01)), // Left
+    ((1, 0x02), (1, 0x02)), // Right
+    ((1, 0x04), (1, 0x40)), // L
+    ((1, 0x08), (1, 0x20)), // R
+];
+
+/// Analog bytes in a DTM sample and in TASD input: triggers, main stick and C-stick
+const AXES: &[(usize, usize)] = &[(2, 6), (3, 7), (4, 2), (5, 3), (6, 4), (7, 5)];
+
+/// Convert one DTM controller sample into TASD GameCube input
+fn sample_to_tasd(sample: &[u8]) -> [u8; SAMPLE_LEN] {
+    let mut frame = [0u8; SAMPLE_LEN];
+    for ((dtm_byte, dtm_mask), (tasd_byte, tasd_mask)) in BUTTONS {
+        if sample[*dtm_byte] & dtm_mask != 0 {
+            frame[*tasd_byte] |= tasd_mask;
+        }
+    }
+    for (dtm, tasd) in AXES {
+        frame[*tasd] = sample[*dtm];
+    }
+    frame
+}
+
+/// Convert one frame of TASD GameCube input into a DTM controller sample
+fn tasd_to_sample(frame: &[u8]) -> [u8; SAMPLE_LEN] {
+    let mut sample = [0u8; SAMPLE_LEN];
+    sample[1] = CONNECTED;
+    for ((dtm_byte, dtm_mask), (tasd_byte, tasd_mask)) in BUTTONS {
+        if frame[*tasd_byte] & tasd_mask != 0 {
+            sample[*dtm_byte] |= dtm_mask;
+        }
+    }
+    for (dtm, tasd) in AXES {
+        sample[*dtm] = frame[*tasd];
+    }
+    sample
+}
+
+/// Read a NUL-padded string field
+fn read_str(data: &[u8], offset: usize, len: usize) -> String {
+    let field = &data[offset..offset + len];
+    let end = field.iter().position(|b| *b == 0).unwrap_or(len);
+    String::from_utf8_lossy(&field[..end]).trim().to_string()
+}
+
+/// Write a string into a NUL-padded field, cutting it at a character boundary if it is too long
+fn write_str(data: &mut [u8], offset: usize, len: usize, text: &str) {
+    let mut end = text.len().min(len);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    data[offset..offset + end].copy_from_slice(&text.as_bytes()[..end]);
+}
+
+/// Convert a Dolphin `.dtm` movie of GameCube controllers into TASD packets
+pub fn import(data: &[u8]) -> Result<TASD> {
+    if data.len() < HEADER_LEN || &data[..4] != SIGNATURE {
+        return Err(eyre!("Not a Dolphin movie (missing DTM signature)"));
+    }
+    let controllers = data[0x00B];
+    if data[0x00A] != 0 || controllers & 0xF0 != 0 {
+        return Err(eyre!("Wii movies and Wii Remote input cannot be converted"));
+    }
+    if data[0x00C] != 0 {
+        return Err(eyre!("Movies that start from a savestate cannot be converted"));
+    }
+
+    // Bits 0-3 mark connected GameCube controllers
+    let ports: Vec<u8> = (0..4).filter(|i| controllers & (1 << i) != 0).map(|i| i as u8 + 1).collect();
+    if ports.is_empty() {
+        return Err(eyre!("DTM movie has no GameCube controllers connected"));
+    }
+
+    let mut tasd = TASD::new();
+    let packets = &mut tasd.packets;
+    packets.push(Packet::ConsoleType(ConsoleType { console: Console::Gc, name: String::new() }));
+    packets.push(Packet::EmulatorName(EmulatorName { name: "Dolphin".to_string() }));
+
+    let game_id = read_str(data, 0x004, 6);
+    if !game_id.is_empty() {
+        packets.push(Packet::RomName(RomName { name: game_id }));
+    }
+    let author = read_str(data, 0x031, 32);
+    if !author.is_empty() {
+        packets.push(Packet::Attribution(Attribution { attribution_type: AttributionType::Author, name: author }));
+    }
+    let rerecords = u32::from_le_bytes(data[0x02D..0x031].try_into().unwrap());
+    packets.push(Packet::TotalRerecords(TotalRerecords { rerecords }));
+
+    for port in &ports {
+        packets.push(Packet::PortController(PortController {
+            port: *port,
+            controller_type: ControllerType::GameCube.code(),
+        }));
+    }
+
+    // Samples are interleaved: one 8-byte sample per connected controller for each poll
+    let stride = SAMPLE_LEN * ports.len();
+    let frames = (data.len() - HEADER_LEN) / stride;
+    packets.push(Packet::TotalFrames(TotalFrames { frames: frames as u32 }));
+
+    for (i, port) in ports.iter().enumerate() {
+        let inputs = data[HEADER_LEN..HEADER_LEN + frames * stride]
+            .chunks_exact(stride)
+            .flat_map(|frame| sample_to_tasd(&frame[i * SAMPLE_LEN..(i + 1) * SAMPLE_LEN]))
+            .collect();
+        packets.push(Packet::InputChunk(InputChunk { port: *port, inputs }));
+    }
+
+    Ok(tasd)
+}
+
+/// Convert a TASD file with GameCube controllers into a Dolphin `.dtm` movie
+pub fn export(tasd: &TASD) -> Result<Vec<u8>> {
+    let mut ports = Vec::new();
+    let mut game_id = String::new();
+    let mut authors = Vec::new();
+    let mut rerecords = 0u32;
+
+    for packet in &tasd.packets {
+        match packet {
+            Packet::PortController(pc) if ControllerType::from_code(pc.controller_type) == ControllerType::GameCube => {
+                ports.push(pc.port);
+            }
+            Packet::RomName(rn) => game_id = rn.name.clone(),
+            Packet::Attribution(at) if at.attribution_type as u8 == 1 => authors.push(at.name.clone()),
+            Packet::TotalRerecords(tr) => rerecords = tr.rerecords,
+            _ => {}
+        }
+    }
+
+    ports.sort();
+    if ports.is_empty() {
+        return Err(eyre!("No GameCube controllers declared, nothing to export"));
+    }
+    if let Some(port) = ports.iter().find(|port| !(1..=4).contains(*port)) {
+        return Err(eyre!("DTM movies only have ports 1-4, found port {}", port));
+    }
+
+    let inputs: Vec<Vec<u8>> = ports.iter()
+        .map(|port| {
+            tasd.packets.iter()
+                .filter_map(|packet| match packet {
+                    Packet::InputChunk(chunk) if chunk.port == *port => Some(chunk.inputs.as_slice()),
+                    _ => None,
+                })
+                .flatten()
+                .copied()
+                .collect()
+        })
+        .collect();
+    let frames = inputs.iter().map(|port| port.len() / SAMPLE_LEN).max().unwrap_or(0);
+
+    let controllers = ports.iter().fold(0u8, |bits, port| bits | 1 << (port - 1));
+    let polls = (frames * ports.len()) as u64;
+
+    let mut data = vec![0u8; HEADER_LEN];
+    data[..4].copy_from_slice(SIGNATURE);
+    write_str(&mut data, 0x004, 6, &game_id);
+    data[0x00B] = controllers;
+    // TASD has no VI count, so assume one input poll per VI
+    data[0x00D..0x015].copy_from_slice(&(frames as u64).to_le_bytes());
+    data[0x015..0x01D].copy_from_slice(&polls.to_le_bytes());
+    data[0x02D..0x031].copy_from_slice(&rerecords.to_le_bytes());
+    write_str(&mut data, 0x031, 32, &authors.join(", "));
+
+    // Centered sticks and released buttons for ports that run out of input
+    let neutral = [0, 0, 128, 128, 128, 128, 0, 0];
+    for frame in 0..frames {
+        for port in &inputs {
+            let input = port.get(frame * SAMPLE_LEN..(frame + 1) * SAMPLE_LEN).unwrap_or(&neutral);
+            data.extend_from_slice(&tasd_to_sample(input));
+        }
+    }
+
+    Ok(data)
+}
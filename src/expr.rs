@@ -0,0 +1,192 @@
+use color_eyre::{eyre::eyre, Result};
+
+use crate::controller::ControllerType;
+
+/// A parsed button expression, e.g. `A & !B` (or `A+B`), `port2.Start`, `changed(A)`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// Button held, on a specific port or on any port
+    Button { port: Option<u8>, name: String },
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    /// True when the inner expression differs from the previous frame
+    Changed(Box<Expr>),
+}
+
+/// Input bytes and controller of every port, for evaluating expressions
+pub struct Inputs {
+    pub ports: Vec<(u8, ControllerType, Vec<u8>)>,
+}
+
+impl Inputs {
+    /// Bytes of one frame on a port, if the port has that many frames
+    fn frame(data: &[u8], controller: ControllerType, frame: usize) -> Option<&[u8]> {
+        let size = controller.frame_size();
+        data.get(frame * size..(frame + 1) * size)
+    }
+}
+
+impl Expr {
+    /// Parse an expression, reporting the column of any syntax error
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut parser = Parser { text, pos: 0 };
+        let expr = parser.or()?;
+        parser.skip_space();
+        if parser.pos < text.len() {
+            return Err(parser.error(&format!("unexpected '{}'", &text[parser.pos..])));
+        }
+        Ok(expr)
+    }
+
+    /// Make sure every button and port in the expression exists
+    pub fn check(&self, inputs: &Inputs) -> Result<()> {
+        match self {
+            Expr::Button { port, name } => {
+                let candidates: Vec<&(u8, ControllerType, Vec<u8>)> = inputs.ports.iter()
+                    .filter(|(p, _, _)| port.is_none_or(|port| port == *p))
+                    .collect();
+                if let Some(port) = port {
+                    if candidates.is_empty() {
+                        return Err(eyre!("No port {} in this file", port));
+                    }
+                }
+                let known = candidates.iter()
+                    .any(|(_, controller, _)| controller.buttons().iter().any(|b| b.name.eq_ignore_ascii_case(name)));
+                if !known {
+                    let mut names: Vec<&str> = candidates.iter()
+                        .flat_map(|(_, controller, _)| controller.buttons().iter().map(|b| b.name))
+                        .collect();
+                    names.sort();
+                    names.dedup();
+                    return Err(eyre!("Unknown button '{}' (known: {})", name, names.join(", ")));
+                }
+                Ok(())
+            }
+            Expr::Not(inner) | Expr::Changed(inner) => inner.check(inputs),
+            Expr::And(left, right) | Expr::Or(left, right) => {
+                left.check(inputs)?;
+                right.check(inputs)
+            }
+        }
+    }
+
+    /// Evaluate the expression on a movie frame
+    pub fn eval(&self, inputs: &Inputs, frame: usize) -> bool {
+        match self {
+            Expr::Button { port, name } => inputs.ports.iter()
+                .filter(|(p, _, _)| port.is_none_or(|port| port == *p))
+                .any(|(_, controller, data)| {
+                    let Some(bytes) = Inputs::frame(data, *controller, frame) else {
+                        return false;
+                    };
+                    controller.buttons().iter()
+                        .find(|b| b.name.eq_ignore_ascii_case(name))
+                        .is_some_and(|b| controller.is_pressed(b, bytes))
+                }),
+            Expr::Not(inner) => !inner.eval(inputs, frame),
+            Expr::And(left, right) => left.eval(inputs, frame) && right.eval(inputs, frame),
+            Expr::Or(left, right) => left.eval(inputs, frame) || right.eval(inputs, frame),
+            Expr::Changed(inner) => frame > 0 && inner.eval(inputs, frame) != inner.eval(inputs, frame - 1),
+        }
+    }
+
+    /// All frames below `total` where the expression holds
+    pub fn matching_frames(&self, inputs: &Inputs, total: usize) -> Vec<usize> {
+        (0..total).filter(|frame| self.eval(inputs, *frame)).collect()
+    }
+}
+
+/// Recursive descent parser over the expression text
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> color_eyre::Report {
+        eyre!("{} at column {}", message, self.pos + 1)
+    }
+
+    fn skip_space(&mut self) {
+        // Whitespace can be more than one byte (e.g. U+3000), so skip whole chars
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Consume `token` if it comes next
+    fn eat(&mut self, token: char) -> bool {
+        self.skip_space();
+        if self.text[self.pos..].starts_with(token) {
+            self.pos += token.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn ident(&mut self) -> Result<&str> {
+        self.skip_space();
+        let start = self.pos;
+        let rest = &self.text[start..];
+        let len = rest.find(|c: char| !c.is_ascii_alphanumeric() && c != '_').unwrap_or(rest.len());
+        if len == 0 {
+            return Err(match rest.chars().next() {
+                Some(c) => self.error(&format!("expected a button name, found '{}'", c)),
+                None => self.error("expected a button name"),
+            });
+        }
+        self.pos += len;
+        Ok(&self.text[start..start + len])
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut expr = self.and()?;
+        while self.eat('|') {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut expr = self.unary()?;
+        while self.eat('&') || self.eat('+') {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.eat('!') {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat('(') {
+            let expr = self.or()?;
+            if !self.eat(')') {
+                return Err(self.error("expected ')'"));
+            }
+            return Ok(expr);
+        }
+
+        let start = self.pos;
+        let name = self.ident()?.to_string();
+
+        if name == "changed" && self.eat('(') {
+            let expr = self.or()?;
+            if !self.eat(')') {
+                return Err(self.error("expected ')' to close changed("));
+            }
+            return Ok(Expr::Changed(Box::new(expr)));
+        }
+
+        if self.eat('.') {
+            let port = name.strip_prefix("port")
+                .and_then(|n| n.parse().ok())
+                .ok_or_else(|| eyre!("expected portN before '.' at column {}", start + 1))?;
+            let button = self.ident()?.to_string();
+            return Ok(Expr::Button { port: Some(port), name: button });
+        }
+
+        Ok(Expr::Button { port: None, name })
+    }
+}
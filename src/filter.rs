@@ -0,0 +1,7 @@
+/// Frames matching a button expression, for `:filter` and `:highlight`
+pub struct Filter {
+    /// Expression the frames were matched with
+    pub expr: String,
+    /// Matching frame numbers, in order
+    pub frames: Vec<usize>,
+}
@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tasd_lib::Packet;
+use crate::inventory;
+
+/// Most files offered as candidates, keeping the first by path
+const MAX_FILES: usize = 5000;
+
+/// Game titles of files already read, keyed by path and checked against the modification time
+#[derive(Default)]
+pub struct TitleCache {
+    titles: HashMap<PathBuf, (Option<SystemTime>, String)>,
+}
+
+impl TitleCache {
+    /// Game title of a file, reading it only if it changed since the last lookup
+    pub fn title(&mut self, path: &Path) -> String {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        if let Some((cached, title)) = self.titles.get(path) {
+            if *cached == modified {
+                return title.clone();
+            }
+        }
+
+        let title = crate::load_tasd(path).ok()
+            .and_then(|tasd| tasd.packets.into_iter().find_map(|packet| match packet {
+                Packet::GameTitle(gt) => Some(gt.title),
+                _ => None,
+            }))
+            .unwrap_or_default();
+        self.titles.insert(path.to_path_buf(), (modified, title.clone()));
+        title
+    }
+}
+
+/// TASD files below a directory by path, skipping hidden directories; only names are read, so
+/// this stays quick on large trees
+pub fn find_tasd_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let hidden = entry.file_name().to_string_lossy().starts_with('.');
+            if path.is_dir() && !hidden {
+                pending.push(path);
+            } else if path.is_file() && inventory::is_tasd_file(&path) {
+                files.push(path);
+            }
+        }
+    }
+
+    // Sorted before cutting down, so the same files are kept whatever order the directories are read in
+    files.sort();
+    files.truncate(MAX_FILES);
+    files
+}
+
+/// Score a fuzzy match of `query` against `text`, or `None` if the characters do not all appear in order
+///
+/// Consecutive characters and characters at the start of a word score higher.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut last_match: Option<usize> = None;
+
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = pos + text[pos..].iter().position(|c| *c == q)?;
+        score += 1;
+        if last_match.is_some_and(|last| last + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || matches!(text[found - 1], '/' | '\\' | '_' | '-' | '.' | ' ') {
+            score += 3;
+        }
+        last_match = Some(found);
+        pos = found + 1;
+    }
+    Some(score)
+}
+
+/// Fuzzy file finder state
+pub struct Finder {
+    /// Directory the candidates were found under
+    pub root: PathBuf,
+    /// Search text typed so far
+    pub query: String,
+    /// Every candidate file and its game title (empty until loaded)
+    pub candidates: Vec<(PathBuf, String)>,
+    /// Index into the matches
+    pub selected: usize,
+    /// Titles read so far by the background thread, by candidate index
+    titles: Option<Receiver<(usize, String)>>,
+}
+
+impl Finder {
+    /// List the files below `root` and start reading their titles in the background
+    pub fn new(root: &Path, cache: Arc<Mutex<TitleCache>>) -> Self {
+        let paths = find_tasd_files(root);
+        let (sender, titles) = mpsc::channel();
+        let queue = paths.clone();
+        std::thread::spawn(move || {
+            for (index, path) in queue.iter().enumerate() {
+                let Ok(mut cache) = cache.lock() else {
+                    return;
+                };
+                let title = cache.title(path);
+                drop(cache);
+                // The finder was closed, so nobody wants the rest
+                if sender.send((index, title)).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Self {
+            root: root.to_path_buf(),
+            query: String::new(),
+            candidates: paths.into_iter().map(|path| (path, String::new())).collect(),
+            selected: 0,
+            titles: Some(titles),
+        }
+    }
+
+    /// Take in the titles read since the last call
+    pub fn poll(&mut self) {
+        let Some(titles) = &self.titles else {
+            return;
+        };
+        loop {
+            match titles.try_recv() {
+                Ok((index, title)) => self.candidates[index].1 = title,
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => break,
+            }
+        }
+        self.titles = None;
+    }
+
+    /// Whether titles are still being read
+    pub fn busy(&self) -> bool {
+        self.titles.is_some()
+    }
+
+    /// Path shown for a candidate, relative to the root
+    pub fn display_path<'a>(&self, path: &'a Path) -> std::borrow::Cow<'a, str> {
+        path.strip_prefix(&self.root).unwrap_or(path).to_string_lossy()
+    }
+
+    /// Candidates matching the query on path or title, best first
+    pub fn matches(&self) -> Vec<&(PathBuf, String)> {
+        let mut scored: Vec<_> = self.candidates.iter()
+            .filter_map(|candidate| {
+                let path_score = fuzzy_score(&self.query, &self.display_path(&candidate.0));
+                let title_score = fuzzy_score(&self.query, &candidate.1);
+                path_score.max(title_score).map(|score| (score, candidate))
+            })
+            .collect();
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored.into_iter().map(|(_, candidate)| candidate).collect()
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.selected = 0;
+    }
+
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.selected = 0;
+    }
+
+    pub fn next(&mut self) {
+        if self.selected + 1 < self.matches().len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Path of the highlighted match
+    pub fn selection(&self) -> Option<PathBuf> {
+        self.matches().get(self.selected).map(|(path, _)| path.clone())
+    }
+}
@@ -0,0 +1,123 @@
+use std::fmt::Write;
+use color_eyre::{eyre::eyre, Result};
+use tasd_lib::{Packet, TASD};
+use crate::app::App;
+use crate::controller::{self, ControllerType};
+
+/// Button letters of an FM2 gamepad field, from the top bit of the TASD byte down
+const BUTTONS: &[u8; 8] = b"RLDUTSBA";
+/// Transition index type counting frames
+const INDEX_FRAME: u8 = 1;
+/// FM2 frame commands for a soft and a hard reset
+const COMMAND_RESET: u8 = 1;
+const COMMAND_POWER: u8 = 2;
+/// ROM checksum written when the ROM is unknown (an all-zero MD5)
+const UNKNOWN_CHECKSUM: &str = "base64:AAAAAAAAAAAAAAAAAAAAAA==";
+
+/// Format one frame of TASD NES input as an FM2 gamepad field
+///
+/// TASD stores the buttons active low with A in the lowest bit; FM2 lists them
+/// from Right down to A, with a dot for each released button.
+fn gamepad_field(byte: u8) -> String {
+    BUTTONS.iter().enumerate()
+        .map(|(i, letter)| if byte & (0x80 >> i) == 0 { *letter as char } else { '.' })
+        .collect()
+}
+
+/// Convert a TASD file with NES controllers into an FCEUX `.fm2` movie
+pub fn export(tasd: &TASD) -> Result<Vec<u8>> {
+    let mut pal = false;
+    let mut rom_name = None;
+    let mut title = None;
+    let mut authors = Vec::new();
+    let mut rerecords = 0;
+    let mut commands: Vec<(usize, u8)> = Vec::new();
+
+    for packet in &tasd.packets {
+        match packet {
+            Packet::ConsoleRegion(cr) => pal = cr.video_signal as u8 == 2,
+            Packet::RomName(rn) => rom_name = Some(rn.name.clone()),
+            Packet::GameTitle(gt) => title = Some(gt.title.clone()),
+            Packet::Attribution(at) if at.attribution_type as u8 == 1 => authors.push(at.name.clone()),
+            Packet::TotalRerecords(tr) => rerecords = tr.rerecords,
+            Packet::Transition(tr) if tr.index_type == INDEX_FRAME => match tr.transition_type as u8 {
+                1 => commands.push((tr.index as usize, COMMAND_RESET)),
+                2 => commands.push((tr.index as usize, COMMAND_POWER)),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    // Ports without a PortController packet hold standard controllers
+    let mut ports: Vec<u8> = App::detect_ports(tasd).into_iter()
+        .filter(|port| controller::detect_controller(&tasd.packets, *port) == ControllerType::NesStandard)
+        .collect();
+    ports.sort();
+    if ports.is_empty() {
+        return Err(eyre!("No NES controllers declared, nothing to export"));
+    }
+    if let Some(port) = ports.iter().find(|port| !(1..=4).contains(*port)) {
+        return Err(eyre!("FM2 movies only have controllers 1-4, found port {}", port));
+    }
+    // Ports 3 and 4 need a Four Score, which always carries all four controllers
+    let four_score = ports.iter().any(|port| *port > 2);
+    let fields: Vec<u8> = if four_score { vec![1, 2, 3, 4] } else { vec![1, 2] };
+
+    let inputs: Vec<Vec<u8>> = fields.iter()
+        .map(|port| {
+            tasd.packets.iter()
+                .filter_map(|packet| match packet {
+                    Packet::InputChunk(chunk) if chunk.port == *port && ports.contains(port) => Some(chunk.inputs.as_slice()),
+                    _ => None,
+                })
+                .flatten()
+                .copied()
+                .collect()
+        })
+        .collect();
+    let frames = inputs.iter().map(Vec::len).max().unwrap_or(0);
+
+    // FCEUX only needs the GUID to be unique, so spread the clock over all 128 bits
+    let uid = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let guid = format!("{:032X}", uid.wrapping_mul(0x9E37_79B9_7F4A_7C15_F39C_C060_5CED_C835));
+
+    let mut text = String::new();
+    writeln!(text, "version 3")?;
+    writeln!(text, "emuVersion 22020")?;
+    writeln!(text, "rerecordCount {}", rerecords)?;
+    writeln!(text, "palFlag {}", pal as u8)?;
+    writeln!(text, "romFilename {}", rom_name.or(title).unwrap_or_default())?;
+    writeln!(text, "romChecksum {}", UNKNOWN_CHECKSUM)?;
+    writeln!(text, "guid {}-{}-{}-{}-{}", &guid[..8], &guid[8..12], &guid[12..16], &guid[16..20], &guid[20..])?;
+    writeln!(text, "fourscore {}", four_score as u8)?;
+    writeln!(text, "microphone 0")?;
+    writeln!(text, "port0 {}", if four_score { 0 } else { ports.contains(&1) as u8 })?;
+    writeln!(text, "port1 {}", if four_score { 0 } else { ports.contains(&2) as u8 })?;
+    writeln!(text, "port2 0")?;
+    writeln!(text, "FDS 0")?;
+    writeln!(text, "NewPPU 0")?;
+    for author in &authors {
+        writeln!(text, "comment author {}", author)?;
+    }
+
+    for frame in 0..frames {
+        let command = commands.iter()
+            .filter(|(index, _)| *index == frame)
+            .fold(0, |bits, (_, command)| bits | command);
+        let pads: Vec<String> = fields.iter().zip(&inputs)
+            .map(|(port, input)| match input.get(frame) {
+                Some(byte) => gamepad_field(*byte),
+                // Empty fields for unplugged controllers, released buttons for ones that ran out of input
+                None if ports.contains(port) => gamepad_field(0xFF),
+                None => String::new(),
+            })
+            .collect();
+        writeln!(text, "|{}|{}||", command, pads.join("|"))?;
+    }
+
+    Ok(text.into_bytes())
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use tasd_lib::{Packet, TotalFrames};
+use crate::controller::ControllerType;
+
+/// Input stored for one port, measured in frames of its controller
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortFrames {
+    pub port: u8,
+    /// Whole frames in the port's input chunks
+    pub frames: usize,
+    /// Bytes left over after the last whole frame
+    pub extra_bytes: usize,
+}
+
+/// A declared TotalFrames that doesn't match the longest port, or a port ending in a partial frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub declared: usize,
+    /// Whole frames in the longest port
+    pub longest: usize,
+    /// Ports with bytes left over after their last whole frame
+    pub partial: Vec<PortFrames>,
+}
+
+impl Mismatch {
+    /// One-line summary, like `TotalFrames 300, longest port has 310`
+    pub fn describe(&self) -> String {
+        let mut parts = vec![format!("TotalFrames {}", self.declared)];
+        if self.longest != self.declared {
+            parts.push(format!("longest port has {}", self.longest));
+        }
+        parts.extend(self.partial.iter()
+            .map(|port| format!("port {} has {} + {} bytes", port.port, port.frames, port.extra_bytes)));
+        parts.join(", ")
+    }
+}
+
+/// How the input chunks of every port line up with each other and with TotalFrames
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    pub mismatch: Option<Mismatch>,
+    /// Ports that end before the longest one, which is fine (e.g. a controller unplugged early)
+    pub shorter: Vec<PortFrames>,
+    /// Whole frames in the longest port
+    pub longest: usize,
+}
+
+impl Report {
+    /// One-line summary of the shorter ports, like `port 2 ends at 150 of 300`
+    pub fn describe_shorter(&self) -> Option<String> {
+        if self.shorter.is_empty() {
+            return None;
+        }
+        let ports: Vec<String> = self.shorter.iter()
+            .map(|port| format!("port {} ends at {} of {}", port.port, port.frames, self.longest))
+            .collect();
+        Some(ports.join(", "))
+    }
+}
+
+/// Frame count declared by the TotalFrames packet, if any
+pub fn declared(packets: &[Packet]) -> Option<usize> {
+    packets.iter().find_map(|packet| match packet {
+        Packet::TotalFrames(tf) => Some(tf.frames as usize),
+        _ => None,
+    })
+}
+
+/// Frames of input chunk data on every port that has any
+pub fn port_frames(packets: &[Packet], controllers: &HashMap<u8, ControllerType>) -> Vec<PortFrames> {
+    let mut bytes: HashMap<u8, usize> = HashMap::new();
+    for packet in packets {
+        if let Packet::InputChunk(chunk) = packet {
+            *bytes.entry(chunk.port).or_default() += chunk.inputs.len();
+        }
+    }
+
+    let mut ports: Vec<PortFrames> = bytes.into_iter()
+        .map(|(port, bytes)| {
+            let size = controllers.get(&port).copied().unwrap_or(ControllerType::NesStandard).frame_size().max(1);
+            PortFrames { port, frames: bytes / size, extra_bytes: bytes % size }
+        })
+        .collect();
+    ports.sort_by_key(|port| port.port);
+    ports
+}
+
+/// Compare the declared TotalFrames against the longest port, noting ports that end early
+pub fn check(packets: &[Packet], controllers: &HashMap<u8, ControllerType>) -> Report {
+    let ports = port_frames(packets, controllers);
+    let longest = ports.iter().map(|port| port.frames).max().unwrap_or(0);
+    let partial: Vec<PortFrames> = ports.iter().filter(|port| port.extra_bytes != 0).cloned().collect();
+    let mismatch = declared(packets)
+        .filter(|declared| *declared != longest || !partial.is_empty())
+        .map(|declared| Mismatch { declared, longest, partial });
+    let shorter = ports.into_iter().filter(|port| port.frames < longest).collect();
+    Report { mismatch, shorter, longest }
+}
+
+/// Set TotalFrames to the longest port's input, adding the packet if there is none; returns the old value
+pub fn fix(packets: &mut Vec<Packet>, controllers: &HashMap<u8, ControllerType>) -> Option<usize> {
+    let frames = port_frames(packets, controllers).iter().map(|port| port.frames).max().unwrap_or(0);
+    for packet in packets.iter_mut() {
+        if let Packet::TotalFrames(tf) = packet {
+            let old = tf.frames as usize;
+            tf.frames = frames as u32;
+            return Some(old);
+        }
+    }
+
+    // New packets go with the other metadata, ahead of the input
+    let position = packets.iter()
+        .position(|packet| matches!(packet, Packet::PortController(_) | Packet::InputChunk(_) | Packet::InputMoment(_)))
+        .unwrap_or(packets.len());
+    packets.insert(position, Packet::TotalFrames(TotalFrames { frames: frames as u32 }));
+    None
+}
@@ -0,0 +1,40 @@
+use crate::controller::ControllerType;
+
+/// Densities as last computed, kept while the strip height and shown ports stay the same
+pub struct Cache {
+    pub rows: usize,
+    pub ports: Vec<u8>,
+    pub densities: Vec<f32>,
+}
+
+/// Frames summarized by each row of a heatmap `rows` tall
+pub fn frames_per_row(frames: usize, rows: usize) -> usize {
+    frames.div_ceil(rows.max(1)).max(1)
+}
+
+/// Share of buttons held over each row's frames, from 0 (nothing pressed) to 1 (everything held throughout)
+pub fn densities(ports: &[(ControllerType, Vec<u8>)], frames: usize, rows: usize) -> Vec<f32> {
+    let per_row = frames_per_row(frames, rows);
+    let buttons: usize = ports.iter().map(|(controller, _)| controller.buttons().len()).sum();
+    let mut pressed = vec![0usize; frames.div_ceil(per_row)];
+    for (controller, inputs) in ports {
+        for (frame, input) in inputs.chunks_exact(controller.frame_size().max(1)).enumerate().take(frames) {
+            pressed[frame / per_row] += controller.buttons().iter()
+                .filter(|button| controller.is_pressed(button, input))
+                .count();
+        }
+    }
+    pressed.iter()
+        .enumerate()
+        .map(|(row, count)| {
+            let row_frames = per_row.min(frames - row * per_row);
+            *count as f32 / (row_frames * buttons).max(1) as f32
+        })
+        .collect()
+}
+
+/// Rows at least half as dense as the densest one, where the busy parts of a movie are
+pub fn dense_rows(densities: &[f32]) -> Vec<bool> {
+    let max = densities.iter().copied().fold(0.0, f32::max);
+    densities.iter().map(|density| *density > 0.0 && *density >= max / 2.0).collect()
+}
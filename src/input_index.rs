@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+use tasd_lib::Packet;
+
+/// One input chunk of a port, placed in the port's input stream
+struct Span {
+    /// Index of the InputChunk packet
+    packet: usize,
+    /// Byte offset of the chunk's first input in the port's stream
+    start: usize,
+    len: usize,
+}
+
+/// Where every port's input lives in the packet list
+///
+/// Built once per change to the packets, so the input table can read the frames it shows
+/// straight out of the chunks instead of concatenating a port's whole input on every draw.
+#[derive(Default)]
+pub struct InputIndex {
+    ports: HashMap<u8, Vec<Span>>,
+}
+
+impl InputIndex {
+    pub fn build(packets: &[Packet]) -> Self {
+        let mut ports: HashMap<u8, Vec<Span>> = HashMap::new();
+        for (packet, chunk) in packets.iter().enumerate().filter_map(|(i, packet)| match packet {
+            Packet::InputChunk(chunk) => Some((i, chunk)),
+            _ => None,
+        }) {
+            let spans = ports.entry(chunk.port).or_default();
+            let start = spans.last().map_or(0, |span| span.start + span.len);
+            spans.push(Span { packet, start, len: chunk.inputs.len() });
+        }
+        Self { ports }
+    }
+
+    /// Total bytes of input stored for a port
+    pub fn port_len(&self, port: u8) -> usize {
+        self.ports.get(&port)
+            .and_then(|spans| spans.last())
+            .map_or(0, |span| span.start + span.len)
+    }
+
+    /// Bytes `offset..offset + len` of a port's input stream, cut short at its end
+    pub fn read(&self, packets: &[Packet], port: u8, offset: usize, len: usize) -> Vec<u8> {
+        let Some(spans) = self.ports.get(&port) else {
+            return Vec::new();
+        };
+        let end = (offset + len).min(self.port_len(port));
+
+        let mut bytes = Vec::with_capacity(end.saturating_sub(offset));
+        let first = spans.partition_point(|span| span.start + span.len <= offset);
+        for span in spans[first..].iter().take_while(|span| span.start < end) {
+            if let Packet::InputChunk(chunk) = &packets[span.packet] {
+                let from = offset.saturating_sub(span.start);
+                let to = (end - span.start).min(span.len);
+                bytes.extend_from_slice(&chunk.inputs[from..to]);
+            }
+        }
+        bytes
+    }
+
+    /// One whole frame of a port, or None if the port's input ends before it
+    pub fn frame(&self, packets: &[Packet], port: u8, frame: usize, frame_size: usize) -> Option<Vec<u8>> {
+        let bytes = self.read(packets, port, frame * frame_size, frame_size);
+        (bytes.len() == frame_size).then_some(bytes)
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use color_eyre::Result;
+use tasd_lib::{Packet, TASD};
+use crate::app::App;
+use crate::attributions;
+use crate::catalog;
+use crate::controller;
+use crate::timing;
+
+/// Key metadata of one TASD file, as listed by `ls`
+pub struct Summary {
+    pub title: String,
+    pub console: String,
+    pub frames: usize,
+    pub duration: String,
+    pub authors: Vec<String>,
+    /// Value of the Verified packet, if the file has one
+    pub verified: Option<bool>,
+}
+
+/// Collect the metadata shown in a directory listing
+pub fn summarize(tasd: &TASD) -> Summary {
+    let mut title = String::new();
+    let mut console = String::new();
+    let mut verified = None;
+
+    for packet in &tasd.packets {
+        match packet {
+            Packet::GameTitle(gt) => title = gt.title.clone(),
+            Packet::ConsoleType(ct) => console = catalog::console_name(ct.console as u8).to_string(),
+            Packet::Verified(v) => verified = Some(v.verified),
+            _ => {}
+        }
+    }
+
+    let controllers: HashMap<_, _> = App::detect_ports(tasd).into_iter()
+        .map(|port| (port, controller::detect_controller(&tasd.packets, port)))
+        .collect();
+    let frames = App::count_inputs(tasd, &controllers);
+
+    // Attribution type 1 is the author
+    let authors = attributions::entries(&tasd.packets).into_iter()
+        .filter(|(code, _)| *code == 1)
+        .map(|(_, name)| name)
+        .collect();
+
+    Summary {
+        title,
+        console,
+        frames,
+        duration: timing::format_timestamp(frames, timing::default_framerate(&tasd.packets)),
+        authors,
+        verified,
+    }
+}
+
+/// Whether a path looks like a TASD file, compressed or not
+pub fn is_tasd_file(path: &Path) -> bool {
+    let name = path.to_string_lossy().to_lowercase();
+    [".tasd", ".tasd.gz", ".tasd.zst", ".tasd.xz"].iter().any(|ext| name.ends_with(ext))
+}
+
+/// TASD files directly inside a directory, sorted by name
+pub fn tasd_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && is_tasd_file(&path) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
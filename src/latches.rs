@@ -0,0 +1,82 @@
+use tasd_lib::Packet;
+
+/// Latches a frame should have: none on a lag frame, one input read on any other
+fn expected_latches(lag: bool) -> u64 {
+    if lag { 0 } else { 1 }
+}
+
+/// Latch timing of a movie: the latch filter, lag frames (frames the console didn't latch on),
+/// and the SNES latch train of each frame with the inputs it consumes, flagging frames that
+/// latch more or less than expected for a lag or normal frame.
+/// None when the movie has no latch-related packets.
+pub fn report(packets: &[Packet], input_frames: usize) -> Option<Vec<String>> {
+    let mut filter = None;
+    let mut points = Vec::new();
+    let mut lag = Vec::new();
+    for packet in packets {
+        match packet {
+            Packet::NesLatchFilter(p) => filter = Some(p.time),
+            Packet::SnesLatchTrain(p) => points.extend_from_slice(&p.points),
+            Packet::LagFrameChunk(p) => lag.push((p.movie_frame as usize, p.count as usize)),
+            _ => {}
+        }
+    }
+    if filter.is_none() && points.is_empty() && lag.is_empty() {
+        return None;
+    }
+
+    let mut lines = Vec::new();
+    if let Some(time) = filter {
+        lines.push(format!("NES latch filter: {}", time));
+    }
+    if !lag.is_empty() {
+        lag.sort();
+        let frames: usize = lag.iter().map(|(_, count)| count).sum();
+        lines.push(format!("Lag frames (no latch): {} in {} runs", frames, lag.len()));
+        for (frame, count) in &lag {
+            lines.push(format!("  frame {:>8}  {} lag frame{}", frame, count, if *count == 1 { "" } else { "s" }));
+        }
+    }
+    if points.is_empty() {
+        return Some(lines);
+    }
+
+    // One train per movie frame; lag frames are the ones the console never latched on
+    let mut lag_frames = vec![false; points.len()];
+    for (frame, count) in &lag {
+        for flag in lag_frames.iter_mut().skip(*frame).take(*count) {
+            *flag = true;
+        }
+    }
+
+    let consumed: u64 = points.iter().sum();
+    let expected = |frame: usize| expected_latches(lag_frames[frame]);
+    let double = (0..points.len()).filter(|frame| points[*frame] > expected(*frame)).count();
+    let missed = (0..points.len()).filter(|frame| points[*frame] < expected(*frame)).count();
+    lines.push(format!("SNES latch trains: {} frames, {} latches", points.len(), consumed));
+    lines.push(format!("Double latches: {} frames, missed latches: {} frames", double, missed));
+    if consumed != input_frames as u64 {
+        lines.push(format!("MISMATCH: the trains latch {} inputs but the movie has {}", consumed, input_frames));
+    }
+
+    lines.push(String::new());
+    lines.push(format!("{:>8}  {:>7}  {:>8}  {:<17}", "Frame", "Latches", "Expected", "Inputs"));
+    let mut input = 0u64;
+    for (frame, latches) in points.iter().enumerate() {
+        let inputs = match latches {
+            0 => "-".to_string(),
+            1 => input.to_string(),
+            _ => format!("{}-{}", input, input + latches - 1),
+        };
+        let flag = match (lag_frames[frame], latches.cmp(&expected(frame))) {
+            (true, std::cmp::Ordering::Greater) => "  << latched on a lag frame",
+            (false, std::cmp::Ordering::Greater) => "  << double latch",
+            (_, std::cmp::Ordering::Less) => "  << missed latch",
+            (true, std::cmp::Ordering::Equal) => "  (lag)",
+            (false, std::cmp::Ordering::Equal) => "",
+        };
+        lines.push(format!("{:>8}  {:>7}  {:>8}  {:<17}{}", frame, latches, expected(frame), inputs, flag));
+        input += latches;
+    }
+    Some(lines)
+}
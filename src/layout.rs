@@ -0,0 +1,50 @@
+use std::path::{Path, PathBuf};
+use color_eyre::Result;
+
+/// File holding remembered port orders, one `<file>\t<ports>` line per movie
+fn path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("tasd-cli").join("port-order"))
+}
+
+/// Key identifying a movie file across sessions
+fn file_key(file: &Path) -> String {
+    file.canonicalize()
+        .unwrap_or_else(|_| file.to_path_buf())
+        .display()
+        .to_string()
+}
+
+/// Port column order remembered for a file, if any
+pub fn load_port_order(file: &Path) -> Option<Vec<u8>> {
+    let content = std::fs::read_to_string(path()?).ok()?;
+    let key = file_key(file);
+    content.lines()
+        .filter_map(|line| line.split_once('\t'))
+        .find(|(name, _)| *name == key)
+        .map(|(_, ports)| ports.split_whitespace().filter_map(|p| p.parse().ok()).collect())
+}
+
+/// Remember the port column order for a file, replacing any previous entry
+pub fn save_port_order(file: &Path, order: &[u8]) -> Result<()> {
+    let Some(path) = path() else {
+        return Ok(());
+    };
+    let key = file_key(file);
+
+    let mut lines: Vec<String> = std::fs::read_to_string(&path)
+        .unwrap_or_default()
+        .lines()
+        .filter(|line| line.split_once('\t').is_none_or(|(name, _)| name != key))
+        .map(str::to_string)
+        .collect();
+    if !order.is_empty() {
+        let ports: Vec<String> = order.iter().map(u8::to_string).collect();
+        lines.push(format!("{}\t{}", key, ports.join(" ")));
+    }
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, lines.join("\n") + "\n")?;
+    Ok(())
+}
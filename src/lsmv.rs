@@ -0,0 +1,115 @@
+use std::io::{Cursor, Write};
+use color_eyre::{eyre::eyre, Result};
+use tasd_lib::{Packet, TASD};
+use zip::write::SimpleFileOptions;
+use crate::controller::ControllerType;
+
+/// lsnes gamepad buttons, in the order the console reads them and TASD stores them (B in the lowest bit)
+const BUTTONS: &[u8; 12] = b"BYsSudlrAXLR";
+/// Bytes per controller per frame in TASD
+const SAMPLE_LEN: usize = 2;
+/// Transition index type counting frames
+const INDEX_FRAME: u8 = 1;
+/// Movie format written by lsnes rr1 and later
+const SYSTEM_ID: &str = "lsnes-rr1";
+/// Core named in the movie; lsnes warns about a mismatch but plays it anyway
+const CORE_VERSION: &str = "bsnes v085 (Compatibility core)";
+
+/// Format one frame of TASD SNES input as an lsnes gamepad field, a letter per held button
+fn gamepad_field(frame: &[u8]) -> String {
+    let bits = !u16::from_le_bytes([frame[0], frame[1]]);
+    BUTTONS.iter().enumerate()
+        .map(|(i, letter)| if bits & (1 << i) != 0 { *letter as char } else { '.' })
+        .collect()
+}
+
+/// Convert a TASD file with SNES controllers into an lsnes `.lsmv` movie
+///
+/// The movie is a zip archive of small text members. Its `input` member has a line per frame:
+/// the system field (`F` for the frame sync, `R` on a reset, then the reset delay) and a
+/// `|`-separated gamepad field per controller. Controllers 3-5 go through a multitap on port 2.
+pub fn export(tasd: &TASD) -> Result<Vec<u8>> {
+    let mut ports = Vec::new();
+    let mut pal = false;
+    let mut title = None;
+    let mut authors = Vec::new();
+    let mut rerecords = 0u32;
+    let mut resets = Vec::new();
+
+    for packet in &tasd.packets {
+        match packet {
+            Packet::PortController(pc) if ControllerType::from_code(pc.controller_type) == ControllerType::SnesStandard => {
+                ports.push(pc.port);
+            }
+            Packet::ConsoleRegion(cr) => pal = cr.video_signal as u8 == 2,
+            Packet::GameTitle(gt) => title = Some(gt.title.clone()),
+            Packet::Attribution(at) if at.attribution_type as u8 == 1 => authors.push(at.name.clone()),
+            Packet::TotalRerecords(tr) => rerecords = tr.rerecords,
+            Packet::Transition(tr) if tr.index_type == INDEX_FRAME && tr.transition_type as u8 == 1 => {
+                resets.push(tr.index as usize);
+            }
+            _ => {}
+        }
+    }
+
+    ports.sort();
+    if ports.is_empty() {
+        return Err(eyre!("No SNES controllers declared, nothing to export"));
+    }
+    if let Some(port) = ports.iter().find(|port| !(1..=5).contains(*port)) {
+        return Err(eyre!("lsnes movies have controllers 1-5, found port {}", port));
+    }
+    let multitap = ports.iter().any(|port| *port > 2);
+
+    // Every controller of the layout gets a field, released when the file has none for it
+    let controllers: Vec<u8> = if multitap { (1..=5).collect() } else { (1..=*ports.last().unwrap()).collect() };
+    let inputs: Vec<Vec<u8>> = controllers.iter()
+        .map(|port| {
+            tasd.packets.iter()
+                .filter_map(|packet| match packet {
+                    Packet::InputChunk(chunk) if chunk.port == *port => Some(chunk.inputs.as_slice()),
+                    _ => None,
+                })
+                .flatten()
+                .copied()
+                .collect()
+        })
+        .collect();
+    let frames = inputs.iter().map(|port| port.len() / SAMPLE_LEN).max().unwrap_or(0);
+
+    let released = [0xFF; SAMPLE_LEN];
+    let mut input = String::new();
+    for frame in 0..frames {
+        input.push_str(if resets.contains(&frame) { "FR 0 0" } else { "F. 0 0" });
+        for port in &inputs {
+            input.push('|');
+            input.push_str(&gamepad_field(port.get(frame * SAMPLE_LEN..(frame + 1) * SAMPLE_LEN).unwrap_or(&released)));
+        }
+        input.push('\n');
+    }
+
+    let port2 = match (multitap, controllers.len()) {
+        (true, _) => "multitap",
+        (false, 2) => "gamepad",
+        _ => "none",
+    };
+    let members = [
+        ("systemid", format!("{}\n", SYSTEM_ID)),
+        ("controlsversion", "0\n".to_string()),
+        ("coreversion", format!("{}\n", CORE_VERSION)),
+        ("gametype", format!("{}\n", if pal { "snes_pal" } else { "snes_ntsc" })),
+        ("port1", "gamepad\n".to_string()),
+        ("port2", format!("{}\n", port2)),
+        ("gamename", format!("{}\n", title.unwrap_or_default())),
+        ("authors", authors.iter().map(|author| format!("{}\n", author)).collect()),
+        ("rerecords", format!("{}\n", rerecords)),
+        ("input", input),
+    ];
+
+    let mut archive = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, content) in members {
+        archive.start_file(name, SimpleFileOptions::default())?;
+        archive.write_all(content.as_bytes())?;
+    }
+    Ok(archive.finish()?.into_inner())
+}
@@ -0,0 +1,208 @@
+use color_eyre::{eyre::eyre, Result};
+use tasd_lib::{
+    Attribution, AttributionType, Comment, Console, ConsoleRegion, ConsoleType, InputChunk, Packet,
+    PortController, RomName, TotalFrames, TotalRerecords, VideoSignal, TASD,
+};
+use crate::controller::{ControllerType, N64Accessory};
+
+/// Mupen64 movie signature, "M64" followed by an EOF byte
+const SIGNATURE: &[u8; 4] = b"M64\x1A";
+/// Offset of the controller data, which follows the fixed-size header
+const HEADER_LEN: usize = 0x400;
+/// Bytes per controller per input sample
+const SAMPLE_LEN: usize = 4;
+/// Movie starts from power-on rather than a savestate
+const START_POWER_ON: u16 = 2;
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+/// Read a NUL-padded string field
+fn read_str(data: &[u8], offset: usize, len: usize) -> String {
+    let field = &data[offset..offset + len];
+    let end = field.iter().position(|b| *b == 0).unwrap_or(len);
+    String::from_utf8_lossy(&field[..end]).trim().to_string()
+}
+
+/// Write a string into a NUL-padded field, cutting it at a character boundary if it is too long
+fn write_str(data: &mut [u8], offset: usize, len: usize, text: &str) {
+    let mut end = text.len().min(len - 1);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    data[offset..offset + end].copy_from_slice(&text.as_bytes()[..end]);
+}
+
+/// Convert a Mupen64 `.m64` movie into TASD packets
+///
+/// M64 controller samples use the same 4-byte layout as TASD's N64 input, so they are copied as is.
+pub fn import(data: &[u8]) -> Result<TASD> {
+    if data.len() < HEADER_LEN || &data[..4] != SIGNATURE {
+        return Err(eyre!("Not a Mupen64 movie (missing M64 signature)"));
+    }
+    let version = read_u32(data, 0x004);
+    if version != 3 {
+        return Err(eyre!("Unsupported M64 version {} (only version 3 is supported)", version));
+    }
+    if read_u16(data, 0x01C) != START_POWER_ON {
+        return Err(eyre!("Movies that start from a savestate or EEPROM cannot be converted"));
+    }
+
+    let fps = data[0x014];
+    let samples = read_u32(data, 0x018) as usize;
+    let rerecords = read_u32(data, 0x010);
+    let flags = read_u32(data, 0x020);
+
+    // Bits 0-3 mark connected controllers, 4-7 a Controller Pak and 8-11 a Rumble Pak
+    let ports: Vec<u8> = (0..4).filter(|i| flags & (1 << i) != 0).map(|i| i as u8 + 1).collect();
+    if ports.is_empty() {
+        return Err(eyre!("M64 movie has no controllers connected"));
+    }
+
+    let mut tasd = TASD::new();
+    let packets = &mut tasd.packets;
+    packets.push(Packet::ConsoleType(ConsoleType { console: Console::N64, name: String::new() }));
+    packets.push(Packet::ConsoleRegion(ConsoleRegion {
+        video_signal: if fps == 50 { VideoSignal::Pal } else { VideoSignal::Ntsc },
+    }));
+
+    let rom_name = read_str(data, 0x0C4, 32);
+    if !rom_name.is_empty() {
+        packets.push(Packet::RomName(RomName { name: rom_name }));
+    }
+    let author = read_str(data, 0x222, 222);
+    if !author.is_empty() {
+        packets.push(Packet::Attribution(Attribution { attribution_type: AttributionType::Author, name: author }));
+    }
+    let description = read_str(data, 0x300, 256);
+    if !description.is_empty() {
+        packets.push(Packet::Comment(Comment { comment: description }));
+    }
+    packets.push(Packet::TotalRerecords(TotalRerecords { rerecords }));
+
+    for (i, port) in ports.iter().enumerate() {
+        let accessory = if flags & (1 << (i + 8)) != 0 {
+            N64Accessory::RumblePak
+        } else if flags & (1 << (i + 4)) != 0 {
+            N64Accessory::ControllerPak
+        } else {
+            N64Accessory::None
+        };
+        packets.push(Packet::PortController(PortController {
+            port: *port,
+            controller_type: ControllerType::N64(accessory).code(),
+        }));
+    }
+
+    // Samples are interleaved: one 4-byte sample per connected controller for each frame
+    let stride = SAMPLE_LEN * ports.len();
+    let frames = samples.min((data.len() - HEADER_LEN) / stride);
+    packets.push(Packet::TotalFrames(TotalFrames { frames: frames as u32 }));
+
+    for (i, port) in ports.iter().enumerate() {
+        let inputs = data[HEADER_LEN..HEADER_LEN + frames * stride]
+            .chunks_exact(stride)
+            .flat_map(|frame| &frame[i * SAMPLE_LEN..(i + 1) * SAMPLE_LEN])
+            .copied()
+            .collect();
+        packets.push(Packet::InputChunk(InputChunk { port: *port, inputs }));
+    }
+
+    Ok(tasd)
+}
+
+/// Convert a TASD file with N64 controllers into a Mupen64 `.m64` movie
+pub fn export(tasd: &TASD) -> Result<Vec<u8>> {
+    let mut ports: Vec<(u8, N64Accessory)> = Vec::new();
+    let mut pal = false;
+    let mut rom_name = String::new();
+    let mut authors = Vec::new();
+    let mut description = None;
+    let mut rerecords = 0;
+
+    for packet in &tasd.packets {
+        match packet {
+            Packet::PortController(pc) => {
+                if let Some(accessory) = ControllerType::from_code(pc.controller_type).accessory() {
+                    ports.push((pc.port, accessory));
+                }
+            }
+            Packet::ConsoleRegion(cr) => pal = cr.video_signal as u8 == 2,
+            Packet::RomName(rn) => rom_name = rn.name.clone(),
+            Packet::Attribution(at) if at.attribution_type as u8 == 1 => authors.push(at.name.clone()),
+            Packet::Comment(c) if description.is_none() && !c.comment.starts_with("tasd-cli:") => {
+                description = Some(c.comment.clone());
+            }
+            Packet::TotalRerecords(tr) => rerecords = tr.rerecords,
+            _ => {}
+        }
+    }
+
+    ports.sort_by_key(|(port, _)| *port);
+    if ports.is_empty() {
+        return Err(eyre!("No N64 controllers declared, nothing to export"));
+    }
+    if let Some((port, _)) = ports.iter().find(|(port, _)| !(1..=4).contains(port)) {
+        return Err(eyre!("M64 movies only have ports 1-4, found port {}", port));
+    }
+
+    let inputs: Vec<Vec<u8>> = ports.iter()
+        .map(|(port, _)| {
+            tasd.packets.iter()
+                .filter_map(|packet| match packet {
+                    Packet::InputChunk(chunk) if chunk.port == *port => Some(chunk.inputs.as_slice()),
+                    _ => None,
+                })
+                .flatten()
+                .copied()
+                .collect()
+        })
+        .collect();
+    let frames = inputs.iter().map(|port| port.len() / SAMPLE_LEN).max().unwrap_or(0);
+
+    let mut flags = 0u32;
+    for (port, accessory) in &ports {
+        let bit = *port as u32 - 1;
+        flags |= 1 << bit;
+        match accessory {
+            N64Accessory::ControllerPak => flags |= 1 << (bit + 4),
+            N64Accessory::RumblePak => flags |= 1 << (bit + 8),
+            _ => {}
+        }
+    }
+
+    let uid = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as u32)
+        .unwrap_or(0);
+
+    let mut data = vec![0u8; HEADER_LEN];
+    data[..4].copy_from_slice(SIGNATURE);
+    data[0x004..0x008].copy_from_slice(&3u32.to_le_bytes());
+    data[0x008..0x00C].copy_from_slice(&uid.to_le_bytes());
+    // TASD has no VI count, so assume one input poll per VI
+    data[0x00C..0x010].copy_from_slice(&(frames as u32).to_le_bytes());
+    data[0x010..0x014].copy_from_slice(&rerecords.to_le_bytes());
+    data[0x014] = if pal { 50 } else { 60 };
+    data[0x015] = ports.len() as u8;
+    data[0x018..0x01C].copy_from_slice(&(frames as u32).to_le_bytes());
+    data[0x01C..0x01E].copy_from_slice(&START_POWER_ON.to_le_bytes());
+    data[0x020..0x024].copy_from_slice(&flags.to_le_bytes());
+    write_str(&mut data, 0x0C4, 32, &rom_name);
+    write_str(&mut data, 0x222, 222, &authors.join(", "));
+    write_str(&mut data, 0x300, 256, description.as_deref().unwrap_or_default());
+
+    for frame in 0..frames {
+        for port in &inputs {
+            let sample = port.get(frame * SAMPLE_LEN..(frame + 1) * SAMPLE_LEN).unwrap_or(&[0; SAMPLE_LEN]);
+            data.extend_from_slice(sample);
+        }
+    }
+
+    Ok(data)
+}
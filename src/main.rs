@@ -1,21 +1,222 @@
 mod app;
+mod attachments;
+mod attributions;
+mod bk2;
+mod browser;
+mod catalog;
+mod checkpoint;
+mod checksum;
+mod clipboard;
+mod compare;
+mod compression;
+mod config;
+mod controller;
+mod convert;
+mod diff;
+mod download;
+mod dtm;
+mod expr;
+mod filter;
+mod finder;
+mod frame_count;
+mod fm2;
+mod heatmap;
+mod input_index;
+mod inventory;
+mod latches;
+mod layout;
+mod lsmv;
+mod m64;
+mod marks;
+mod metadata;
+mod notes;
+mod optimize;
+mod overlay;
+mod packet_list;
+mod picker;
+mod plugins;
+mod repair;
+mod replay;
+mod report;
+mod segment;
+mod signature;
+mod smv;
+mod stats;
+mod tasvideos;
+mod timing;
+mod tree;
 mod tui;
 mod ui;
+mod vbm;
+mod worker;
 
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use app::App;
-use clap::Parser;
+use compression::Compression;
+use config::Config;
+use repair::Salvage;
+use clap::{Parser, Subcommand};
 use tasd_lib::{Serializable, TASD};
-use color_eyre::Result;
+use color_eyre::{eyre::eyre, Result};
 use crossterm::event::{self, Event, KeyEventKind};
 
 /// A CLI interface to read and write TASD files, and to send them to a TAStm32.
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, args_conflicts_with_subcommands = true)]
 struct Args {
-    /// Path to the TASD file
+    /// Path to the TASD file, an HTTP(S) URL to download it from, or a directory to browse; repeat to open several in tabs
     #[arg(short, long)]
-    file: PathBuf,
+    file: Vec<PathBuf>,
+
+    /// More files to open in tabs; switch between them with gt and gT
+    files: Vec<PathBuf>,
+
+    /// Open damaged files without asking, skipping the packets that can't be parsed (listed by :errors)
+    #[arg(long)]
+    lenient: bool,
+
+    /// Block every edit, so a file can be inspected without changing it (:set readonly off lifts it)
+    #[arg(long)]
+    readonly: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Headless commands that run without the TUI
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Write an embedded attachment (see :attachments for indices) to disk
+    Extract {
+        /// Path to the TASD file
+        file: PathBuf,
+        /// Attachment index
+        index: usize,
+        /// Output path
+        output: PathBuf,
+    },
+    /// Convert between TASD and other movie formats (.m64, .dtm, .smv, .vbm, .yaml, .json; .bk2 input only; .fm2, .lsmv, .r08 and .r16m output only), chosen by file extension or --format
+    Convert {
+        /// Movie to read and where to write the converted movie, or with --out-dir every movie to convert
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Format to write (default: from the output file extension)
+        #[arg(short, long, value_enum)]
+        format: Option<convert::Format>,
+        /// Convert every file into this directory, named after the input
+        #[arg(long, requires = "format")]
+        out_dir: Option<PathBuf>,
+        /// Files to convert in parallel with --out-dir (0 for one per core)
+        #[arg(short, long, default_value_t = 1)]
+        jobs: usize,
+    },
+    /// Write a TASD file in another format (yaml and json write every packet with its typed fields, overlay-json/overlay-csv the pressed buttons of each frame for input displays)
+    Export {
+        /// Format to write
+        #[arg(short, long, value_enum)]
+        format: convert::Format,
+        /// Path to the TASD file
+        file: PathBuf,
+        /// Where to write the text (default: standard output, e.g. to pipe json into jq)
+        output: Option<PathBuf>,
+    },
+    /// Build a TASD file from text written by `export` or another movie format, like a BizHawk .bk2
+    Import {
+        /// Format to read (default: from the file extension)
+        #[arg(short, long, value_enum)]
+        format: Option<convert::Format>,
+        /// File to read
+        input: PathBuf,
+        /// Where to write the TASD file
+        output: PathBuf,
+    },
+    /// Generate an ed25519 key pair for signing files (public key goes to KEY.pub)
+    Keygen {
+        /// Where to write the secret key
+        key: PathBuf,
+    },
+    /// Sign a TASD file, replacing any existing signature
+    Sign {
+        /// Path to the TASD file
+        file: PathBuf,
+        /// Secret key from `keygen`
+        #[arg(short, long)]
+        key: PathBuf,
+        /// Write the signed file here instead of overwriting the input
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Print the SHA-256 of a TASD file and of each port's input data
+    Hash {
+        /// Path to the TASD file
+        file: PathBuf,
+        /// Store the input digests in the file, replacing any stored before
+        #[arg(long)]
+        embed: bool,
+        /// With --embed, write the file here instead of overwriting the input
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Print the metadata packets of a TASD file
+    Info {
+        /// Path to the TASD file
+        file: PathBuf,
+    },
+    /// Compare the input of two TASD files frame by frame, side by side
+    Diff {
+        /// First TASD file
+        a: PathBuf,
+        /// Second TASD file
+        b: PathBuf,
+        /// Match frames by content, so inserted or deleted frames don't shift everything after them
+        #[arg(long)]
+        align: bool,
+    },
+    /// Print button press statistics for every port
+    Stats {
+        /// Path to the TASD file
+        file: PathBuf,
+    },
+    /// Print every packet of a TASD file, one per line
+    Dump {
+        /// Path to the TASD file
+        file: PathBuf,
+    },
+    /// Open files in the viewer (the same as giving them without a command)
+    View {
+        /// Paths to TASD files, HTTP(S) URLs or a directory to browse
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Open damaged files without asking, skipping the packets that can't be parsed
+        #[arg(long)]
+        lenient: bool,
+        /// Block every edit, so a file can be inspected without changing it
+        #[arg(long)]
+        readonly: bool,
+    },
+    /// List the TASD files in a directory with their title, console, length and authors
+    Ls {
+        /// Directory to scan
+        dir: PathBuf,
+    },
+    /// Salvage the readable packets of a damaged TASD file
+    Repair {
+        /// Path to the damaged file
+        file: PathBuf,
+        /// Where to write the repaired file
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Check the signatures stored in a TASD file
+    VerifySig {
+        /// Path to the TASD file
+        file: PathBuf,
+        /// Require a signature from this public key (hex or .pub file)
+        #[arg(short, long)]
+        key: Option<String>,
+    },
 }
 
 fn main() -> Result<()> {
@@ -25,17 +226,341 @@ fn main() -> Result<()> {
     // Parse command line arguments
     let args = Args::parse();
 
-    // Read and parse TASD file - fix lifetime issue by cloning the content
-    let content = std::fs::read(&args.file)?;
-    let (_, tasd) = TASD::deserialize(&content).map_err(|e| color_eyre::eyre::eyre!("Failed to parse TASD file: {:?}", e))?;
+    // Run headless commands without entering the TUI
+    match args.command {
+        Some(Command::View { files, lenient, readonly }) => view(files, lenient, readonly),
+        Some(command) => run_command(command),
+        None => {
+            let files: Vec<PathBuf> = args.file.into_iter().chain(args.files).collect();
+            if files.is_empty() {
+                return Err(eyre!("No file given, use --file <FILE>"));
+            }
+            view(files, args.lenient, args.readonly)
+        }
+    }
+}
+
+/// Open files in the TUI
+fn view(mut files: Vec<PathBuf>, lenient: bool, readonly: bool) -> Result<()> {
+    // Download remote files into the cache before opening them
+    for file in &mut files {
+        let file_arg = file.to_string_lossy().to_string();
+        if download::is_url(&file_arg) {
+            *file = download::download_to_cache(&file_arg)?;
+        }
+    }
+
+    // A directory opens the browser, with its first TASD file loaded behind it
+    let browse_dir = if files[0].is_dir() { Some(files.remove(0)) } else { None };
+    if let Some(dir) = &browse_dir {
+        if files.is_empty() {
+            let first = inventory::tasd_files(dir)?.into_iter().next()
+                .ok_or_else(|| eyre!("No TASD files in {}", dir.display()))?;
+            files.push(first);
+        }
+    }
+
+    // Load user configuration
+    let mut config = Config::load()?;
+    config.lenient |= lenient;
+    config.readonly |= readonly;
+
+    let salvage = open_tasd(&files[0], config.lenient)?;
 
     // Initialize application state
-    let app = App::new(tasd, args.file);
+    let banner = salvage.banner();
+    let mut app = App::new(salvage.tasd, files[0].clone(), config);
+    app.banner = banner;
+    app.parse_errors = salvage.dropped;
+    app.arg_list = files;
+    if let Some(dir) = browse_dir {
+        app.open_browser(&dir)?;
+    }
 
     // Run the application using TUI
     run(app)
 }
 
+/// Read and parse a TASD file, decompressing it if needed
+pub fn load_tasd(path: &Path) -> Result<TASD> {
+    let content = compression::decompress(std::fs::read(path)?)?;
+    let (_, tasd) = TASD::deserialize(&content).map_err(|e| eyre!("Failed to parse TASD file: {:?}", e))?;
+    Ok(tasd)
+}
+
+/// Read a TASD file, decompressing it if needed and salvaging the readable packets if it is damaged
+pub fn salvage_tasd(path: &Path) -> Result<Salvage> {
+    let content = compression::decompress(std::fs::read(path)?)?;
+    repair::read(&content)
+}
+
+/// Open a TASD file for the TUI, offering to show whatever is readable if it is damaged
+/// (or showing it without asking when `lenient`)
+fn open_tasd(path: &Path, lenient: bool) -> Result<Salvage> {
+    let salvage = salvage_tasd(path)?;
+    let Some(first) = salvage.dropped.first() else {
+        return Ok(salvage);
+    };
+    if lenient {
+        return Ok(salvage);
+    }
+
+    eprintln!("{} is damaged at offset {:#x} ({}).", path.display(), first.offset, first.reason);
+    eprint!("Open the {} readable packets anyway? [y/N] ", salvage.kept);
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    if !answer.trim().eq_ignore_ascii_case("y") {
+        return Err(eyre!("{} is damaged; open it with --lenient or repair it with `repair`", path.display()));
+    }
+    Ok(salvage)
+}
+
+/// Execute a headless command
+fn run_command(command: Command) -> Result<()> {
+    match command {
+        Command::Extract { file, index, output } => {
+            let tasd = load_tasd(&file)?;
+            let size = attachments::extract(&tasd.packets, index, &output)?;
+            println!("Wrote {} bytes to {}", size, output.display());
+        }
+        Command::Convert { files, format: Some(format), out_dir: Some(out_dir), jobs } => {
+            let results = convert::batch(&files, format, &out_dir, jobs)?;
+            for (input, result) in files.iter().zip(&results) {
+                match result {
+                    Ok((output, size)) => println!("OK      {} -> {} ({} bytes)", input.display(), output.display(), size),
+                    Err(e) => println!("FAILED  {}: {}", input.display(), e),
+                }
+            }
+            let failed = results.iter().filter(|result| result.is_err()).count();
+            println!("Converted {} of {} files into {}", results.len() - failed, results.len(), out_dir.display());
+            if failed > 0 {
+                return Err(eyre!("{} conversions failed", failed));
+            }
+        }
+        Command::Convert { files, format, .. } => {
+            let [input, output] = files.as_slice() else {
+                return Err(eyre!("Expected an input and an output file; use --out-dir to convert several"));
+            };
+            let tasd = convert::read(input)?;
+            let format = format.unwrap_or_else(|| convert::Format::from_path(output));
+            let size = convert::write_as(&tasd, output, format)?;
+            println!("Converted {} to {} ({} bytes)", input.display(), output.display(), size);
+        }
+        Command::Export { format, file, output } => {
+            let tasd = load_tasd(&file)?;
+            let data = convert::encode(&tasd, format)?;
+            match output {
+                Some(output) => {
+                    worker::write_atomic(&output, &data)?;
+                    println!("Wrote {} bytes to {}", data.len(), output.display());
+                }
+                None => std::io::stdout().write_all(&data)?,
+            }
+        }
+        Command::Import { format, input, output } => {
+            let format = format.unwrap_or_else(|| convert::Format::from_path(&input));
+            let tasd = convert::read_as(&input, format)?;
+            let data = compression::compress(tasd.serialize(), Compression::from_path(&output), None)?;
+            worker::write_atomic(&output, &data)?;
+            println!("Imported {} packets into {}", tasd.packets.len(), output.display());
+        }
+        Command::Keygen { key } => {
+            let public = signature::generate_key(&key)?;
+            println!("Wrote secret key to {}", key.display());
+            println!("Public key: {}", public);
+        }
+        Command::Sign { file, key, output } => {
+            let mut tasd = load_tasd(&file)?;
+            let public = signature::sign(&mut tasd, &signature::load_key(&key)?);
+            let output = output.unwrap_or(file);
+            let data = compression::compress(tasd.serialize(), Compression::from_path(&output), None)?;
+            worker::write_atomic(&output, &data)?;
+            println!("Signed {} with {}", output.display(), public);
+        }
+        Command::Hash { file, embed, output } => {
+            let data = std::fs::read(&file)?;
+            let mut tasd = load_tasd(&file)?;
+            let mut ports = App::detect_ports(&tasd);
+            ports.sort();
+            for line in checksum::report(&data, &tasd.packets, &ports) {
+                println!("{}", line);
+            }
+            if embed {
+                checksum::update_checksums(&mut tasd, &ports);
+                let output = output.unwrap_or(file);
+                let data = compression::compress(tasd.serialize(), Compression::from_path(&output), None)?;
+                worker::write_atomic(&output, &data)?;
+                println!("Stored input checksums for {} ports in {}", ports.len(), output.display());
+            }
+        }
+        Command::Info { file } => print_info(&load_tasd(&file)?),
+        Command::Diff { a, b, align } => print_diff(&load_tasd(&a)?, &load_tasd(&b)?, align),
+        Command::Stats { file } => {
+            for line in stats::report(&load_tasd(&file)?.packets) {
+                println!("{}", line);
+            }
+        }
+        Command::Dump { file } => {
+            let tasd = load_tasd(&file)?;
+            for (index, packet) in tasd.packets.iter().enumerate() {
+                println!(
+                    "{:>6}  {:<16} {:>8}  {}",
+                    index,
+                    report::packet_kind(packet),
+                    packet.serialize().len(),
+                    report::describe_packet(packet),
+                );
+            }
+        }
+        Command::View { files, lenient, readonly } => view(files, lenient, readonly)?,
+        Command::Ls { dir } => list_directory(&dir)?,
+        Command::Repair { file, output } => {
+            let data = compression::decompress(std::fs::read(&file)?)?;
+            let salvage = repair::salvage(&data)?;
+            for dropped in &salvage.dropped {
+                println!(
+                    "Dropped {} bytes at offset {:#x}: {}",
+                    dropped.length, dropped.offset, dropped.reason,
+                );
+            }
+            let data = compression::compress(salvage.tasd.serialize(), Compression::from_path(&output), None)?;
+            worker::write_atomic(&output, &data)?;
+            println!("Recovered {} packets into {}", salvage.kept, output.display());
+        }
+        Command::VerifySig { file, key } => {
+            let mut tasd = load_tasd(&file)?;
+            let results = signature::verify(&mut tasd);
+            if results.is_empty() {
+                return Err(eyre!("{} is not signed", file.display()));
+            }
+            for (public, valid) in &results {
+                println!("{} {}", if *valid { "VALID  " } else { "INVALID" }, public);
+            }
+
+            let required = key.map(|key| signature::parse_public_key(&key)).transpose()?;
+            let trusted = results.iter()
+                .any(|(public, valid)| *valid && required.as_ref().is_none_or(|key| key == public));
+            if !trusted || results.iter().any(|(_, valid)| !valid) {
+                return Err(eyre!("Signature check failed"));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Print the metadata packets of a file, followed by its length
+fn print_info(tasd: &TASD) {
+    for packet in &tasd.packets {
+        let kind = report::packet_kind(packet);
+        if report::packet_group(&kind) == "Inputs" {
+            continue;
+        }
+        println!("{}: {}", kind, report::describe_packet(packet));
+    }
+
+    let summary = inventory::summarize(tasd);
+    println!("Frames: {}", summary.frames);
+    println!("Duration: {}", summary.duration);
+}
+
+/// Print the frames that differ between two files, with the input of each side by side
+fn print_diff(a: &TASD, b: &TASD, align: bool) {
+    let mut same = true;
+    for port in compare::compare(&a.packets, &b.packets, align) {
+        if port.differences.is_empty() {
+            continue;
+        }
+        same = false;
+        println!(
+            "Port {}: {} frames differ ({} -> {} frames)",
+            port.port, port.differences.len(), port.frames.0, port.frames.1,
+        );
+
+        let a_controller = controller::detect_controller(&a.packets, port.port);
+        let b_controller = controller::detect_controller(&b.packets, port.port);
+        let a_inputs = checkpoint::port_inputs(&a.packets, port.port);
+        let b_inputs = checkpoint::port_inputs(&b.packets, port.port);
+        let side = |controller: controller::ControllerType, inputs: &[u8], frame: Option<usize>| match frame {
+            Some(frame) => {
+                let size = controller.frame_size().max(1);
+                let input = inputs.get(frame * size..(frame + 1) * size).unwrap_or_default();
+                (format!("{:>7}", frame), controller.format_frame(input))
+            }
+            None => (format!("{:>7}", "-"), String::new()),
+        };
+        let rows: Vec<_> = port.differences.iter()
+            .map(|difference| (side(a_controller, &a_inputs, difference.a), side(b_controller, &b_inputs, difference.b)))
+            .collect();
+        let width = rows.iter().map(|((_, a_input), _)| a_input.chars().count()).max().unwrap_or(0);
+        for ((a_frame, a_input), (b_frame, b_input)) in rows {
+            let line = format!("{}  {:<width$}  | {}  {}", a_frame, a_input, b_frame, b_input, width = width);
+            println!("{}", line.trim_end());
+        }
+    }
+    if same {
+        println!("Inputs are identical");
+    }
+}
+
+/// Print a table of the metadata of every TASD file in a directory
+fn list_directory(dir: &Path) -> Result<()> {
+    let files = inventory::tasd_files(dir)?;
+    if files.is_empty() {
+        return Err(eyre!("No TASD files in {}", dir.display()));
+    }
+
+    let mut rows = vec![["File", "Title", "Console", "Frames", "Duration", "Verified", "Authors"].map(String::from)];
+    let mut unreadable = Vec::new();
+    for file in &files {
+        let name = file.file_name().unwrap_or_default().to_string_lossy().to_string();
+        // Parse errors carry the whole remaining input, so only the name is reported
+        let Ok(tasd) = load_tasd(file) else {
+            unreadable.push(name);
+            continue;
+        };
+        let summary = inventory::summarize(&tasd);
+        let verified = match summary.verified {
+            Some(true) => "Yes",
+            Some(false) => "No",
+            None => "-",
+        };
+        rows.push([
+            name,
+            summary.title,
+            summary.console,
+            summary.frames.to_string(),
+            summary.duration,
+            verified.to_string(),
+            summary.authors.join(", "),
+        ]);
+    }
+
+    let mut widths = [0; 7];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in &rows {
+        let line: Vec<String> = row.iter().zip(widths).enumerate()
+            .map(|(i, (cell, width))| match i {
+                // Right-align the numeric columns
+                3 | 4 => format!("{:>width$}", cell),
+                _ => format!("{:<width$}", cell),
+            })
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    }
+
+    for name in &unreadable {
+        eprintln!("Skipped {}: not a readable TASD file", name);
+    }
+    Ok(())
+}
+
+/// How often the TUI redraws while a background task runs
+const TASK_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 fn run(mut app: App) -> Result<()> {
     // Setup terminal
     let mut terminal = tui::init()?;
@@ -45,6 +570,14 @@ fn run(mut app: App) -> Result<()> {
         // Draw UI - pass mutable reference to app
         terminal.draw(|frame| ui::components::render(&mut app, frame))?;
 
+        // While background tasks, renderer plugins or finder titles run, wake up regularly to turn
+        // the spinner and pick up their results
+        let finding = app.finder.as_ref().is_some_and(|finder| finder.busy());
+        if (app.worker.busy() || app.renderers.busy() || finding) && !event::poll(TASK_POLL_INTERVAL)? {
+            app.poll_tasks();
+            continue;
+        }
+
         // Handle events
         match event::read()? {
             // It's important to check that the event is a key press event as
@@ -54,6 +587,7 @@ fn run(mut app: App) -> Result<()> {
             }
             _ => {}
         }
+        app.poll_tasks();
     }
 
     // Restore terminal
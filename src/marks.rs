@@ -0,0 +1,41 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use color_eyre::Result;
+
+/// Sidecar file holding a movie's marks, one `<letter> <frame>` line each, e.g. `movie.tasd.marks`
+fn sidecar(file: &Path) -> PathBuf {
+    let mut name = file.as_os_str().to_owned();
+    name.push(".marks");
+    PathBuf::from(name)
+}
+
+/// Marks saved for a movie, or none if it has no sidecar file
+pub fn load(file: &Path) -> BTreeMap<char, usize> {
+    let Ok(content) = std::fs::read_to_string(sidecar(file)) else {
+        return BTreeMap::new();
+    };
+    content.lines()
+        .filter_map(|line| {
+            let (letter, frame) = line.trim().split_once(' ')?;
+            let mut chars = letter.chars();
+            match (chars.next(), chars.next()) {
+                (Some(letter), None) if letter.is_ascii_alphabetic() => Some((letter, frame.trim().parse().ok()?)),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Write a movie's marks to its sidecar file, removing the file once there are none
+pub fn save(file: &Path, marks: &BTreeMap<char, usize>) -> Result<()> {
+    let path = sidecar(file);
+    if marks.is_empty() {
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        return Ok(());
+    }
+    let lines: Vec<String> = marks.iter().map(|(letter, frame)| format!("{} {}", letter, frame)).collect();
+    std::fs::write(path, lines.join("\n") + "\n")?;
+    Ok(())
+}
@@ -0,0 +1,158 @@
+use tasd_lib::{
+    Attribution, AttributionType, Category, Comment, EmulatorName, EmulatorVersion, GameTitle, MovieLicense, Packet,
+    RomName, SourceLink,
+};
+use crate::attributions;
+
+/// Kinds of editable text metadata
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    GameTitle,
+    RomName,
+    Category,
+    Attribution,
+    EmulatorName,
+    EmulatorVersion,
+    SourceLink,
+    MovieLicense,
+    Comment,
+}
+
+impl FieldKind {
+    /// Packet holding a value of this kind (attributions are added as authors)
+    fn packet(&self, value: String) -> Packet {
+        match self {
+            FieldKind::GameTitle => Packet::GameTitle(GameTitle { title: value }),
+            FieldKind::RomName => Packet::RomName(RomName { name: value }),
+            FieldKind::Category => Packet::Category(Category { category: value }),
+            FieldKind::Attribution => Packet::Attribution(Attribution { attribution_type: AttributionType::Author, name: value }),
+            FieldKind::EmulatorName => Packet::EmulatorName(EmulatorName { name: value }),
+            FieldKind::EmulatorVersion => Packet::EmulatorVersion(EmulatorVersion { version: value }),
+            FieldKind::SourceLink => Packet::SourceLink(SourceLink { link: value }),
+            FieldKind::MovieLicense => Packet::MovieLicense(MovieLicense { license: value }),
+            FieldKind::Comment => Packet::Comment(Comment { comment: value }),
+        }
+    }
+
+    /// Kind of a packet shown in the pane; comments written by tasd-cli itself are left out
+    fn of(packet: &Packet) -> Option<Self> {
+        Some(match packet {
+            Packet::GameTitle(_) => FieldKind::GameTitle,
+            Packet::RomName(_) => FieldKind::RomName,
+            Packet::Category(_) => FieldKind::Category,
+            Packet::Attribution(_) => FieldKind::Attribution,
+            Packet::EmulatorName(_) => FieldKind::EmulatorName,
+            Packet::EmulatorVersion(_) => FieldKind::EmulatorVersion,
+            Packet::SourceLink(_) => FieldKind::SourceLink,
+            Packet::MovieLicense(_) => FieldKind::MovieLicense,
+            Packet::Comment(c) if !c.comment.starts_with("tasd-cli:") => FieldKind::Comment,
+            _ => return None,
+        })
+    }
+}
+
+/// Fields in the order the pane lists them, with the label used when the field is missing
+const FIELDS: [(FieldKind, &str); 9] = [
+    (FieldKind::GameTitle, "Game Title"),
+    (FieldKind::RomName, "ROM"),
+    (FieldKind::Category, "Category"),
+    (FieldKind::Attribution, "Author"),
+    (FieldKind::EmulatorName, "Emulator"),
+    (FieldKind::EmulatorVersion, "Emulator Version"),
+    (FieldKind::SourceLink, "Source"),
+    (FieldKind::MovieLicense, "License"),
+    (FieldKind::Comment, "Comment"),
+];
+
+/// One row of the metadata pane
+pub struct Field {
+    pub kind: FieldKind,
+    pub label: String,
+    pub value: String,
+    /// Position of the packet holding the value, or None if editing creates it
+    pub position: Option<usize>,
+}
+
+/// State of the metadata pane
+pub struct MetadataPane {
+    /// Selected row
+    pub selected: usize,
+    /// Value being typed, when editing the selected row
+    pub editing: Option<String>,
+}
+
+impl MetadataPane {
+    pub fn new() -> Self {
+        Self {
+            selected: 0,
+            editing: None,
+        }
+    }
+}
+
+/// Every editable field: one row per existing packet, or an empty row for a missing field
+pub fn fields(packets: &[Packet]) -> Vec<Field> {
+    let mut fields = Vec::new();
+    for (kind, label) in FIELDS {
+        let before = fields.len();
+        for (position, packet) in packets.iter().enumerate().filter(|(_, packet)| FieldKind::of(packet) == Some(kind)) {
+            let (label, value) = match packet {
+                Packet::GameTitle(p) => (label.to_string(), p.title.clone()),
+                Packet::RomName(p) => (label.to_string(), p.name.clone()),
+                Packet::Category(p) => (label.to_string(), p.category.clone()),
+                Packet::Attribution(p) => (attributions::type_name(p.attribution_type as u8).to_string(), p.name.clone()),
+                Packet::EmulatorName(p) => (label.to_string(), p.name.clone()),
+                Packet::EmulatorVersion(p) => (label.to_string(), p.version.clone()),
+                Packet::SourceLink(p) => (label.to_string(), p.link.clone()),
+                Packet::MovieLicense(p) => (label.to_string(), p.license.clone()),
+                Packet::Comment(p) => (label.to_string(), p.comment.clone()),
+                _ => continue,
+            };
+            fields.push(Field { kind, label, value, position: Some(position) });
+        }
+        if fields.len() == before {
+            fields.push(Field { kind, label: label.to_string(), value: String::new(), position: None });
+        }
+    }
+    fields
+}
+
+/// Where a new packet of a kind goes: after the last one like it, or before the input packets
+fn insert_position(packets: &[Packet], kind: FieldKind) -> usize {
+    if let Some(last) = packets.iter().rposition(|packet| FieldKind::of(packet) == Some(kind)) {
+        return last + 1;
+    }
+    packets.iter()
+        .position(|packet| matches!(
+            packet,
+            Packet::PortController(_) | Packet::InputChunk(_) | Packet::InputMoment(_) | Packet::Transition(_)
+        ))
+        .unwrap_or(packets.len())
+}
+
+/// Set the value of a field, creating its packet if needed; an empty value removes the packet
+pub fn set(packets: &mut Vec<Packet>, field: &Field, value: String) {
+    let Some(position) = field.position else {
+        if !value.is_empty() {
+            packets.insert(insert_position(packets, field.kind), field.kind.packet(value));
+        }
+        return;
+    };
+    if value.is_empty() {
+        packets.remove(position);
+        return;
+    }
+
+    match &mut packets[position] {
+        Packet::GameTitle(p) => p.title = value,
+        Packet::RomName(p) => p.name = value,
+        Packet::Category(p) => p.category = value,
+        Packet::Attribution(p) => p.name = value,
+        Packet::EmulatorName(p) => p.name = value,
+        Packet::EmulatorVersion(p) => p.version = value,
+        Packet::SourceLink(p) => p.link = value,
+        Packet::MovieLicense(p) => p.license = value,
+        Packet::Comment(p) => p.comment = value,
+        _ => {}
+    }
+}
@@ -0,0 +1,39 @@
+use std::collections::BTreeMap;
+use tasd_lib::{Comment, Packet};
+
+/// Prefix marking Comment packets that carry a note on a frame
+///
+/// Notes are stored as `tasd-cli:note frame=<n> <text>`, so other tools
+/// treat them as ordinary comments.
+const PREFIX: &str = "tasd-cli:note";
+
+/// Parse a note comment into its frame and text
+fn parse_comment(comment: &str) -> Option<(usize, &str)> {
+    let rest = comment.strip_prefix(PREFIX)?.trim_start();
+    let (frame, text) = rest.split_once(' ').unwrap_or((rest, ""));
+    let frame = frame.strip_prefix("frame=")?.parse().ok()?;
+    Some((frame, text.trim()))
+}
+
+/// Whether a packet is a frame note written by this tool
+pub fn is_note_packet(packet: &Packet) -> bool {
+    matches!(packet, Packet::Comment(c) if parse_comment(&c.comment).is_some())
+}
+
+/// Every note in the file, by frame
+pub fn notes(packets: &[Packet]) -> BTreeMap<usize, String> {
+    packets.iter()
+        .filter_map(|packet| match packet {
+            Packet::Comment(c) => parse_comment(&c.comment).map(|(frame, text)| (frame, text.to_string())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Replace the note on a frame, or remove it when there is no text
+pub fn set_note(packets: &mut Vec<Packet>, frame: usize, text: Option<&str>) {
+    packets.retain(|packet| !matches!(packet, Packet::Comment(c) if parse_comment(&c.comment).is_some_and(|(f, _)| f == frame)));
+    if let Some(text) = text {
+        packets.push(Packet::Comment(Comment { comment: format!("{} frame={} {}", PREFIX, frame, text) }));
+    }
+}
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+use tasd_lib::Packet;
+
+/// What an optimization pass changed
+pub struct Optimized {
+    /// Input chunks folded into the chunk before them
+    pub merged_chunks: usize,
+    /// Packets dropped because an identical one was already declared
+    pub removed_duplicates: usize,
+}
+
+/// Packets that are part of the input stream, where repeats are meaningful
+fn is_stream_packet(packet: &Packet) -> bool {
+    matches!(
+        packet,
+        Packet::InputChunk(_) | Packet::InputMoment(_) | Packet::Transition(_) | Packet::LagFrameChunk(_) | Packet::Comment(_)
+    )
+}
+
+/// Merge input chunks of the same port within a run of chunks and drop repeated declarations
+pub fn optimize(packets: &mut Vec<Packet>) -> Optimized {
+    let mut result = Optimized { merged_chunks: 0, removed_duplicates: 0 };
+    let mut declared: Vec<String> = Vec::new();
+    let mut kept: Vec<Packet> = Vec::with_capacity(packets.len());
+    // Last chunk of each port in the current run of input chunks
+    let mut open_chunks: HashMap<u8, usize> = HashMap::new();
+
+    for packet in std::mem::take(packets) {
+        let Packet::InputChunk(chunk) = &packet else {
+            // Any other packet ends the run, so chunks never move past it
+            open_chunks.clear();
+            if !is_stream_packet(&packet) {
+                let text = format!("{:?}", packet);
+                if declared.contains(&text) {
+                    result.removed_duplicates += 1;
+                    continue;
+                }
+                declared.push(text);
+            }
+            kept.push(packet);
+            continue;
+        };
+
+        if let Some(Packet::InputChunk(last)) = open_chunks.get(&chunk.port).and_then(|i| kept.get_mut(*i)) {
+            last.inputs.extend_from_slice(&chunk.inputs);
+            result.merged_chunks += 1;
+            continue;
+        }
+        open_chunks.insert(chunk.port, kept.len());
+        kept.push(packet);
+    }
+
+    *packets = kept;
+    result
+}
@@ -0,0 +1,61 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use color_eyre::Result;
+use serde_json::json;
+use tasd_lib::{Packet, TASD};
+
+use crate::checkpoint::port_inputs;
+use crate::controller;
+
+/// Buttons pressed on one port of one frame
+struct Row {
+    frame: usize,
+    port: u8,
+    buttons: Vec<&'static str>,
+}
+
+/// One row per frame and port with input, ordered by frame so a display script can read it straight through
+fn rows(packets: &[Packet]) -> Vec<Row> {
+    let ports: BTreeSet<u8> = packets.iter()
+        .filter_map(|packet| match packet {
+            Packet::InputChunk(chunk) => Some(chunk.port),
+            _ => None,
+        })
+        .collect();
+
+    let mut rows = Vec::new();
+    for port in ports {
+        let controller = controller::detect_controller(packets, port);
+        let inputs = port_inputs(packets, port);
+        for (frame, input) in inputs.chunks_exact(controller.frame_size().max(1)).enumerate() {
+            let buttons = controller.buttons().iter()
+                .filter(|button| controller.is_pressed(button, input))
+                .map(|button| button.name)
+                .collect();
+            rows.push(Row { frame, port, buttons });
+        }
+    }
+    // Stable, so ports stay in order within a frame
+    rows.sort_by_key(|row| row.frame);
+    rows
+}
+
+/// Pressed buttons as CSV: `frame,port,buttons`, with the buttons joined by `+`
+pub fn export_csv(tasd: &TASD) -> Result<Vec<u8>> {
+    let mut out = String::from("frame,port,buttons\n");
+    for row in rows(&tasd.packets) {
+        writeln!(out, "{},{},{}", row.frame, row.port, row.buttons.join("+"))?;
+    }
+    Ok(out.into_bytes())
+}
+
+/// Pressed buttons as a JSON array of `{"frame", "port", "buttons"}` objects, one per line
+pub fn export_json(tasd: &TASD) -> Result<Vec<u8>> {
+    let lines: Vec<String> = rows(&tasd.packets).into_iter()
+        .map(|row| json!({ "frame": row.frame, "port": row.port, "buttons": row.buttons }).to_string())
+        .collect();
+    if lines.is_empty() {
+        return Ok(b"[]\n".to_vec());
+    }
+    Ok(format!("[\n{}\n]\n", lines.join(",\n")).into_bytes())
+}
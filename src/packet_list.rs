@@ -0,0 +1,126 @@
+use tasd_lib::{Packet, Serializable};
+use crate::report;
+
+/// One packet as listed in the packet browser
+pub struct PacketRow {
+    /// Packet key, as written in the file
+    pub key: u16,
+    /// Packet type name
+    pub kind: String,
+    /// Serialized size in bytes, including the key and length
+    pub size: usize,
+    /// Parsed one-line description
+    pub summary: String,
+}
+
+impl PacketRow {
+    fn new(packet: &Packet) -> Self {
+        let data = packet.serialize();
+        Self {
+            key: data.get(..2).map_or(0, |key| u16::from_be_bytes([key[0], key[1]])),
+            kind: report::packet_kind(packet),
+            size: data.len(),
+            summary: report::describe_packet(packet),
+        }
+    }
+}
+
+/// State of the full-screen packet browser
+pub struct PacketList {
+    /// Every packet of the file, in file order
+    pub rows: Vec<PacketRow>,
+    /// Index of the highlighted packet
+    pub selected: usize,
+    /// First visible row
+    pub scroll: usize,
+    /// Rows that fit on screen, updated when rendering
+    pub page: usize,
+    /// Hex dump of the highlighted packet, when open
+    pub hex: Option<HexView>,
+}
+
+/// Bytes per line of a hex dump
+pub const HEX_WIDTH: usize = 16;
+
+/// Hex and ASCII dump of one serialized packet
+pub struct HexView {
+    /// Index of the packet in the file
+    pub index: usize,
+    pub kind: String,
+    /// Serialized packet: key, length and payload
+    pub data: Vec<u8>,
+    /// Bytes holding the key
+    pub key_len: usize,
+    /// Bytes holding the payload length, including the byte giving its size
+    pub length_len: usize,
+    /// First visible line
+    pub scroll: usize,
+    /// Lines that fit on screen, updated when rendering
+    pub page: usize,
+}
+
+impl HexView {
+    pub fn new(index: usize, packet: &Packet) -> Self {
+        let data = packet.serialize();
+        // The byte after the key says how many bytes the payload length takes
+        let length_len = data.get(2).map_or(0, |size| 1 + *size as usize).min(data.len().saturating_sub(2));
+        Self {
+            index,
+            kind: report::packet_kind(packet),
+            key_len: data.len().min(2),
+            length_len,
+            data,
+            scroll: 0,
+            page: 20,
+        }
+    }
+
+    /// Payload length as written in the header
+    pub fn payload_len(&self) -> usize {
+        self.data[self.key_len..self.key_len + self.length_len].iter()
+            .skip(1)
+            .fold(0, |len, byte| (len << 8) | *byte as usize)
+    }
+
+    pub fn lines(&self) -> usize {
+        self.data.len().div_ceil(HEX_WIDTH)
+    }
+
+    /// Scroll by a number of lines, stopping at either end
+    pub fn scroll_by(&mut self, delta: isize) {
+        let last = self.lines().saturating_sub(self.page.max(1));
+        self.scroll = self.scroll.saturating_add_signed(delta).min(last);
+    }
+}
+
+impl PacketList {
+    pub fn new(packets: &[Packet]) -> Self {
+        Self {
+            rows: packets.iter().map(PacketRow::new).collect(),
+            selected: 0,
+            scroll: 0,
+            page: 20,
+            hex: None,
+        }
+    }
+
+    /// Move the highlight by a number of rows, stopping at either end
+    pub fn move_by(&mut self, delta: isize) {
+        let last = self.rows.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+
+    /// Highlight a row, clamped to the list
+    pub fn jump_to(&mut self, index: usize) {
+        self.selected = index.min(self.rows.len().saturating_sub(1));
+    }
+
+    /// Scroll so the highlighted row is visible
+    pub fn keep_visible(&mut self) {
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.selected >= self.scroll + self.page.max(1) {
+            self.scroll = self.selected + 1 - self.page.max(1);
+        }
+    }
+}
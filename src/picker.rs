@@ -0,0 +1,83 @@
+/// What a confirmed picker selection is applied to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickerTarget {
+    /// The MovieLicense packet
+    License,
+    /// The Category packet
+    Category,
+}
+
+/// A searchable list popup for choosing a value
+pub struct Picker {
+    /// Popup title
+    pub title: String,
+    /// Value and description of every choice
+    pub items: Vec<(String, String)>,
+    /// Search text typed so far
+    pub query: String,
+    /// Index into the filtered list
+    pub selected: usize,
+    /// Where the chosen value goes
+    pub target: PickerTarget,
+}
+
+impl Picker {
+    pub fn new(title: &str, items: &[(&str, &str)], target: PickerTarget) -> Self {
+        Self {
+            title: title.to_string(),
+            items: items.iter()
+                .map(|(value, description)| (value.to_string(), description.to_string()))
+                .collect(),
+            query: String::new(),
+            selected: 0,
+            target,
+        }
+    }
+
+    /// Items whose value or description contains the query (case-insensitive)
+    pub fn filtered(&self) -> Vec<&(String, String)> {
+        let query = self.query.to_lowercase();
+        self.items.iter()
+            .filter(|(value, description)| {
+                value.to_lowercase().contains(&query) || description.to_lowercase().contains(&query)
+            })
+            .collect()
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.selected = 0;
+    }
+
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.selected = 0;
+    }
+
+    pub fn next(&mut self) {
+        if self.selected + 1 < self.filtered().len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Complete the query to the highlighted value so it can be extended
+    pub fn complete(&mut self) {
+        if let Some((value, _)) = self.filtered().get(self.selected) {
+            self.query = value.clone();
+            self.selected = 0;
+        }
+    }
+
+    /// The highlighted value, or the typed text when nothing matches
+    pub fn selection(&self) -> Option<String> {
+        match self.filtered().get(self.selected) {
+            Some((value, _)) => Some(value.clone()),
+            None if !self.query.trim().is_empty() => Some(self.query.trim().to_string()),
+            None => None,
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant};
+use tasd_lib::{Packet, Serializable};
+use crate::config::Config;
+use crate::tree;
+
+/// How long a renderer command may run before its packet is shown without it
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Sidebar rows (label and text) describing one packet
+pub type Rows = Vec<(String, String)>;
+
+/// Renders packets the built-in views don't understand, like custom or experimental packets
+pub trait PacketRenderer {
+    /// Rows to show for the packet at `index`, or None to leave it to the next renderer
+    fn render(&self, index: usize, packet: &Packet) -> Option<Rows>;
+
+    /// Whether results are still being worked out, so the UI should redraw to pick them up
+    fn busy(&self) -> bool {
+        false
+    }
+}
+
+/// Packet renderers, asked in registration order
+#[derive(Default)]
+pub struct Registry {
+    renderers: Vec<Box<dyn PacketRenderer>>,
+}
+
+impl Registry {
+    /// Registry with the renderers enabled in the config
+    pub fn new(config: &Config) -> Self {
+        let mut registry = Registry::default();
+        if let Some(command) = &config.renderer {
+            registry.register(Box::new(CommandRenderer::new(command.clone())));
+        }
+        registry
+    }
+
+    /// Add a renderer, asked after the ones already registered
+    pub fn register(&mut self, renderer: Box<dyn PacketRenderer>) {
+        self.renderers.push(renderer);
+    }
+
+    /// Rows from the first renderer that handles the packet at `index`
+    pub fn render(&self, index: usize, packet: &Packet) -> Option<Rows> {
+        self.renderers.iter().find_map(|renderer| renderer.render(index, packet))
+    }
+
+    pub fn busy(&self) -> bool {
+        self.renderers.iter().any(|renderer| renderer.busy())
+    }
+}
+
+/// Renders packets with an external command, set with `:set renderer COMMAND`
+///
+/// The command runs through `sh -c` with the packet on stdin as a JSON object in the same
+/// layout as `tasd-cli export`. It prints one `Label: text` row per line, or nothing to leave
+/// the packet to the built-in display. Commands run one at a time on a background thread, and
+/// results are cached per packet index and content, so each packet is rendered once per change.
+/// Until its result comes in, a packet is shown with the built-in display.
+pub struct CommandRenderer {
+    jobs: Sender<(Key, String)>,
+    results: Receiver<(Key, Option<Rows>)>,
+    cache: RefCell<HashMap<Key, Option<Rows>>>,
+    queued: RefCell<HashSet<Key>>,
+}
+
+/// Packet index and a hash of its contents
+type Key = (usize, u64);
+
+impl CommandRenderer {
+    pub fn new(command: String) -> Self {
+        let (jobs, job_receiver) = mpsc::channel::<(Key, String)>();
+        let (result_sender, results) = mpsc::channel();
+        std::thread::spawn(move || {
+            for (key, input) in job_receiver {
+                // The receiver only goes away when the renderer is replaced
+                if result_sender.send((key, run(&command, input))).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { jobs, results, cache: RefCell::new(HashMap::new()), queued: RefCell::new(HashSet::new()) }
+    }
+
+    /// Move finished results into the cache
+    fn collect(&self) {
+        for (key, rows) in self.results.try_iter() {
+            self.queued.borrow_mut().remove(&key);
+            self.cache.borrow_mut().insert(key, rows);
+        }
+    }
+}
+
+/// Run the command on one packet, giving up on failure or timeout
+fn run(command: &str, input: String) -> Option<Rows> {
+    let mut child = Command::new("sh")
+        .args(["-c", command])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    // Feed stdin and drain stdout on their own threads, so a command that ignores its input
+    // or prints more than a pipe holds can't block before the timeout
+    let mut stdin = child.stdin.take()?;
+    std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let mut stdout = child.stdout.take()?;
+    let reader = std::thread::spawn(move || {
+        let mut output = String::new();
+        stdout.read_to_string(&mut output).map(|_| output)
+    });
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().ok()? {
+            break status;
+        }
+        if started.elapsed() > COMMAND_TIMEOUT {
+            let _ = child.kill();
+            let _ = child.wait();
+            return None;
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    };
+    if !status.success() {
+        return None;
+    }
+
+    let output = reader.join().ok()?.ok()?;
+    let rows: Rows = output.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| match line.split_once(':') {
+            Some((label, text)) => (label.trim().to_string(), text.trim().to_string()),
+            None => ("Packet".to_string(), line.trim().to_string()),
+        })
+        .collect();
+    Some(rows).filter(|rows| !rows.is_empty())
+}
+
+impl PacketRenderer for CommandRenderer {
+    fn render(&self, index: usize, packet: &Packet) -> Option<Rows> {
+        // Input is shown in the input table, and would make for huge commands
+        if matches!(packet, Packet::InputChunk(_) | Packet::InputMoment(_)) {
+            return None;
+        }
+
+        self.collect();
+        let mut hasher = DefaultHasher::new();
+        packet.serialize().hash(&mut hasher);
+        let key = (index, hasher.finish());
+        if let Some(rows) = self.cache.borrow().get(&key) {
+            return rows.clone();
+        }
+        if self.queued.borrow_mut().insert(key) {
+            let input = tree::packet_to_value(packet, &[]).to_string();
+            // The thread only stops once this renderer is dropped
+            let _ = self.jobs.send((key, input));
+        }
+        None
+    }
+
+    fn busy(&self) -> bool {
+        self.collect();
+        !self.queued.borrow().is_empty()
+    }
+}
@@ -0,0 +1,143 @@
+use color_eyre::{eyre::eyre, Result};
+use tasd_lib::{Serializable, TASD};
+
+/// "TASD" magic, u16 version and u8 key width
+const HEADER_LEN: usize = 7;
+
+/// Packet keys defined by the TASD spec, used to find the next packet after damage
+const KNOWN_KEYS: &[u16] = &[
+    0x0001, 0x0002, 0x0003, 0x0004, 0x0005, 0x0006, 0x0007, 0x0008, 0x0009, 0x000A, 0x000B,
+    0x000C, 0x000D, 0x000E, 0x000F, 0x0010, 0x0011, 0x0012, 0x0013, 0x0014, 0x0015,
+    0x00F0, 0x00F1,
+    0x0100, 0x0101, 0x0102, 0x0103,
+    0x0200, 0x0201, 0x0202, 0x0203,
+    0x0300,
+    0xFE01, 0xFE02, 0xFE03, 0xFE04, 0xFE05,
+    0xFF01, 0xFF02, 0xFF03,
+];
+
+/// A byte range that had to be dropped
+pub struct Dropped {
+    /// Offset of the first dropped byte
+    pub offset: usize,
+    /// Number of bytes dropped
+    pub length: usize,
+    /// Why the range could not be used
+    pub reason: &'static str,
+    /// First bytes of the range, kept to show what was skipped
+    pub bytes: Vec<u8>,
+}
+
+/// Bytes of a dropped range kept for display
+const KEPT_BYTES: usize = 16;
+
+/// Outcome of salvaging a damaged file
+pub struct Salvage {
+    /// Every packet that could be recovered
+    pub tasd: TASD,
+    /// Number of packets recovered
+    pub kept: usize,
+    /// Ranges that were skipped
+    pub dropped: Vec<Dropped>,
+}
+
+impl Salvage {
+    /// Warning to show above the inputs, if anything was skipped
+    pub fn banner(&self) -> Option<String> {
+        let first = self.dropped.first()?;
+        let dropped: usize = self.dropped.iter().map(|d| d.length).sum();
+        Some(format!(
+            "DAMAGED FILE: parsing failed at offset {:#x} ({}); {} bytes skipped, showing {} recovered packets (:errors)",
+            first.offset, first.reason, dropped, self.kept,
+        ))
+    }
+}
+
+/// Read a big-endian unsigned number
+fn read_be(bytes: &[u8]) -> usize {
+    bytes.iter().fold(0, |value, byte| (value << 8) | *byte as usize)
+}
+
+/// Length of the packet framed at `pos` (key, length exponent, length, payload), if it fits
+fn framed_length(data: &[u8], pos: usize, key_width: usize) -> Option<usize> {
+    let exp_pos = pos + key_width;
+    let exp = *data.get(exp_pos)? as usize;
+    if exp == 0 || exp > 8 {
+        return None;
+    }
+    let payload_len = read_be(data.get(exp_pos + 1..exp_pos + 1 + exp)?);
+    let total = key_width.checked_add(1 + exp)?.checked_add(payload_len)?;
+    if pos.checked_add(total)? > data.len() {
+        return None;
+    }
+    Some(total)
+}
+
+/// Whether the bytes at `pos` form a packet tasd-lib accepts, returning its length
+fn valid_packet(data: &[u8], pos: usize, header: &[u8], key_width: usize) -> Option<usize> {
+    let key = read_be(data.get(pos..pos + key_width)?) as u16;
+    if !KNOWN_KEYS.contains(&key) {
+        return None;
+    }
+    let length = framed_length(data, pos, key_width)?;
+
+    // Let tasd-lib judge the payload by parsing it as a one-packet file
+    let mut single = header.to_vec();
+    single.extend_from_slice(&data[pos..pos + length]);
+    match TASD::deserialize(&single) {
+        Ok((_, tasd)) if tasd.packets.len() == 1 => Some(length),
+        _ => None,
+    }
+}
+
+/// Parse a file, falling back to salvaging its readable packets if it is damaged
+pub fn read(data: &[u8]) -> Result<Salvage> {
+    let error = match TASD::deserialize(data) {
+        Ok((_, tasd)) => return Ok(Salvage { kept: tasd.packets.len(), tasd, dropped: Vec::new() }),
+        Err(e) => eyre!("Failed to parse TASD file: {:?}", e),
+    };
+    match salvage(data) {
+        Ok(salvage) if !salvage.dropped.is_empty() => Ok(salvage),
+        _ => Err(error),
+    }
+}
+
+/// Recover every readable packet, skipping damaged ranges until the next valid packet
+pub fn salvage(data: &[u8]) -> Result<Salvage> {
+    if data.len() < HEADER_LEN || &data[..4] != b"TASD" {
+        return Err(eyre!("Missing TASD header, nothing to salvage"));
+    }
+    let header = &data[..HEADER_LEN];
+    let key_width = data[6] as usize;
+    if key_width == 0 || key_width > 2 {
+        return Err(eyre!("Unsupported key width {}", key_width));
+    }
+
+    let mut recovered = header.to_vec();
+    let mut kept = 0;
+    let mut dropped = Vec::new();
+    let mut pos = HEADER_LEN;
+
+    while pos < data.len() {
+        if let Some(length) = valid_packet(data, pos, header, key_width) {
+            recovered.extend_from_slice(&data[pos..pos + length]);
+            kept += 1;
+            pos += length;
+            continue;
+        }
+
+        // Resynchronize on the next offset that holds a valid packet
+        let start = pos;
+        let reason = if framed_length(data, pos, key_width).is_none() { "truncated or malformed packet" } else { "unreadable packet" };
+        pos += 1;
+        while pos < data.len() && valid_packet(data, pos, header, key_width).is_none() {
+            pos += 1;
+        }
+        let bytes = data[start..pos.min(start + KEPT_BYTES)].to_vec();
+        dropped.push(Dropped { offset: start, length: pos - start, reason, bytes });
+    }
+
+    let (_, tasd) = TASD::deserialize(&recovered)
+        .map_err(|e| eyre!("Recovered packets still fail to parse: {:?}", e))?;
+    Ok(Salvage { tasd, kept, dropped })
+}
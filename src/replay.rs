@@ -0,0 +1,72 @@
+use color_eyre::{eyre::eyre, Result};
+use tasd_lib::TASD;
+use crate::app::App;
+use crate::checkpoint::port_inputs;
+use crate::controller::{self, ControllerType};
+
+/// Controllers in each frame of an `.r16m` replay: 4 data lines on each of the 2 SNES ports
+const R16M_CONTROLLERS: usize = 8;
+/// Bytes per controller per frame of SNES input, in both TASD and `.r16m`
+const SNES_SAMPLE_LEN: usize = 2;
+
+/// Input of every port holding the given controller, in port order
+fn ports_of(tasd: &TASD, controller: ControllerType) -> Vec<(u8, Vec<u8>)> {
+    let mut ports: Vec<u8> = App::detect_ports(tasd).into_iter()
+        .filter(|port| controller::detect_controller(&tasd.packets, *port) == controller)
+        .collect();
+    ports.sort();
+    ports.into_iter().map(|port| (port, port_inputs(&tasd.packets, port))).collect()
+}
+
+/// Convert a TASD file with NES controllers into an `.r08` replay
+///
+/// Each frame is one byte for controller 1 and one for controller 2, active high with A in the
+/// top bit, the order the console shifts them in. TASD stores them active low with A in the lowest bit.
+pub fn export_r08(tasd: &TASD) -> Result<Vec<u8>> {
+    let ports = ports_of(tasd, ControllerType::NesStandard);
+    if ports.is_empty() {
+        return Err(eyre!("No NES controllers declared, nothing to export"));
+    }
+    if let Some((port, _)) = ports.iter().find(|(port, _)| *port > 2) {
+        return Err(eyre!("r08 replays only have controllers 1 and 2, found port {}", port));
+    }
+
+    let frames = ports.iter().map(|(_, inputs)| inputs.len()).max().unwrap_or(0);
+    let mut data = vec![0u8; frames * 2];
+    for (port, inputs) in &ports {
+        for (frame, byte) in inputs.iter().enumerate() {
+            data[frame * 2 + *port as usize - 1] = (!byte).reverse_bits();
+        }
+    }
+    Ok(data)
+}
+
+/// Convert a TASD file with SNES controllers into an `.r16m` replay
+///
+/// Each frame is 8 controllers of 2 bytes: data lines 1-4 of port 1, then of port 2. Buttons are
+/// active high and big-endian, B in the top bit down to R. Controller 1 is on port 1 and
+/// controller 2 on port 2, with controllers 3-5 on the rest of port 2 through a multitap.
+pub fn export_r16m(tasd: &TASD) -> Result<Vec<u8>> {
+    let ports = ports_of(tasd, ControllerType::SnesStandard);
+    if ports.is_empty() {
+        return Err(eyre!("No SNES controllers declared, nothing to export"));
+    }
+    if let Some((port, _)) = ports.iter().find(|(port, _)| !(1..=5).contains(port)) {
+        return Err(eyre!("r16m replays have controllers 1-5, found port {}", port));
+    }
+
+    let frames = ports.iter().map(|(_, inputs)| inputs.len() / SNES_SAMPLE_LEN).max().unwrap_or(0);
+    let stride = R16M_CONTROLLERS * SNES_SAMPLE_LEN;
+    let mut data = vec![0u8; frames * stride];
+    for (port, inputs) in &ports {
+        let slot = if *port == 1 { 0 } else { *port as usize + 2 };
+        for (frame, sample) in inputs.chunks_exact(SNES_SAMPLE_LEN).enumerate() {
+            let order = !u16::from_le_bytes([sample[0], sample[1]]);
+            // Only the 12 button bits are meaningful, the rest is the controller's signature
+            let bits = order.reverse_bits() & 0xFFF0;
+            let offset = frame * stride + slot * SNES_SAMPLE_LEN;
+            data[offset..offset + SNES_SAMPLE_LEN].copy_from_slice(&bits.to_be_bytes());
+        }
+    }
+    Ok(data)
+}
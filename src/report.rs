@@ -0,0 +1,131 @@
+use std::collections::BTreeMap;
+use tasd_lib::{Packet, Serializable};
+use crate::attributions;
+use crate::catalog;
+use crate::controller::ControllerType;
+
+/// Packet type name, e.g. `InputChunk`
+pub fn packet_kind(packet: &Packet) -> String {
+    let text = format!("{:?}", packet);
+    match text.find(['(', ' ', '{']) {
+        Some(end) => text[..end].to_string(),
+        None => text,
+    }
+}
+
+/// One-line description of a packet's contents, for `info` and `dump`
+pub fn describe_packet(packet: &Packet) -> String {
+    match packet {
+        Packet::ConsoleType(p) if p.name.is_empty() => catalog::console_name(p.console as u8).to_string(),
+        Packet::ConsoleType(p) => format!("{} ({})", catalog::console_name(p.console as u8), p.name),
+        Packet::ConsoleRegion(p) => if p.video_signal as u8 == 2 { "PAL" } else { "NTSC" }.to_string(),
+        Packet::GameTitle(p) => p.title.clone(),
+        Packet::RomName(p) => p.name.clone(),
+        Packet::Attribution(p) => format!("{}: {}", attributions::type_name(p.attribution_type as u8), p.name),
+        Packet::Category(p) => p.category.clone(),
+        Packet::EmulatorName(p) => p.name.clone(),
+        Packet::EmulatorVersion(p) => p.version.clone(),
+        Packet::TotalFrames(p) => p.frames.to_string(),
+        Packet::TotalRerecords(p) => p.rerecords.to_string(),
+        Packet::SourceLink(p) => p.link.clone(),
+        Packet::BlankFrames(p) => p.blank_frames.to_string(),
+        Packet::Verified(p) => if p.verified { "Yes" } else { "No" }.to_string(),
+        Packet::MovieLicense(p) => p.license.clone(),
+        Packet::MovieFile(p) => format!("{} ({} bytes)", p.name, p.data.len()),
+        Packet::MemoryInit(p) => format!("{} ({} bytes)", p.name, p.data.len()),
+        Packet::PortController(p) => format!("Port {}: {}", p.port, ControllerType::from_code(p.controller_type).name()),
+        Packet::NesLatchFilter(p) => p.time.to_string(),
+        Packet::SnesLatchTrain(p) => format!("{} points", p.points.len()),
+        Packet::InputChunk(p) => format!("Port {}: {} bytes", p.port, p.inputs.len()),
+        Packet::InputMoment(p) => format!("Port {} at {}: {} bytes", p.port, p.index, p.inputs.len()),
+        Packet::Transition(p) => format!("{:?} at {}", p.transition_type, p.index),
+        Packet::LagFrameChunk(p) => format!("{} frames from {}", p.count, p.movie_frame),
+        Packet::Comment(p) => p.comment.clone(),
+        Packet::Experimental(p) => if p.experimental { "Yes" } else { "No" }.to_string(),
+        Packet::Unspecified(p) => format!("{} bytes", p.payload.len()),
+    }
+}
+
+/// Broad group a packet type belongs to, for the size chart
+pub fn packet_group(kind: &str) -> &'static str {
+    match kind {
+        "InputChunk" | "InputMoment" | "Transition" | "LagFrameChunk" => "Inputs",
+        "MovieFile" | "MemoryInit" => "Attachments",
+        _ => "Metadata",
+    }
+}
+
+/// Packet count and serialized size per packet type, largest first
+pub fn bytes_by_kind(packets: &[Packet]) -> Vec<(String, usize, usize)> {
+    let mut kinds: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    for packet in packets {
+        let entry = kinds.entry(packet_kind(packet)).or_default();
+        entry.0 += 1;
+        entry.1 += packet.serialize().len();
+    }
+
+    let mut kinds: Vec<(String, usize, usize)> = kinds.into_iter()
+        .map(|(kind, (count, bytes))| (kind, count, bytes))
+        .collect();
+    kinds.sort_by_key(|kind| std::cmp::Reverse(kind.2));
+    kinds
+}
+
+/// Lines of the `:report structure` popup
+pub fn structure(packets: &[Packet]) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    // Input chunk layout per port
+    let mut chunks: BTreeMap<u8, Vec<usize>> = BTreeMap::new();
+    for packet in packets {
+        if let Packet::InputChunk(chunk) = packet {
+            chunks.entry(chunk.port).or_default().push(chunk.inputs.len());
+        }
+    }
+
+    lines.push("Input chunks".to_string());
+    if chunks.is_empty() {
+        lines.push("  none".to_string());
+    }
+    for (port, sizes) in &mut chunks {
+        sizes.sort();
+        lines.push(format!(
+            "  Port {}: {} chunks, {} bytes (min {}, median {}, max {})",
+            port,
+            sizes.len(),
+            sizes.iter().sum::<usize>(),
+            sizes[0],
+            sizes[sizes.len() / 2],
+            sizes[sizes.len() - 1],
+        ));
+
+        // Power-of-two size buckets
+        let mut buckets: BTreeMap<u32, usize> = BTreeMap::new();
+        for size in sizes.iter() {
+            *buckets.entry(size.max(&1).ilog2()).or_default() += 1;
+        }
+        for (bucket, count) in buckets {
+            lines.push(format!(
+                "    {:>7}-{:<7} bytes: {}",
+                1usize << bucket,
+                (1usize << (bucket + 1)) - 1,
+                count,
+            ));
+        }
+    }
+
+    lines.push(String::new());
+    lines.push(format!("{:<20} {:>8} {:>12}", "Packet type", "Count", "Bytes"));
+    let kinds = bytes_by_kind(packets);
+    for (kind, count, bytes) in &kinds {
+        lines.push(format!("{:<20} {:>8} {:>12}", kind, count, bytes));
+    }
+    lines.push(format!(
+        "{:<20} {:>8} {:>12}",
+        "Total",
+        packets.len(),
+        kinds.iter().map(|k| k.2).sum::<usize>(),
+    ));
+
+    lines
+}
@@ -0,0 +1,74 @@
+use std::collections::{HashMap, HashSet};
+use tasd_lib::{InputChunk, LagFrameChunk, Packet, TotalFrames};
+
+use crate::checksum;
+use crate::controller::ControllerType;
+use crate::signature;
+
+/// Transition and input moment index type counting frames
+const INDEX_FRAME: u8 = 1;
+
+/// Copy of a movie holding only frames `start..=end`
+///
+/// Metadata is copied as is, TotalFrames is set to the length of the range, and frame-indexed
+/// packets are kept when they fall inside it, renumbered from its start. Checksums and
+/// signatures are left out since they no longer match.
+pub fn extract(packets: &[Packet], controllers: &HashMap<u8, ControllerType>, start: usize, end: usize) -> Vec<Packet> {
+    let frames = end + 1 - start;
+    let in_range = |index: u64| (start as u64..=end as u64).contains(&index);
+
+    // Each port's input is cut from its whole stream, so chunk boundaries don't matter
+    let mut streams: HashMap<u8, Vec<u8>> = HashMap::new();
+    for packet in packets {
+        if let Packet::InputChunk(chunk) = packet {
+            streams.entry(chunk.port).or_default().extend_from_slice(&chunk.inputs);
+        }
+    }
+
+    let mut written = HashSet::new();
+    let mut segment = Vec::new();
+    for packet in packets {
+        match packet {
+            Packet::InputChunk(chunk) => {
+                // The port's whole range goes where its first chunk was
+                if written.insert(chunk.port) {
+                    let size = controllers.get(&chunk.port).copied().unwrap_or(ControllerType::NesStandard).frame_size();
+                    let stream = &streams[&chunk.port];
+                    let from = (start * size).min(stream.len());
+                    let to = ((end + 1) * size).min(stream.len());
+                    segment.push(Packet::InputChunk(InputChunk { port: chunk.port, inputs: stream[from..to].to_vec() }));
+                }
+            }
+            Packet::TotalFrames(_) => segment.push(Packet::TotalFrames(TotalFrames { frames: frames as u32 })),
+            Packet::Transition(t) if t.index_type == INDEX_FRAME => {
+                if in_range(t.index) {
+                    let mut t = t.clone();
+                    t.index -= start as u64;
+                    segment.push(Packet::Transition(t));
+                }
+            }
+            Packet::InputMoment(m) if m.index_type == INDEX_FRAME => {
+                if in_range(m.index) {
+                    let mut m = m.clone();
+                    m.index -= start as u64;
+                    segment.push(Packet::InputMoment(m));
+                }
+            }
+            // Other index types can't be renumbered
+            Packet::Transition(_) | Packet::InputMoment(_) => {}
+            Packet::LagFrameChunk(lag) => {
+                let first = (lag.movie_frame as usize).max(start);
+                let last = (lag.movie_frame as usize + lag.count as usize).min(end + 1);
+                if first < last {
+                    segment.push(Packet::LagFrameChunk(LagFrameChunk {
+                        movie_frame: (first - start) as u32,
+                        count: (last - first) as u32,
+                    }));
+                }
+            }
+            packet if checksum::is_checksum_packet(packet) || signature::is_signature_packet(packet) => {}
+            packet => segment.push(packet.clone()),
+        }
+    }
+    segment
+}
@@ -0,0 +1,117 @@
+use std::path::{Path, PathBuf};
+use color_eyre::{eyre::eyre, Result};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use tasd_lib::{Comment, Packet, Serializable, TASD};
+
+/// Prefix marking Comment packets that carry a file signature
+///
+/// Signatures are stored as `tasd-cli:ed25519 <public key hex> <signature hex>` and
+/// cover the serialized file with every signature comment left out.
+const PREFIX: &str = "tasd-cli:ed25519";
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Whether a packet is a signature written by this tool
+pub fn is_signature_packet(packet: &Packet) -> bool {
+    matches!(packet, Packet::Comment(c) if c.comment.starts_with(PREFIX))
+}
+
+/// Parse a signature comment into its public key and signature
+fn parse_comment(comment: &str) -> Option<(VerifyingKey, Signature)> {
+    let rest = comment.strip_prefix(PREFIX)?.trim();
+    let (key, signature) = rest.split_once(' ')?;
+    let key = VerifyingKey::from_bytes(&from_hex(key)?.try_into().ok()?).ok()?;
+    let signature = Signature::from_slice(&from_hex(signature.trim())?).ok()?;
+    Some((key, signature))
+}
+
+/// Serialized file with signature comments left out
+fn signed_bytes(tasd: &mut TASD) -> Vec<u8> {
+    let (signatures, others): (Vec<Packet>, Vec<Packet>) = std::mem::take(&mut tasd.packets)
+        .into_iter()
+        .partition(is_signature_packet);
+    tasd.packets = others;
+    let data = tasd.serialize();
+    tasd.packets.extend(signatures);
+    data
+}
+
+/// Path of the public key written next to a secret key
+pub fn public_key_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".pub");
+    PathBuf::from(name)
+}
+
+/// Generate a key pair, writing the secret key to `path` and the public key to `path.pub`
+pub fn generate_key(path: &Path) -> Result<String> {
+    let mut secret = [0u8; 32];
+    getrandom::getrandom(&mut secret).map_err(|e| eyre!("No random source available: {}", e))?;
+    let key = SigningKey::from_bytes(&secret);
+    let public = to_hex(key.verifying_key().as_bytes());
+
+    std::fs::write(path, to_hex(&secret) + "\n")?;
+    std::fs::write(public_key_path(path), public.clone() + "\n")?;
+    Ok(public)
+}
+
+/// Load a secret key written by `generate_key`
+pub fn load_key(path: &Path) -> Result<SigningKey> {
+    let text = std::fs::read_to_string(path)?;
+    let secret: [u8; 32] = from_hex(text.trim())
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| eyre!("{} is not an ed25519 secret key", path.display()))?;
+    Ok(SigningKey::from_bytes(&secret))
+}
+
+/// Parse a public key given as hex or as a path to a `.pub` file
+pub fn parse_public_key(key: &str) -> Result<String> {
+    let text = match std::fs::read_to_string(key) {
+        Ok(text) => text.trim().to_lowercase(),
+        Err(_) => key.trim().to_lowercase(),
+    };
+    match from_hex(&text) {
+        Some(bytes) if bytes.len() == 32 => Ok(text),
+        _ => Err(eyre!("{} is not an ed25519 public key", key)),
+    }
+}
+
+/// Replace any signatures with a fresh one made with this key, returning the public key
+pub fn sign(tasd: &mut TASD, key: &SigningKey) -> String {
+    tasd.packets.retain(|packet| !is_signature_packet(packet));
+
+    let signature = key.sign(&tasd.serialize());
+    let public = to_hex(key.verifying_key().as_bytes());
+    tasd.packets.push(Packet::Comment(Comment {
+        comment: format!("{} {} {}", PREFIX, public, to_hex(&signature.to_bytes())),
+    }));
+    public
+}
+
+/// Check every stored signature, returning each public key and whether it is valid
+pub fn verify(tasd: &mut TASD) -> Vec<(String, bool)> {
+    let data = signed_bytes(tasd);
+
+    tasd.packets.iter()
+        .filter_map(|packet| match packet {
+            Packet::Comment(c) if c.comment.starts_with(PREFIX) => Some(&c.comment),
+            _ => None,
+        })
+        .map(|comment| match parse_comment(comment) {
+            Some((key, signature)) => (to_hex(key.as_bytes()), key.verify(&data, &signature).is_ok()),
+            None => ("malformed".to_string(), false),
+        })
+        .collect()
+}
@@ -0,0 +1,230 @@
+use color_eyre::{eyre::eyre, Result};
+use tasd_lib::{
+    Attribution, AttributionType, Console, ConsoleRegion, ConsoleType, InputChunk, Packet, PortController,
+    TotalFrames, TotalRerecords, Transition, TransitionType, VideoSignal, TASD,
+};
+use crate::controller::ControllerType;
+
+/// Snes9x movie signature, "SMV" followed by an EOF byte
+const SIGNATURE: &[u8; 4] = b"SMV\x1A";
+/// Bytes per controller per frame, in both SMV and TASD
+const SAMPLE_LEN: usize = 2;
+/// Sample written for every controller on a frame where the console is reset
+const RESET_SAMPLE: u16 = 0xFFFF;
+/// Transition index type counting frames
+const INDEX_FRAME: u8 = 1;
+/// Movie flag: the movie starts from power-on instead of an embedded savestate
+const FLAG_FROM_RESET: u8 = 0x01;
+/// Movie flag: the movie was recorded on a PAL console
+const FLAG_PAL: u8 = 0x02;
+/// Port type values of the 1.51 header for a joypad and a multitap
+const PORT_JOYPAD: u8 = 1;
+const PORT_MULTITAP: u8 = 2;
+
+/// Convert an SMV sample into TASD SNES controller input
+///
+/// SMV stores the buttons active high with B in the top bit. TASD stores them in the order
+/// the console reads them (B first, in the lowest bit) and active low, like the NES controller.
+fn sample_to_tasd(sample: u16) -> [u8; SAMPLE_LEN] {
+    let order = (0..16).fold(0u16, |bits, i| bits | ((sample >> (15 - i)) & 1) << i);
+    (!order).to_le_bytes()
+}
+
+/// Convert a frame of TASD SNES controller input into an SMV sample
+fn tasd_to_sample(frame: &[u8]) -> u16 {
+    let order = !u16::from_le_bytes([frame[0], frame[1]]);
+    // Only the 12 button bits are meaningful, the rest is the controller's signature
+    (0..12).fold(0u16, |sample, i| sample | ((order >> i) & 1) << (15 - i))
+}
+
+/// Convert a Snes9x `.smv` movie (1.43 or 1.51+) into TASD packets
+pub fn import(data: &[u8]) -> Result<TASD> {
+    if data.len() < 0x20 || &data[..4] != SIGNATURE {
+        return Err(eyre!("Not a Snes9x movie (missing SMV signature)"));
+    }
+    let read_u32 = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+
+    let version = read_u32(0x04);
+    let extended = match version {
+        1 => false,
+        4 | 5 => true,
+        other => return Err(eyre!("Unsupported SMV version {}", other)),
+    };
+    // 1.51 adds a second header block, read below for the sample count and port types
+    if extended && data.len() < 0x40 {
+        return Err(eyre!("SMV file is too short for a version {} header ({} bytes)", version, data.len()));
+    }
+    let rerecords = read_u32(0x0C);
+    let frame_count = read_u32(0x10) as usize;
+    let controller_flags = data[0x14];
+    let movie_flags = data[0x15];
+    let savestate_offset = read_u32(0x18) as usize;
+    let data_offset = read_u32(0x1C) as usize;
+
+    if movie_flags & FLAG_FROM_RESET == 0 {
+        return Err(eyre!("Movies that start from a savestate cannot be converted"));
+    }
+    if extended && (data[0x24] > PORT_MULTITAP || data[0x25] > PORT_MULTITAP) {
+        return Err(eyre!("Only joypads and multitaps can be converted, not mice or light guns"));
+    }
+
+    // Bits 0-4 mark connected controllers 1-5 (3-5 through a multitap)
+    let ports: Vec<u8> = (0..5).filter(|i| controller_flags & (1 << i) != 0).map(|i| i as u8 + 1).collect();
+    if ports.is_empty() {
+        return Err(eyre!("SMV movie has no controllers connected"));
+    }
+    if data_offset > data.len() {
+        return Err(eyre!("SMV controller data offset {:#x} is past the end of the file", data_offset));
+    }
+
+    // The author is stored as UTF-16 between the header and the savestate
+    let metadata_offset = if extended { 0x40 } else { 0x20 };
+    let author = data.get(metadata_offset..savestate_offset.min(data_offset))
+        .map(|bytes| {
+            let units: Vec<u16> = bytes.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect();
+            let end = units.iter().position(|unit| *unit == 0).unwrap_or(units.len());
+            String::from_utf16_lossy(&units[..end]).trim().to_string()
+        })
+        .unwrap_or_default();
+
+    // 1.43 stores one sample more than the frame count, 1.51 records the sample count
+    let samples = if extended { read_u32(0x20) as usize } else { frame_count + 1 };
+    let stride = SAMPLE_LEN * ports.len();
+    let frames = samples.min((data.len() - data_offset) / stride);
+
+    let mut tasd = TASD::new();
+    let packets = &mut tasd.packets;
+    packets.push(Packet::ConsoleType(ConsoleType { console: Console::Snes, name: String::new() }));
+    packets.push(Packet::ConsoleRegion(ConsoleRegion {
+        video_signal: if movie_flags & FLAG_PAL != 0 { VideoSignal::Pal } else { VideoSignal::Ntsc },
+    }));
+    if !author.is_empty() {
+        packets.push(Packet::Attribution(Attribution { attribution_type: AttributionType::Author, name: author }));
+    }
+    packets.push(Packet::TotalRerecords(TotalRerecords { rerecords }));
+    for port in &ports {
+        packets.push(Packet::PortController(PortController {
+            port: *port,
+            controller_type: ControllerType::SnesStandard.code(),
+        }));
+    }
+    packets.push(Packet::TotalFrames(TotalFrames { frames: frames as u32 }));
+
+    let mut inputs = vec![Vec::with_capacity(frames * SAMPLE_LEN); ports.len()];
+    for (frame, chunk) in data[data_offset..data_offset + frames * stride].chunks_exact(stride).enumerate() {
+        let samples: Vec<u16> = chunk.chunks_exact(SAMPLE_LEN).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect();
+
+        // A reset is recorded as every controller holding every button
+        if samples.iter().all(|sample| *sample == RESET_SAMPLE) {
+            packets.push(Packet::Transition(Transition {
+                index_type: INDEX_FRAME,
+                index: frame as u64,
+                transition_type: TransitionType::SoftReset,
+            }));
+            for port in &mut inputs {
+                port.extend_from_slice(&sample_to_tasd(0));
+            }
+            continue;
+        }
+
+        for (port, sample) in inputs.iter_mut().zip(samples) {
+            port.extend_from_slice(&sample_to_tasd(sample));
+        }
+    }
+
+    for (port, inputs) in ports.iter().zip(inputs) {
+        packets.push(Packet::InputChunk(InputChunk { port: *port, inputs }));
+    }
+
+    Ok(tasd)
+}
+
+/// Convert a TASD file with SNES controllers into a Snes9x 1.51 `.smv` movie
+pub fn export(tasd: &TASD) -> Result<Vec<u8>> {
+    let mut ports = Vec::new();
+    let mut pal = false;
+    let mut authors = Vec::new();
+    let mut rerecords = 0u32;
+    let mut resets = Vec::new();
+
+    for packet in &tasd.packets {
+        match packet {
+            Packet::PortController(pc) if ControllerType::from_code(pc.controller_type) == ControllerType::SnesStandard => {
+                ports.push(pc.port);
+            }
+            Packet::ConsoleRegion(cr) => pal = cr.video_signal as u8 == 2,
+            Packet::Attribution(at) if at.attribution_type as u8 == 1 => authors.push(at.name.clone()),
+            Packet::TotalRerecords(tr) => rerecords = tr.rerecords,
+            Packet::Transition(tr) if tr.index_type == INDEX_FRAME && tr.transition_type as u8 == 1 => {
+                resets.push(tr.index as usize);
+            }
+            _ => {}
+        }
+    }
+
+    ports.sort();
+    if ports.is_empty() {
+        return Err(eyre!("No SNES controllers declared, nothing to export"));
+    }
+    if let Some(port) = ports.iter().find(|port| !(1..=5).contains(*port)) {
+        return Err(eyre!("SMV movies only have controllers 1-5, found port {}", port));
+    }
+
+    let inputs: Vec<Vec<u8>> = ports.iter()
+        .map(|port| {
+            tasd.packets.iter()
+                .filter_map(|packet| match packet {
+                    Packet::InputChunk(chunk) if chunk.port == *port => Some(chunk.inputs.as_slice()),
+                    _ => None,
+                })
+                .flatten()
+                .copied()
+                .collect()
+        })
+        .collect();
+    let frames = inputs.iter().map(|port| port.len() / SAMPLE_LEN).max().unwrap_or(0);
+
+    let controller_flags = ports.iter().fold(0u8, |bits, port| bits | 1 << (port - 1));
+    let uid = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as u32)
+        .unwrap_or(0);
+
+    let author: Vec<u8> = authors.join(", ")
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .flat_map(|unit| unit.to_le_bytes())
+        .collect();
+    let data_offset = (0x40 + author.len()) as u32;
+
+    let mut data = vec![0u8; 0x40];
+    data[..4].copy_from_slice(SIGNATURE);
+    data[0x04..0x08].copy_from_slice(&4u32.to_le_bytes());
+    data[0x08..0x0C].copy_from_slice(&uid.to_le_bytes());
+    data[0x0C..0x10].copy_from_slice(&rerecords.to_le_bytes());
+    data[0x10..0x14].copy_from_slice(&(frames as u32).to_le_bytes());
+    data[0x14] = controller_flags;
+    data[0x15] = FLAG_FROM_RESET | if pal { FLAG_PAL } else { 0 };
+    // Power-on movies carry no savestate, so it is empty and ends where the inputs begin
+    data[0x18..0x1C].copy_from_slice(&data_offset.to_le_bytes());
+    data[0x1C..0x20].copy_from_slice(&data_offset.to_le_bytes());
+    data[0x20..0x24].copy_from_slice(&(frames as u32).to_le_bytes());
+    data[0x24] = PORT_JOYPAD;
+    data[0x25] = if ports.iter().any(|port| *port > 2) { PORT_MULTITAP } else { PORT_JOYPAD };
+    data.extend_from_slice(&author);
+
+    // Released buttons for ports that run out of input
+    let neutral = sample_to_tasd(0);
+    for frame in 0..frames {
+        for port in &inputs {
+            let sample = if resets.contains(&frame) {
+                RESET_SAMPLE
+            } else {
+                tasd_to_sample(port.get(frame * SAMPLE_LEN..(frame + 1) * SAMPLE_LEN).unwrap_or(&neutral))
+            };
+            data.extend_from_slice(&sample.to_le_bytes());
+        }
+    }
+
+    Ok(data)
+}
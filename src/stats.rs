@@ -0,0 +1,127 @@
+use std::collections::{BTreeSet, HashMap};
+use tasd_lib::Packet;
+
+use crate::checkpoint::port_inputs;
+use crate::controller::{self, ControllerType};
+
+/// Button usage of one port
+pub struct PortStats {
+    pub port: u8,
+    pub controller: ControllerType,
+    pub frames: usize,
+    /// Frames with the neutral input (nothing pressed, sticks centered)
+    pub blank_frames: usize,
+    /// Per button, in the controller's order: times pressed and frames held
+    pub buttons: Vec<(&'static str, usize, usize)>,
+    /// Longest hold: button, frames and the frame it started on
+    pub longest_hold: Option<(&'static str, usize, usize)>,
+    /// Most common set of pressed buttons, other than none, and its frame count
+    pub common: Option<(String, usize)>,
+}
+
+/// Count presses, holds and combinations on every port with input
+pub fn collect(packets: &[Packet]) -> Vec<PortStats> {
+    let ports: BTreeSet<u8> = packets.iter()
+        .filter_map(|packet| match packet {
+            Packet::InputChunk(chunk) => Some(chunk.port),
+            _ => None,
+        })
+        .collect();
+
+    ports.into_iter()
+        .map(|port| {
+            let controller = controller::detect_controller(packets, port);
+            let buttons = controller.buttons();
+            let neutral = controller.neutral_frame();
+            let inputs = port_inputs(packets, port);
+
+            let mut presses = vec![0; buttons.len()];
+            let mut held = vec![0; buttons.len()];
+            // Frame each button has been held since, while it is held
+            let mut since: Vec<Option<usize>> = vec![None; buttons.len()];
+            let mut longest_hold: Option<(&'static str, usize, usize)> = None;
+            let mut combinations: HashMap<Vec<&'static str>, usize> = HashMap::new();
+            let mut blank_frames = 0;
+            let mut frames = 0;
+
+            let mut end_hold = |i: usize, start: usize, end: usize| {
+                if longest_hold.is_none_or(|(_, length, _)| end - start > length) {
+                    longest_hold = Some((buttons[i].name, end - start, start));
+                }
+            };
+            for (frame, input) in inputs.chunks_exact(controller.frame_size().max(1)).enumerate() {
+                frames += 1;
+                if input == neutral {
+                    blank_frames += 1;
+                }
+                let mut pressed = Vec::new();
+                for (i, button) in buttons.iter().enumerate() {
+                    if controller.is_pressed(button, input) {
+                        pressed.push(button.name);
+                        held[i] += 1;
+                        if since[i].is_none() {
+                            presses[i] += 1;
+                            since[i] = Some(frame);
+                        }
+                    } else if let Some(start) = since[i].take() {
+                        end_hold(i, start, frame);
+                    }
+                }
+                if !pressed.is_empty() {
+                    *combinations.entry(pressed).or_default() += 1;
+                }
+            }
+            for (i, start) in since.iter().enumerate() {
+                if let Some(start) = start {
+                    end_hold(i, *start, frames);
+                }
+            }
+
+            // Ties go to the combination with fewer buttons, then by name, so the result is stable
+            let common = combinations.into_iter()
+                .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count).then(b.len().cmp(&a.len())).then(b.cmp(a)))
+                .map(|(buttons, count)| (buttons.join("+"), count));
+
+            PortStats {
+                port,
+                controller,
+                frames,
+                blank_frames,
+                buttons: buttons.iter().zip(presses).zip(held).map(|((button, presses), held)| (button.name, presses, held)).collect(),
+                longest_hold,
+                common,
+            }
+        })
+        .collect()
+}
+
+/// Statistics of every port as text, for the `:stats` popup and the `stats` command
+pub fn report(packets: &[Packet]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for stats in collect(packets) {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        let percent = |count: usize| count as f64 * 100.0 / stats.frames.max(1) as f64;
+        lines.push(format!("Port {}: {}, {} frames", stats.port, stats.controller.name(), stats.frames));
+        lines.push(format!("  Blank frames: {} ({:.1}%)", stats.blank_frames, percent(stats.blank_frames)));
+        match &stats.longest_hold {
+            Some((button, frames, start)) => lines.push(format!("  Longest hold: {} for {} frames from {}", button, frames, start)),
+            None => lines.push("  Longest hold: none".to_string()),
+        }
+        match &stats.common {
+            Some((buttons, count)) => lines.push(format!("  Most common: {} on {} frames ({:.1}%)", buttons, count, percent(*count))),
+            None => lines.push("  Most common: none".to_string()),
+        }
+        if !stats.buttons.is_empty() {
+            lines.push(format!("  {:<10} {:>8} {:>10} {:>7}", "Button", "Presses", "Held", "Held %"));
+        }
+        for (button, presses, held) in &stats.buttons {
+            lines.push(format!("  {:<10} {:>8} {:>10} {:>6.1}%", button, presses, held, percent(*held)));
+        }
+    }
+    if lines.is_empty() {
+        lines.push("No input".to_string());
+    }
+    lines
+}
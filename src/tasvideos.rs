@@ -0,0 +1,67 @@
+use color_eyre::{eyre::eyre, Result};
+use serde_json::Value;
+
+/// Base URL of the TASVideos REST API
+const API_BASE: &str = "https://tasvideos.org/api/v1";
+
+/// Metadata retrieved for a publication or submission
+#[derive(Debug, Clone, Default)]
+pub struct MovieMetadata {
+    pub game_title: Option<String>,
+    pub authors: Vec<String>,
+    pub category: Option<String>,
+    pub rerecords: Option<u32>,
+    pub source_link: String,
+}
+
+/// Fetch metadata for an ID such as `1234M` (publication) or `5678S` (submission)
+///
+/// A bare number is treated as a publication ID.
+pub fn fetch(id: &str) -> Result<MovieMetadata> {
+    let id = id.trim();
+    let (number, submission) = match id.chars().last() {
+        Some('S' | 's') => (&id[..id.len() - 1], true),
+        Some('M' | 'm') => (&id[..id.len() - 1], false),
+        _ => (id, false),
+    };
+    let number: u64 = number.parse().map_err(|_| eyre!("Invalid publication/submission ID: {}", id))?;
+
+    let (endpoint, suffix) = if submission { ("submissions", "S") } else { ("publications", "M") };
+    let json = get_json(&format!("{}/{}/{}", API_BASE, endpoint, number))?;
+
+    let text = |key: &str| json.get(key).and_then(Value::as_str).map(str::to_string).filter(|s| !s.is_empty());
+
+    let mut metadata = MovieMetadata {
+        // Submissions carry the game name directly; publications only have a display title
+        game_title: text("gameName").or_else(|| text("title")),
+        authors: json.get("authors")
+            .and_then(Value::as_array)
+            .map(|authors| authors.iter().filter_map(Value::as_str).map(str::to_string).collect())
+            .unwrap_or_default(),
+        category: text("branch"),
+        rerecords: json.get("rerecordCount").and_then(Value::as_u64).map(|count| count as u32),
+        source_link: format!("https://tasvideos.org/{}{}", number, suffix),
+    };
+
+    // A publication's game name lives on its originating submission
+    if !submission {
+        if let Some(submission_id) = json.get("submissionId").and_then(Value::as_u64) {
+            let submission = get_json(&format!("{}/submissions/{}", API_BASE, submission_id))?;
+            if let Some(name) = submission.get("gameName").and_then(Value::as_str).filter(|s| !s.is_empty()) {
+                metadata.game_title = Some(name.to_string());
+            }
+        }
+    }
+
+    Ok(metadata)
+}
+
+/// Perform a GET request and parse the body as JSON
+fn get_json(url: &str) -> Result<Value> {
+    let mut response = ureq::get(url)
+        .header("Accept", "application/json")
+        .call()
+        .map_err(|e| eyre!("Request to {} failed: {}", url, e))?;
+    let body = response.body_mut().read_to_string()?;
+    Ok(serde_json::from_str(&body)?)
+}
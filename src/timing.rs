@@ -0,0 +1,95 @@
+use tasd_lib::Packet;
+
+/// Nominal NES/SNES NTSC framerate
+const NTSC_NES: f64 = 60.098_813_897_440_5;
+/// Nominal NES/SNES PAL framerate
+const PAL_NES: f64 = 50.006_978_908_188_6;
+/// Game Boy family framerate (region independent)
+const GAME_BOY: f64 = 59.727_500_569_6;
+/// Genesis NTSC framerate
+const NTSC_GENESIS: f64 = 59.922_743_404_3;
+/// Genesis PAL framerate
+const PAL_GENESIS: f64 = 49.701_459_605_6;
+
+/// Derive the framerate from the ConsoleType and ConsoleRegion packets
+pub fn default_framerate(packets: &[Packet]) -> f64 {
+    let mut console = 1;
+    let mut pal = false;
+
+    for packet in packets {
+        match packet {
+            Packet::ConsoleType(ct) => console = ct.console as u8,
+            Packet::ConsoleRegion(cr) => pal = cr.video_signal as u8 == 2,
+            _ => {}
+        }
+    }
+
+    match (console, pal) {
+        // NES and SNES
+        (1 | 2, false) => NTSC_NES,
+        (1 | 2, true) => PAL_NES,
+        // Game Boy, Game Boy Color, Game Boy Advance
+        (5..=7, _) => GAME_BOY,
+        // Genesis
+        (8, false) => NTSC_GENESIS,
+        (8, true) => PAL_GENESIS,
+        // GameCube runs at the NTSC field rate
+        (4, false) => 59.94,
+        (_, true) => 50.0,
+        (_, false) => 60.0,
+    }
+}
+
+/// Format a frame index as `h:mm:ss.mmm` (hours omitted when zero)
+pub fn format_timestamp(frame: usize, framerate: f64) -> String {
+    let total_millis = (frame as f64 * 1000.0 / framerate).round() as u64;
+    let millis = total_millis % 1000;
+    let seconds = (total_millis / 1000) % 60;
+    let minutes = (total_millis / 60_000) % 60;
+    let hours = total_millis / 3_600_000;
+
+    if hours > 0 {
+        format!("{}:{:02}:{:02}.{:03}", hours, minutes, seconds, millis)
+    } else {
+        format!("{}:{:02}.{:03}", minutes, seconds, millis)
+    }
+}
+
+/// Parse a timestamp like `1:23.456`, `1:02:03` or `83.5` into seconds
+pub fn parse_timestamp(text: &str) -> Option<f64> {
+    let parts: Vec<&str> = text.trim().split(':').collect();
+    if parts.len() > 3 {
+        return None;
+    }
+    let (seconds, units) = parts.split_last()?;
+    let seconds: f64 = seconds.parse().ok().filter(|s: &f64| s.is_finite() && *s >= 0.0)?;
+    // Hours and minutes are whole numbers, from the largest down
+    units.iter().try_fold(0u64, |total, unit| Some(total * 60 + unit.parse::<u64>().ok()?))
+        .map(|minutes| minutes as f64 * 60.0 + seconds)
+}
+
+/// Frame shown at a time in seconds, the inverse of `format_timestamp`
+pub fn frame_at(seconds: f64, framerate: f64) -> usize {
+    (seconds * framerate).round() as usize
+}
+
+/// Today's date in UTC as `YYYY-MM-DD`
+pub fn today() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let days = (secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
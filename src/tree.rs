@@ -0,0 +1,320 @@
+use color_eyre::{eyre::eyre, Result};
+use serde_json::{json, Map, Value};
+use tasd_lib::*;
+use crate::controller;
+
+/// Text formats the packet tree can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum TextFormat {
+    Yaml,
+    Json,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    let digits: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if !digits.len().is_multiple_of(2) {
+        return None;
+    }
+    digits.chunks(2)
+        .map(|pair| u8::from_str_radix(&pair.iter().collect::<String>(), 16).ok())
+        .collect()
+}
+
+/// Input bytes as one hex string per frame, so each frame sits on its own line
+fn frames(inputs: &[u8], frame_size: usize) -> Value {
+    inputs.chunks(frame_size.max(1)).map(to_hex).collect()
+}
+
+/// Describe one packet as a map of its typed fields, tagged with its packet type
+pub fn packet_to_value(packet: &Packet, packets: &[Packet]) -> Value {
+    let (kind, fields) = match packet {
+        Packet::ConsoleType(p) => ("ConsoleType", json!({ "console": p.console as u8, "name": p.name })),
+        Packet::ConsoleRegion(p) => ("ConsoleRegion", json!({ "video_signal": p.video_signal as u8 })),
+        Packet::GameTitle(p) => ("GameTitle", json!({ "title": p.title })),
+        Packet::RomName(p) => ("RomName", json!({ "name": p.name })),
+        Packet::Attribution(p) => ("Attribution", json!({ "attribution_type": p.attribution_type as u8, "name": p.name })),
+        Packet::Category(p) => ("Category", json!({ "category": p.category })),
+        Packet::EmulatorName(p) => ("EmulatorName", json!({ "name": p.name })),
+        Packet::EmulatorVersion(p) => ("EmulatorVersion", json!({ "version": p.version })),
+        Packet::TotalFrames(p) => ("TotalFrames", json!({ "frames": p.frames })),
+        Packet::TotalRerecords(p) => ("TotalRerecords", json!({ "rerecords": p.rerecords })),
+        Packet::SourceLink(p) => ("SourceLink", json!({ "link": p.link })),
+        Packet::BlankFrames(p) => ("BlankFrames", json!({ "blank_frames": p.blank_frames })),
+        Packet::Verified(p) => ("Verified", json!({ "verified": p.verified })),
+        Packet::MovieLicense(p) => ("MovieLicense", json!({ "license": p.license })),
+        Packet::MovieFile(p) => ("MovieFile", json!({ "name": p.name, "data": to_hex(&p.data) })),
+        Packet::MemoryInit(p) => ("MemoryInit", json!({
+            "data_type": p.data_type,
+            "device": p.device,
+            "required": p.required,
+            "name": p.name,
+            "data": to_hex(&p.data),
+        })),
+        Packet::PortController(p) => ("PortController", json!({ "port": p.port, "controller_type": p.controller_type })),
+        Packet::NesLatchFilter(p) => ("NesLatchFilter", json!({ "time": p.time })),
+        Packet::SnesLatchTrain(p) => ("SnesLatchTrain", json!({ "points": p.points })),
+        Packet::InputChunk(p) => {
+            let frame_size = controller::detect_controller(packets, p.port).frame_size();
+            ("InputChunk", json!({ "port": p.port, "inputs": frames(&p.inputs, frame_size) }))
+        }
+        Packet::InputMoment(p) => ("InputMoment", json!({
+            "port": p.port,
+            "index_type": p.index_type,
+            "index": p.index,
+            "inputs": to_hex(&p.inputs),
+        })),
+        Packet::Transition(p) => ("Transition", json!({
+            "index_type": p.index_type,
+            "index": p.index,
+            "transition_type": p.transition_type as u8,
+        })),
+        Packet::LagFrameChunk(p) => ("LagFrameChunk", json!({ "movie_frame": p.movie_frame, "count": p.count })),
+        Packet::Comment(p) => ("Comment", json!({ "comment": p.comment })),
+        Packet::Experimental(p) => ("Experimental", json!({ "experimental": p.experimental })),
+        Packet::Unspecified(p) => ("Unspecified", json!({ "payload": to_hex(&p.payload) })),
+    };
+
+    let mut map = Map::new();
+    map.insert("type".to_string(), Value::from(kind));
+    if let Value::Object(fields) = fields {
+        map.extend(fields);
+    }
+    Value::Object(map)
+}
+
+/// Every packet of a file as a tree of typed fields, in file order
+pub fn to_value(tasd: &TASD) -> Value {
+    let packets: Vec<Value> = tasd.packets.iter()
+        .map(|packet| packet_to_value(packet, &tasd.packets))
+        .collect();
+    json!({ "version": tasd.version, "key_width": tasd.key_width, "packets": packets })
+}
+
+/// Typed access to the fields of one packet, naming the packet in errors
+struct Fields<'a> {
+    /// How errors name the packet, like `Packet 3`
+    label: &'a str,
+    kind: &'a str,
+    map: &'a Map<String, Value>,
+}
+
+impl Fields<'_> {
+    fn get(&self, name: &str) -> Result<&Value> {
+        self.map.get(name)
+            .ok_or_else(|| eyre!("{} ({}): missing field '{}'", self.label, self.kind, name))
+    }
+
+    fn invalid(&self, name: &str) -> color_eyre::Report {
+        eyre!("{} ({}): invalid value for '{}'", self.label, self.kind, name)
+    }
+
+    fn string(&self, name: &str) -> Result<String> {
+        self.get(name)?.as_str().map(str::to_string).ok_or_else(|| self.invalid(name))
+    }
+
+    fn bool(&self, name: &str) -> Result<bool> {
+        self.get(name)?.as_bool().ok_or_else(|| self.invalid(name))
+    }
+
+    fn int<T: TryFrom<i64>>(&self, name: &str) -> Result<T> {
+        self.get(name)?.as_i64()
+            .and_then(|value| T::try_from(value).ok())
+            .ok_or_else(|| self.invalid(name))
+    }
+
+    fn u64(&self, name: &str) -> Result<u64> {
+        self.get(name)?.as_u64().ok_or_else(|| self.invalid(name))
+    }
+
+    /// Bytes given as one hex string or a list of them
+    fn hex(&self, name: &str) -> Result<Vec<u8>> {
+        let bytes = match self.get(name)? {
+            Value::String(text) => from_hex(text),
+            Value::Array(items) => items.iter()
+                .map(|item| item.as_str().and_then(from_hex))
+                .collect::<Option<Vec<_>>>()
+                .map(|frames| frames.concat()),
+            _ => None,
+        };
+        bytes.ok_or_else(|| self.invalid(name))
+    }
+
+    /// A spec enum stored by its numeric code
+    fn code<T: TryFrom<u8>>(&self, name: &str) -> Result<T> {
+        T::try_from(self.int::<u8>(name)?).map_err(|_| self.invalid(name))
+    }
+}
+
+/// Rebuild one packet from its typed fields
+fn value_to_packet(label: &str, value: &Value) -> Result<Packet> {
+    let map = value.as_object().ok_or_else(|| eyre!("{} is not a map of fields", label))?;
+    let kind = map.get("type")
+        .and_then(Value::as_str)
+        .ok_or_else(|| eyre!("{} has no 'type'", label))?;
+    let f = Fields { label, kind, map };
+
+    Ok(match kind {
+        "ConsoleType" => Packet::ConsoleType(ConsoleType { console: f.code("console")?, name: f.string("name")? }),
+        "ConsoleRegion" => Packet::ConsoleRegion(ConsoleRegion { video_signal: f.code("video_signal")? }),
+        "GameTitle" => Packet::GameTitle(GameTitle { title: f.string("title")? }),
+        "RomName" => Packet::RomName(RomName { name: f.string("name")? }),
+        "Attribution" => Packet::Attribution(Attribution {
+            attribution_type: f.code("attribution_type")?,
+            name: f.string("name")?,
+        }),
+        "Category" => Packet::Category(Category { category: f.string("category")? }),
+        "EmulatorName" => Packet::EmulatorName(EmulatorName { name: f.string("name")? }),
+        "EmulatorVersion" => Packet::EmulatorVersion(EmulatorVersion { version: f.string("version")? }),
+        "TotalFrames" => Packet::TotalFrames(TotalFrames { frames: f.int("frames")? }),
+        "TotalRerecords" => Packet::TotalRerecords(TotalRerecords { rerecords: f.int("rerecords")? }),
+        "SourceLink" => Packet::SourceLink(SourceLink { link: f.string("link")? }),
+        "BlankFrames" => Packet::BlankFrames(BlankFrames { blank_frames: f.int("blank_frames")? }),
+        "Verified" => Packet::Verified(Verified { verified: f.bool("verified")? }),
+        "MovieLicense" => Packet::MovieLicense(MovieLicense { license: f.string("license")? }),
+        "MovieFile" => Packet::MovieFile(MovieFile { name: f.string("name")?, data: f.hex("data")? }),
+        "MemoryInit" => Packet::MemoryInit(MemoryInit {
+            data_type: f.int("data_type")?,
+            device: f.int("device")?,
+            required: f.bool("required")?,
+            name: f.string("name")?,
+            data: f.hex("data")?,
+        }),
+        "PortController" => Packet::PortController(PortController {
+            port: f.int("port")?,
+            controller_type: f.int("controller_type")?,
+        }),
+        "NesLatchFilter" => Packet::NesLatchFilter(NesLatchFilter { time: f.int("time")? }),
+        "SnesLatchTrain" => Packet::SnesLatchTrain(SnesLatchTrain {
+            points: f.get("points")?.as_array()
+                .and_then(|points| points.iter().map(Value::as_u64).collect())
+                .ok_or_else(|| f.invalid("points"))?,
+        }),
+        "InputChunk" => Packet::InputChunk(InputChunk { port: f.int("port")?, inputs: f.hex("inputs")? }),
+        "InputMoment" => Packet::InputMoment(InputMoment {
+            port: f.int("port")?,
+            index_type: f.int("index_type")?,
+            index: f.u64("index")?,
+            inputs: f.hex("inputs")?,
+        }),
+        "Transition" => Packet::Transition(Transition {
+            index_type: f.int("index_type")?,
+            index: f.u64("index")?,
+            transition_type: f.code("transition_type")?,
+        }),
+        "LagFrameChunk" => Packet::LagFrameChunk(LagFrameChunk {
+            movie_frame: f.int("movie_frame")?,
+            count: f.int("count")?,
+        }),
+        "Comment" => Packet::Comment(Comment { comment: f.string("comment")? }),
+        "Experimental" => Packet::Experimental(Experimental { experimental: f.bool("experimental")? }),
+        "Unspecified" => Packet::Unspecified(Unspecified { payload: f.hex("payload")? }),
+        other => return Err(eyre!("{} has unknown type '{}'", label, other)),
+    })
+}
+
+/// Packet types with a single field: the field a bare value is stored in, and whether it is text
+const SINGLE_FIELD: &[(&str, &str, bool)] = &[
+    ("GameTitle", "title", true),
+    ("RomName", "name", true),
+    ("Category", "category", true),
+    ("EmulatorName", "name", true),
+    ("EmulatorVersion", "version", true),
+    ("TotalFrames", "frames", false),
+    ("TotalRerecords", "rerecords", false),
+    ("SourceLink", "link", true),
+    ("BlankFrames", "blank_frames", false),
+    ("Verified", "verified", false),
+    ("MovieLicense", "license", true),
+    ("NesLatchFilter", "time", false),
+    ("Comment", "comment", true),
+    ("Experimental", "experimental", false),
+];
+
+/// Every packet type name, as used in the tree
+const PACKET_TYPES: &[&str] = &[
+    "ConsoleType", "ConsoleRegion", "GameTitle", "RomName", "Attribution", "Category", "EmulatorName",
+    "EmulatorVersion", "TotalFrames", "TotalRerecords", "SourceLink", "BlankFrames", "Verified", "MovieLicense",
+    "MovieFile", "MemoryInit", "PortController", "NesLatchFilter", "SnesLatchTrain", "InputChunk", "InputMoment",
+    "Transition", "LagFrameChunk", "Comment", "Experimental", "Unspecified",
+];
+
+/// Build a packet from a type name and either its single value (`Comment some text`)
+/// or `field=value` pairs (`PortController port=1 controller_type=0x0101`)
+pub fn parse_packet(kind: &str, args: &str) -> Result<Packet> {
+    let kind = PACKET_TYPES.iter()
+        .find(|name| name.eq_ignore_ascii_case(kind))
+        .ok_or_else(|| eyre!("Unknown packet type '{}'", kind))?;
+    let scalar = |text: &str| -> Value {
+        let text = text.trim();
+        if let Some(hex) = text.strip_prefix("0x").and_then(|hex| u64::from_str_radix(hex, 16).ok()) {
+            return json!(hex);
+        }
+        serde_yaml::from_str(text).unwrap_or_else(|_| Value::String(text.to_string()))
+    };
+
+    let mut map = Map::new();
+    map.insert("type".to_string(), json!(kind));
+    match SINGLE_FIELD.iter().find(|(name, _, _)| name == kind) {
+        // Text keeps the value as typed, even if it looks like a number
+        Some((_, field, true)) => {
+            map.insert(field.to_string(), json!(args.trim()));
+        }
+        Some((_, field, false)) if !args.contains('=') => {
+            map.insert(field.to_string(), scalar(args));
+        }
+        _ => {
+            for pair in args.split_whitespace() {
+                let (field, value) = pair.split_once('=')
+                    .ok_or_else(|| eyre!("Expected field=value, got '{}'", pair))?;
+                map.insert(field.to_string(), scalar(value));
+            }
+        }
+    }
+    value_to_packet("New packet", &Value::Object(map))
+}
+
+/// Rebuild a file from the tree written by `to_value`
+pub fn from_value(value: &Value) -> Result<TASD> {
+    let packets = value.get("packets")
+        .and_then(Value::as_array)
+        .ok_or_else(|| eyre!("Expected a top-level 'packets' list"))?;
+
+    let mut tasd = TASD::new();
+    if let Some(version) = value.get("version") {
+        tasd.version = version.as_u64().and_then(|v| u16::try_from(v).ok())
+            .ok_or_else(|| eyre!("Expected 'version' to be a number up to {}", u16::MAX))?;
+    }
+    if let Some(key_width) = value.get("key_width") {
+        tasd.key_width = key_width.as_u64().and_then(|v| u8::try_from(v).ok())
+            .ok_or_else(|| eyre!("Expected 'key_width' to be a number up to {}", u8::MAX))?;
+    }
+    for (index, packet) in packets.iter().enumerate() {
+        tasd.packets.push(value_to_packet(&format!("Packet {}", index), packet)?);
+    }
+    Ok(tasd)
+}
+
+/// Write the packet tree as text, refusing trees that would not read back as the same file
+pub fn write(tasd: &TASD, format: TextFormat) -> Result<String> {
+    let value = to_value(tasd);
+    if from_value(&value)?.serialize() != tasd.serialize() {
+        return Err(eyre!("The packet tree does not read back as the same file"));
+    }
+    match format {
+        TextFormat::Yaml => Ok(serde_yaml::to_string(&value)?),
+        TextFormat::Json => Ok(serde_json::to_string_pretty(&value)? + "\n"),
+    }
+}
+
+/// Parse a packet tree written by `write`
+pub fn read(text: &str, format: TextFormat) -> Result<TASD> {
+    let value: Value = match format {
+        TextFormat::Yaml => serde_yaml::from_str(text)?,
+        TextFormat::Json => serde_json::from_str(text)?,
+    };
+    from_value(&value)
+}
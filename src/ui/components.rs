@@ -3,20 +3,44 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Table, Row, Cell, Widget},
+    widgets::{Bar, BarChart, BarGroup, Block, Borders, Clear, LineGauge, List, ListItem, Paragraph, Table, Tabs, Row, Cell, Widget},
 };
-use tasd_lib::Packet;
-use std::collections::HashMap;
-
-use crate::app::{App, AppMode};
+use tasd_lib::{Packet, Serializable};
+
+use crate::app::{App, AppMode, Popup};
+use crate::attributions;
+use crate::browser::Browser;
+use crate::finder::Finder;
+use crate::heatmap;
+use crate::metadata;
+use crate::notes;
+use crate::packet_list::{HexView, PacketList, HEX_WIDTH};
+use crate::catalog;
+use crate::checksum::{self, Integrity};
+use crate::config::Column;
+use crate::controller::{self, ControllerType, GameCubePad};
+use crate::picker::Picker;
+use crate::plugins::Registry;
+use crate::report;
+use crate::signature;
+use crate::timing;
 
 /// Render the sidebar with metadata
 pub fn render_sidebar(app: &App, area: Rect, buf: &mut Buffer) {
-    // First, let's debug what packets we actually have
+    // First, let's debug what packets we actually have (only when shown, as input chunks can be huge)
     let mut debug_info = Vec::new();
     for (i, packet) in app.tasd.packets.iter().enumerate() {
-        if i < 100 { // Just show first 100 packets to avoid overwhelming
-            debug_info.push(format!("Packet {}: {:?}", i, packet));
+        if app.display.show_debug && i < 100 { // Just show first 100 packets to avoid overwhelming
+            // Leading raw bytes; the packet browser (P, Enter) dumps the rest
+            let bytes = match packet {
+                Packet::InputChunk(chunk) => format!("port {}, {} bytes", chunk.port, chunk.inputs.len()),
+                _ => {
+                    let data = packet.serialize();
+                    let more = if data.len() > HEX_WIDTH { " …" } else { "" };
+                    format!("{}{}", controller::format_hex(&data[..data.len().min(HEX_WIDTH)]), more)
+                }
+            };
+            debug_info.push(format!("Packet {}: {} {}", i, report::packet_kind(packet), bytes));
         }
     }
 
@@ -25,32 +49,26 @@ pub fn render_sidebar(app: &App, area: Rect, buf: &mut Buffer) {
 
     // Always show file path
     metadata.push(("File", app.file_path.to_string_lossy().to_string()));
+    if app.arg_list.len() > 1 {
+        metadata.push(("Files", format!("{} of {}", app.arg_index + 1, app.arg_list.len())));
+    }
+
+    // Packets taken over by a renderer plugin are shown with its rows instead
+    let rendered: Vec<_> = app.tasd.packets.iter().enumerate().map(|(index, packet)| app.renderers.render(index, packet)).collect();
 
     // Go through all packets and collect metadata
-    for packet in &app.tasd.packets {
+    for (packet, rows) in app.tasd.packets.iter().zip(&rendered) {
+        if let Some(rows) = rows {
+            metadata.extend(rows.iter().map(|(label, text)| (label.as_str(), text.clone())));
+            continue;
+        }
         match packet {
             // Skip input chunks and moments
             Packet::InputChunk(_) | Packet::InputMoment(_) => continue,
 
             // Extract data from known packet types
             Packet::ConsoleType(ct) => {
-                let console_names = [
-                    (1, "NES"),
-                    (2, "SNES"),
-                    (3, "N64"),
-                    (4, "GameCube"),
-                    (5, "Game Boy"),
-                    (6, "Game Boy Color"),
-                    (7, "Game Boy Advance"),
-                    (8, "Sega Genesis"),
-                    (9, "Atari 2600")
-                ];
-
-                let console_name = console_names
-                    .iter()
-                    .find(|(code, _)| *code == ct.console as u8)
-                    .map(|(_, name)| *name)
-                    .unwrap_or("Unknown");
+                let console_name = catalog::console_name(ct.console as u8);
 
                 if !ct.name.is_empty() {
                     metadata.push(("Console", format!("{} ({})", console_name, ct.name)));
@@ -73,13 +91,7 @@ pub fn render_sidebar(app: &App, area: Rect, buf: &mut Buffer) {
                 metadata.push(("ROM", rn.name.clone()));
             }
             Packet::Attribution(at) => {
-                let attr_type = match at.attribution_type as u8 {
-                    1 => "Author",
-                    2 => "Verifier",
-                    3 => "File Creator",
-                    4 => "File Editor",
-                    _ => "Other",
-                };
+                let attr_type = attributions::type_name(at.attribution_type as u8);
                 metadata.push((attr_type, at.name.clone()));
             }
             Packet::Category(cat) => {
@@ -112,7 +124,12 @@ pub fn render_sidebar(app: &App, area: Rect, buf: &mut Buffer) {
             Packet::MovieLicense(ml) => {
                 metadata.push(("License", ml.license.clone()));
             }
-            Packet::Comment(c) => {
+            // Signatures are checked with `tasd-cli verify-sig`, so only note their presence
+            Packet::Comment(_) if signature::is_signature_packet(packet) => {
+                metadata.push(("Signature", "ed25519".to_string()));
+            }
+            // Checksums and frame notes are machine-readable and shown separately
+            Packet::Comment(c) if !checksum::is_checksum_packet(packet) && !notes::is_note_packet(packet) => {
                 metadata.push(("Comment", c.comment.clone()));
             }
             // Add any other packet types you want to show
@@ -120,10 +137,60 @@ pub fn render_sidebar(app: &App, area: Rect, buf: &mut Buffer) {
         }
     }
 
+    // Report stored input checksums
+    match &app.integrity {
+        Integrity::Unchecked => {}
+        Integrity::Verified(count) => metadata.push(("Integrity", format!("OK ({} ports)", count))),
+        Integrity::Mismatch(ports) => metadata.push(("Integrity", format!("MISMATCH on ports {:?}", ports))),
+    }
+
+    // Flag a TotalFrames that disagrees with the stored input
+    if let Some(mismatch) = &app.frame_report.mismatch {
+        metadata.push(("Frame Count", format!("MISMATCH: {} (:fixframes)", mismatch.describe())));
+    }
+    if let Some(shorter) = app.frame_report.describe_shorter() {
+        metadata.push(("Short Ports", shorter));
+    }
+
     // Add UI information
-    metadata.push(("Total Inputs", app.cursor.total_inputs.to_string()));
-    metadata.push(("Current Input", app.cursor.input_index.to_string()));
+    metadata.push(("Total Inputs", app.total_frames.to_string()));
+    metadata.push(("Current Input", app.current_frame().to_string()));
+    if let Some(note) = notes::notes(&app.tasd.packets).remove(&app.current_frame()) {
+        metadata.push(("Note", note));
+    }
+    metadata.push(("Time", timing::format_timestamp(app.current_frame(), app.framerate())));
+    if let Some(filter) = &app.filter {
+        metadata.push(("Filter", format!("{} ({} frames)", filter.expr, filter.frames.len())));
+    }
+    if let Some(highlight) = &app.highlight {
+        metadata.push(("Highlight", format!("{} ({} frames)", highlight.expr, highlight.frames.len())));
+    }
+    metadata.push(("Framerate", format!(
+        "{:.4}{}",
+        app.framerate(),
+        if app.config.framerate.is_some() { " (override)" } else { "" }
+    )));
     metadata.push(("Ports", format!("{:?}", app.ports)));
+    for port in &app.ports {
+        let controller = app.controller(*port);
+        if let Some(accessory) = controller.accessory() {
+            // N64 accessory declarations matter for hardware setup, so list them on their own
+            let text = match app.config.accessories.get(port) {
+                Some(plugged) if *plugged != accessory => format!(
+                    "Port {}: {} (MISMATCH: hardware has {})", port, accessory.name(), plugged.name(),
+                ),
+                _ => format!("Port {}: {}", port, accessory.name()),
+            };
+            metadata.push(("Accessory", text));
+        } else if controller != ControllerType::NesStandard {
+            metadata.push(("Controller", format!("Port {}: {}", port, controller.name())));
+        }
+    }
+    if !app.display.hidden_ports.is_empty() {
+        let mut hidden: Vec<&u8> = app.display.hidden_ports.iter().collect();
+        hidden.sort();
+        metadata.push(("Hidden", format!("{:?}", hidden)));
+    }
     metadata.push(("Debug", if app.display.show_debug { "On".to_string() } else { "Off".to_string() }));
 
     // Add number buffer if active
@@ -177,77 +244,143 @@ pub fn render_sidebar(app: &App, area: Rect, buf: &mut Buffer) {
     }
 }
 
-/// Format NES controller input for display
-fn format_nes_input(input_data: &[u8], input_idx: usize, debug: bool) -> String {
-    if input_data.is_empty() {
-        return if debug { format!("[{}] Empty", input_idx) } else { "· · · · · · · ·".to_string() };
+/// Render the editable metadata fields in place of the sidebar
+pub fn render_metadata_pane(app: &App, area: Rect, buf: &mut Buffer) {
+    let block = Block::default()
+        .title("Edit Metadata")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Yellow));
+    let inner_area = block.inner(area);
+    block.render(area, buf);
+
+    let pane = &app.metadata_pane;
+    let fields = metadata::fields(&app.tasd.packets);
+    let mut lines = Vec::new();
+
+    for (i, field) in fields.iter().enumerate() {
+        let selected = i == pane.selected.min(fields.len().saturating_sub(1));
+        let style = if selected {
+            Style::default().bg(Color::DarkGray).fg(Color::Yellow)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        let value = match &pane.editing {
+            Some(typed) if selected => Span::styled(format!("{}_", typed), style),
+            _ if field.position.is_none() => Span::styled("(none)", style.fg(Color::Gray)),
+            _ => Span::styled(field.value.clone(), style),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{}: ", field.label), style.fg(Color::Blue)),
+            value,
+        ]));
     }
 
-    let input_byte = input_data[0];
+    // Key hints on the last line
+    let hint_row = inner_area.height.saturating_sub(1) as usize;
+    while lines.len() < hint_row {
+        lines.push(Line::raw(""));
+    }
+    let hint = if pane.editing.is_some() {
+        "Enter: save  Esc: cancel"
+    } else {
+        "Enter: edit  y: copy  j/k: select  Tab: back"
+    };
+    lines.push(Line::styled(hint, Style::default().fg(Color::Gray)));
+
+    Paragraph::new(lines).render(inner_area, buf);
+}
+
+/// Format one frame of controller input for display
+fn format_input(controller: ControllerType, frame: &[u8], input_idx: usize, debug: bool) -> String {
+    if frame.is_empty() {
+        return if debug { format!("[{}] Empty", input_idx) } else { controller.empty_frame() };
+    }
 
     if debug {
         // Debug display showing hex and binary
-        format!(
-            "[{}] 0x{:02X} {:08b}",
-            input_idx,
-            input_byte,
-            input_byte
-        )
+        let binary: Vec<String> = frame.iter().map(|byte| format!("{:08b}", byte)).collect();
+        format!("[{}] 0x{} {}", input_idx, controller::format_hex(frame).replace(' ', ""), binary.join(" "))
     } else {
-        // User-friendly display for normal view
-        // NES controller bits are active LOW - 0 means pressed
-        let a = (input_byte & 0x01) == 0;
-        let b = (input_byte & 0x02) == 0;
-        let select = (input_byte & 0x04) == 0;
-        let start = (input_byte & 0x08) == 0;
-        let up = (input_byte & 0x10) == 0;
-        let down = (input_byte & 0x20) == 0;
-        let left = (input_byte & 0x40) == 0;
-        let right = (input_byte & 0x80) == 0;
-
-        // Use consistent fixed-width formatting with spaces between buttons
-        format!(
-            "{} {} {} {} {} {} {} {}",
-            if up { "↑" } else { "·" },
-            if down { "↓" } else { "·" },
-            if left { "←" } else { "·" },
-            if right { "→" } else { "·" },
-            if a { "A" } else { "·" },
-            if b { "B" } else { "·" },
-            if select { "S" } else { "·" },
-            if start { "T" } else { "·" }
-        )
+        controller.format_frame(frame)
     }
 }
 
-/// Simple function to collect all inputs from all chunks for a specific port
-fn collect_port_inputs(packets: &[Packet], port: u8) -> Vec<u8> {
-    let mut inputs = Vec::new();
+/// Width a port column needs to show its frames without clipping
+fn column_width(controller: ControllerType, debug: bool) -> u16 {
+    let size = controller.frame_size();
+    let content = if debug {
+        // "[index] 0x<hex> <binary...>"
+        8 + 3 + size * 2 + size * 9
+    } else {
+        controller.format_frame(&vec![0; size]).chars().count()
+    };
+    content.max("◀ Port 0 ▶".chars().count()) as u16
+}
+
+/// Lag frames and frame-indexed transitions, for the events column
+struct FrameEvents {
+    /// First frame and length of each lag run
+    lag: Vec<(usize, usize)>,
+    /// Frame and type code of each transition
+    transitions: Vec<(usize, u8)>,
+}
 
-    for packet in packets {
-        if let Packet::InputChunk(chunk) = packet {
-            if chunk.port == port {
-                inputs.extend_from_slice(&chunk.inputs);
+impl FrameEvents {
+    fn new(packets: &[Packet]) -> Self {
+        let mut events = FrameEvents { lag: Vec::new(), transitions: Vec::new() };
+        for packet in packets {
+            match packet {
+                Packet::LagFrameChunk(lag) => events.lag.push((lag.movie_frame as usize, lag.count as usize)),
+                // Only transitions indexed by frame line up with table rows
+                Packet::Transition(tr) if tr.index_type == 1 => {
+                    events.transitions.push((tr.index as usize, tr.transition_type as u8));
+                }
+                _ => {}
             }
         }
+        events
     }
 
-    inputs
+    /// Text and color marking a frame, if anything happens on it
+    fn at(&self, frame: usize) -> Option<(String, Color)> {
+        let mut marks = Vec::new();
+        for (_, code) in self.transitions.iter().filter(|(index, _)| *index == frame) {
+            marks.push(match code {
+                1 => "Reset",
+                2 => "Power",
+                3 => "Restart",
+                _ => "Event",
+            });
+        }
+        if self.lag.iter().any(|(start, count)| (*start..start + count).contains(&frame)) {
+            marks.push("Lag");
+        }
+        if marks.is_empty() {
+            return None;
+        }
+
+        // Console events stand out more than lag
+        let color = if marks == ["Lag"] { Color::Cyan } else { Color::Red };
+        Some((marks.join(" "), color))
+    }
 }
 
 /// Render the main panel with inputs in a table format
-pub fn render_inputs(app: &mut App, area: Rect, buf: &mut Buffer) {
+pub fn render_inputs(app: &mut App, area: Rect, buf: &mut Buffer, focused: bool) {
     // Create the block with title based on mode
     let title = match app.mode {
-        AppMode::Normal => format!("Inputs (Current: {})", app.cursor.input_index),
-        AppMode::Command => format!("Command: {}", app.command_buffer),
-        _ => format!("Inputs (Current: {})", app.cursor.input_index),
+        AppMode::Command if focused => format!("Command: {}", app.command_buffer),
+        AppMode::Search if focused => format!("Search: /{}", app.command_buffer),
+        _ => match &app.filter {
+            Some(filter) => format!("Inputs matching {} (Current: {})", filter.expr, app.current_frame()),
+            None => format!("Inputs (Current: {})", app.current_frame()),
+        },
     };
 
     let block = Block::default()
         .title(title)
         .borders(Borders::ALL)
-        .style(Style::default().fg(Color::White));
+        .style(Style::default().fg(if focused { Color::White } else { Color::DarkGray }));
 
     // Calculate inner area BEFORE rendering
     let inner_area = block.inner(area);
@@ -266,21 +399,67 @@ pub fn render_inputs(app: &mut App, area: Rect, buf: &mut Buffer) {
     // Ensure the current input is visible
     app.update_input_window();
 
-    // Collect all inputs for each port - simple approach for debugging
-    let mut all_port_inputs: HashMap<u8, Vec<u8>> = HashMap::new();
-    for port in &app.ports {
-        all_port_inputs.insert(*port, collect_port_inputs(&app.tasd.packets, *port));
+    // Scroll the port columns horizontally, keeping the other columns in place
+    let columns = app.table_columns();
+    let all_ports = app.visible_ports();
+    let active_port = app.select_port();
+    let offset = app.display.column_offset.min(all_ports.len().saturating_sub(1));
+    let events = columns.contains(&Column::Events).then(|| FrameEvents::new(&app.tasd.packets));
+    let framerate = app.framerate();
+    // Raw bytes of every port in the layout, two spaces between ports
+    let hex_width = all_ports.iter()
+        .map(|port| app.controller(*port).frame_size() * 3 - 1)
+        .sum::<usize>()
+        .saturating_add(2 * all_ports.len().saturating_sub(1))
+        .max("Hex".len()) as u16;
+    let timestamp_width = timing::format_timestamp(app.total_frames, framerate).len().max("Time".len()) as u16;
+    let width_of = |column: &Column| match column {
+        Column::Frame => 8,
+        Column::Events => 7,
+        Column::Hex => hex_width,
+        Column::Timestamp => timestamp_width,
+        Column::Ports | Column::Port(_) => 0,
+    };
+    let mut used_width = columns.iter()
+        .filter(|column| !matches!(column, Column::Port(_)))
+        .map(|column| if *column == Column::Frame { 8 } else { 1 + width_of(column) })
+        .sum::<u16>();
+    // With `:diff`, the other movie's input of one port is shown next to the table
+    let diff_port = app.diff.as_ref().and(active_port);
+    let diff_width = diff_port.map_or(0, |port| column_width(app.controller(port), false).max(20));
+    if diff_port.is_some() {
+        used_width += 1 + diff_width;
+    }
+    let mut ports = Vec::new();
+    for port in all_ports.iter().skip(offset) {
+        let width = column_width(app.controller(*port), app.display.show_debug);
+        if !ports.is_empty() && used_width + 1 + width > inner_area.width {
+            break;
+        }
+        used_width += 1 + width;
+        ports.push(*port);
     }
+    let more_right = offset + ports.len() < all_ports.len();
+    let columns: Vec<Column> = columns.into_iter()
+        .filter(|column| match column {
+            Column::Port(port) => ports.contains(port),
+            _ => true,
+        })
+        .collect();
 
     // Create table rows with raw data for each port
     let mut rows = Vec::new();
+    let notes = notes::notes(&app.tasd.packets);
 
     // Start from app.input_window_start and show as many as we can fit
     let start_idx = app.input_window_start;
     let end_idx = (start_idx + app.display.max_visible_inputs).min(app.cursor.total_inputs);
 
-    for idx in start_idx..end_idx {
-        let is_current = idx == app.cursor.input_index;
+    for row in start_idx..end_idx {
+        let is_current = row == app.cursor.input_index;
+        let idx = app.frame_at(row);
+        let is_highlighted = app.highlight.as_ref()
+            .is_some_and(|highlight| highlight.frames.binary_search(&idx).is_ok());
 
         // Define style for line number
         let idx_style = if is_current {
@@ -289,60 +468,135 @@ pub fn render_inputs(app: &mut App, area: Rect, buf: &mut Buffer) {
             Style::default().fg(Color::Gray)
         };
 
-        let mut cells = vec![
-            Cell::from(Span::styled(
-                format!("{:04}", idx),
-                idx_style
-            ))
-        ];
-
-        // Add a cell for each port
-        for port in &app.ports {
-            let empty_vec = Vec::new(); // Create a longer-lived value
-            let port_inputs = all_port_inputs.get(port).unwrap_or(&empty_vec);
-
-            let cell_content = if idx < port_inputs.len() {
-                format_nes_input(&[port_inputs[idx]], idx, app.display.show_debug)
-            } else {
-                if app.display.show_debug {
-                    format!("[{}] Out of range", idx)
-                } else {
-                    "· · · · · · · ·".to_string()
+        let mut cells = Vec::new();
+        for column in &columns {
+            cells.push(match column {
+                // Frames with a note get a marker (Enter shows the note)
+                Column::Frame => Cell::from(Line::from(vec![
+                    Span::styled(format!("{:04}", idx), idx_style),
+                    Span::styled(if notes.contains_key(&idx) { " ✎" } else { "" }, Style::default().fg(Color::Cyan)),
+                ])),
+                Column::Events => match events.as_ref().and_then(|events| events.at(idx)) {
+                    Some((mark, color)) => Cell::from(Span::styled(mark, Style::default().fg(color))),
+                    None => Cell::from(""),
+                },
+                Column::Hex => {
+                    let bytes: Vec<String> = all_ports.iter()
+                        .map(|port| {
+                            let frame = app.frame_inputs(*port, idx).unwrap_or_default();
+                            frame.iter().map(|byte| format!("{:02X}", byte)).collect::<Vec<_>>().join(" ")
+                        })
+                        .collect();
+                    Cell::from(Span::styled(bytes.join("  "), Style::default().fg(Color::DarkGray)))
                 }
-            };
+                Column::Timestamp => Cell::from(Span::styled(
+                    timing::format_timestamp(idx, framerate),
+                    Style::default().fg(Color::DarkGray),
+                )),
+                Column::Ports => Cell::from(""),
+                Column::Port(port) => {
+                    let controller = app.controller(*port);
+
+                    // Only the frames on screen are read out of the chunks
+                    let cell_content = if let Some(frame) = app.frame_inputs(*port, idx) {
+                        format_input(controller, &frame, idx, app.display.show_debug)
+                    } else if app.display.show_debug {
+                        format!("[{}] Out of range", idx)
+                    } else {
+                        controller.empty_frame()
+                    };
+
+                    let differs = app.diff.as_ref()
+                        .and_then(|diff| diff.port(*port))
+                        .is_some_and(|diff| diff.differs(idx));
+
+                    // Define cell style
+                    let cell_style = if is_current {
+                        Style::default().bg(Color::DarkGray)
+                    } else if differs {
+                        Style::default().fg(Color::Red)
+                    } else if is_highlighted {
+                        Style::default().fg(Color::Magenta)
+                    } else {
+                        Style::default()
+                    };
+
+                    Cell::from(Span::styled(cell_content, cell_style))
+                }
+            });
+        }
 
-            // Define cell style
-            let cell_style = if is_current {
-                Style::default().bg(Color::DarkGray)
-            } else {
-                Style::default()
+        if let (Some(diff), Some(port)) = (&app.diff, diff_port) {
+            let differs = diff.port(port).is_some_and(|diff| diff.differs(idx));
+            let controller = diff.controllers.get(&port).copied().unwrap_or(ControllerType::NesStandard);
+            let content = match diff.other_input(port, idx) {
+                Some(frame) => controller.format_frame(&frame),
+                None => "-".to_string(),
             };
-
-            cells.push(Cell::from(Span::styled(cell_content, cell_style)));
+            let style = match (is_current, differs) {
+                (true, _) => Style::default().bg(Color::DarkGray),
+                (false, true) => Style::default().fg(Color::Red),
+                (false, false) => Style::default().fg(Color::DarkGray),
+            };
+            cells.push(Cell::from(Span::styled(content, style)));
         }
 
         rows.push(Row::new(cells));
     }
 
     // Create table header with port numbers
-    let mut header = vec![
-        Cell::from(Span::styled(
-            "Input #",
-            Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD)
-        ))
-    ];
+    let header_style = Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD);
+    let mut header = Vec::new();
+    for column in &columns {
+        header.push(match column {
+            Column::Frame => Cell::from(Span::styled("Input #", header_style)),
+            Column::Events => Cell::from(Span::styled("Events", header_style)),
+            Column::Hex => Cell::from(Span::styled("Hex", header_style)),
+            Column::Timestamp => Cell::from(Span::styled("Time", header_style)),
+            Column::Ports => Cell::from(""),
+            Column::Port(port) => {
+                // Arrows show there are columns scrolled out of view
+                let mut label = format!("Port {}", port);
+                if ports.first() == Some(port) && offset > 0 {
+                    label = format!("◀ {}", label);
+                }
+                if ports.last() == Some(port) && more_right {
+                    label = format!("{} ▶", label);
+                }
+                // The active port, which edits and searches apply to, stands out
+                let color = if Some(*port) == active_port && ports.len() > 1 { Color::Yellow } else { Color::Blue };
+                Cell::from(Span::styled(
+                    label,
+                    Style::default().fg(color).add_modifier(Modifier::BOLD),
+                ))
+            }
+        });
+    }
 
-    for port in &app.ports {
+    if let (Some(diff), Some(port)) = (&app.diff, diff_port) {
         header.push(Cell::from(Span::styled(
-            format!("Port {}", port),
-            Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD),
+            format!("{} P{}", diff.name, port),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
         )));
     }
 
     // Calculate constraints for the table columns
-    let mut constraints = vec![Constraint::Length(8)]; // Input number column
-    for _ in &app.ports {
-        constraints.push(Constraint::Min(20)); // Input data columns - wider for debug info
+    let mut constraints = Vec::new();
+    for column in &columns {
+        constraints.push(match column {
+            Column::Port(port) => {
+                let width = column_width(app.controller(*port), app.display.show_debug);
+                if columns.last() == Some(&Column::Port(*port)) {
+                    Constraint::Min(width) // A port in the last column takes the remaining space
+                } else {
+                    Constraint::Length(width)
+                }
+            }
+            column => Constraint::Length(width_of(column)),
+        });
+    }
+    if diff_port.is_some() {
+        constraints.push(Constraint::Length(diff_width));
     }
 
     // Create and render the table
@@ -354,6 +608,198 @@ pub fn render_inputs(app: &mut App, area: Rect, buf: &mut Buffer) {
     Widget::render(table, inner_area, buf);
 }
 
+/// Render the input table, or both panes of a `:split`
+fn render_panes(app: &mut App, area: Rect, buf: &mut Buffer) {
+    let Some(below) = app.split.as_ref().map(|pane| pane.below) else {
+        render_inputs(app, area, buf, true);
+        return;
+    };
+    let halves = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+    let (active, other) = if below { (halves[0], halves[1]) } else { (halves[1], halves[0]) };
+
+    // Draw the other pane with its own cursor and window, then the active one last so its
+    // window size is the one kept for paging
+    app.swap_panes();
+    render_inputs(app, other, buf, false);
+    app.swap_panes();
+    render_inputs(app, active, buf, true);
+}
+
+/// Render the heatmap strip, each row shaded by how many buttons are held over its frames
+pub fn render_heatmap(app: &mut App, area: Rect, buf: &mut Buffer) {
+    const SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+    let block = Block::default().title("Map").borders(Borders::ALL);
+    let inner = block.inner(area);
+    block.render(area, buf);
+
+    let rows = inner.height as usize;
+    app.display.heatmap_rows = rows;
+    let per_row = heatmap::frames_per_row(app.total_frames, rows);
+    let densities = app.heatmap_densities(rows).to_vec();
+    let max = densities.iter().copied().fold(0.0, f32::max);
+
+    // Rows overlapping the frames currently shown in the input table
+    let window_start = app.input_window_start / per_row;
+    let window_end = (app.input_window_start + app.display.max_visible_inputs).saturating_sub(1) / per_row;
+    let cursor_row = app.current_frame() / per_row;
+
+    let lines: Vec<Line> = densities.iter()
+        .enumerate()
+        .map(|(row, density)| {
+            let shade = if max > 0.0 { (density / max * 4.0).ceil() as usize } else { 0 };
+            let style = if row == cursor_row {
+                Style::default().fg(app.display.highlight_color)
+            } else if (window_start..=window_end).contains(&row) {
+                Style::default().fg(Color::Cyan)
+            } else {
+                Style::default()
+            };
+            Line::from(Span::styled(SHADES[shade.min(4)].to_string().repeat(inner.width as usize), style))
+        })
+        .collect();
+    Paragraph::new(lines).render(inner, buf);
+}
+
+/// Render the detail panel describing every port at the cursor row
+pub fn render_detail(app: &App, area: Rect, buf: &mut Buffer) {
+    let block = Block::default()
+        .title(format!("Input {} Detail", app.current_frame()))
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::White));
+
+    // Calculate inner area BEFORE rendering
+    let inner_area = block.inner(area);
+
+    // Render block
+    block.render(area, buf);
+
+    // Give each port an equal share of the panel
+    let port_areas = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(app.ports.iter().map(|_| Constraint::Ratio(1, app.ports.len() as u32)))
+        .split(inner_area);
+
+    for (port, port_area) in app.ports.iter().zip(port_areas.iter()) {
+        let controller = app.controller(*port);
+        let frame = app.frame_inputs(*port, app.current_frame()).unwrap_or_default();
+        let frame = frame.as_slice();
+
+        let mut lines = vec![Line::styled(
+            format!("Port {}: {}", port, controller.name()),
+            Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD),
+        )];
+        if controller == ControllerType::GameCube && !frame.is_empty() {
+            Paragraph::new(lines).render(*port_area, buf);
+            let body = Rect { y: port_area.y + 1, height: port_area.height.saturating_sub(1), ..*port_area };
+            render_gamecube_detail(controller, frame, body, buf);
+            continue;
+        }
+        if frame.is_empty() {
+            lines.push(Line::raw("No data"));
+        } else {
+            lines.extend(controller.describe_frame(frame).into_iter().map(Line::raw));
+        }
+
+        Paragraph::new(lines).render(*port_area, buf);
+    }
+}
+
+/// Render GameCube sticks as crosshairs next to analog trigger gauges
+fn render_gamecube_detail(controller: ControllerType, frame: &[u8], area: Rect, buf: &mut Buffer) {
+    let pad = GameCubePad::decode(frame);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Min(10),
+        ])
+        .split(area);
+
+    render_stick("Main", pad.stick, columns[0], buf);
+    render_stick("C", pad.c_stick, columns[1], buf);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Min(0),
+        ])
+        .split(columns[2]);
+
+    for (label, value, row) in [("L", pad.l_analog, rows[0]), ("R", pad.r_analog, rows[1])] {
+        LineGauge::default()
+            .label(format!("{} {:3}", label, value))
+            .ratio(value as f64 / 255.0)
+            .filled_style(Style::default().fg(Color::Yellow))
+            .unfilled_style(Style::default().fg(Color::DarkGray))
+            .render(row, buf);
+    }
+
+    let pressed: Vec<&str> = controller.buttons()
+        .iter()
+        .filter(|button| controller.is_pressed(button, frame))
+        .map(|button| button.name)
+        .collect();
+    Paragraph::new(vec![
+        Line::raw(format!("Pressed: {}", if pressed.is_empty() { "none".to_string() } else { pressed.join(" ") })),
+        Line::raw(format!("Raw: {}", controller::format_hex(frame))),
+    ])
+    .render(rows[2], buf);
+}
+
+/// Render an analog stick position as a small crosshair
+fn render_stick(label: &str, (x, y): (i16, i16), area: Rect, buf: &mut Buffer) {
+    let width = area.width.saturating_sub(1).max(1) as i32;
+    let height = area.height.max(1) as i32;
+    let center_col = (width - 1) / 2;
+    let center_row = (height - 1) / 2;
+
+    // Map the -128..127 axis range onto the grid, with positive Y pointing up
+    let dot_col = center_col + (x as i32 * (width - 1)) / 256;
+    let dot_row = center_row - (y as i32 * (height - 1)) / 256;
+
+    let mut lines = Vec::new();
+    for row in 0..height {
+        let mut spans = Vec::new();
+        for col in 0..width {
+            let symbol = if row == dot_row && col == dot_col {
+                "●"
+            } else if row == center_row && col == center_col {
+                "┼"
+            } else if row == center_row {
+                "─"
+            } else if col == center_col {
+                "│"
+            } else {
+                " "
+            };
+            let style = if symbol == "●" {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            spans.push(Span::styled(symbol, style));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    // Label the crosshair in its top-left corner
+    if let Some(first) = lines.first_mut() {
+        first.spans.splice(0..label.chars().count().min(first.spans.len()), label.chars().map(|c| {
+            Span::styled(c.to_string(), Style::default().fg(Color::Blue))
+        }));
+    }
+
+    Paragraph::new(lines).render(area, buf);
+}
+
 /// Render the status bar
 pub fn render_status_bar(app: &App, area: Rect, buf: &mut Buffer) {
     let mode_text = match app.mode {
@@ -361,16 +807,66 @@ pub fn render_status_bar(app: &App, area: Rect, buf: &mut Buffer) {
         AppMode::Input => "INPUT",
         AppMode::Help => "HELP",
         AppMode::Command => "COMMAND",
+        AppMode::Picker => "PICK",
+        AppMode::Attributions => "ATTRIBUTIONS",
+        AppMode::Popup => "VIEW",
+        AppMode::Sizes => "SIZES",
+        AppMode::Browser => "BROWSE",
+        AppMode::Finder => "FIND",
+        AppMode::Search => "SEARCH",
+        AppMode::Metadata => "METADATA",
+        AppMode::Packets => "PACKETS",
     };
 
     // Create elements based on app state
     let mut elements = vec![
         Span::styled(format!(" {} ", mode_text),
                      Style::default().bg(Color::Blue).fg(Color::White)),
+    ];
+
+    // Mark unsaved changes like vim's [+]
+    if app.modified {
+        elements.push(Span::styled(" [+] ", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)));
+    }
+    if app.config.readonly {
+        elements.push(Span::styled(" [RO] ", Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)));
+    }
+
+    elements.extend([
         Span::raw(" | "),
-        Span::styled(format!(" Input: {}/{} ", app.cursor.input_index, app.cursor.total_inputs),
+        Span::styled(format!(" Input: {}/{} ", app.current_frame(), app.total_frames),
                      Style::default().fg(Color::Yellow)),
-    ];
+        Span::styled(format!("{} ", timing::format_timestamp(app.current_frame(), app.framerate())),
+                     Style::default().fg(Color::Yellow)),
+    ]);
+
+    // Undo depth, once there is something to undo
+    if app.history.depth() > 0 {
+        elements.push(Span::raw(" | "));
+        elements.push(Span::styled(format!(" Undo: {} ", app.history.depth()), Style::default().fg(Color::Cyan)));
+    }
+
+    // Matches of the last search, and which one the cursor is on
+    if let Some(search) = &app.search {
+        let position = match search.frames.binary_search(&app.current_frame()) {
+            Ok(index) => (index + 1).to_string(),
+            Err(_) => "-".to_string(),
+        };
+        elements.push(Span::raw(" | "));
+        elements.push(Span::styled(format!(" /{} [{}/{}] ", search.expr, position, search.frames.len()),
+                                   Style::default().fg(Color::Green)));
+    }
+
+    // Frames differing from the `:diff` movie, and which one the cursor is on
+    if let Some(diff) = &app.diff {
+        let position = match diff.frames.binary_search(&app.current_frame()) {
+            Ok(index) => (index + 1).to_string(),
+            Err(_) => "-".to_string(),
+        };
+        elements.push(Span::raw(" | "));
+        elements.push(Span::styled(format!(" diff {} [{}/{}] ", diff.name, position, diff.frames.len()),
+                                   Style::default().fg(Color::Red)));
+    }
 
     // Show number buffer if active
     if let Some(num) = app.number_buffer {
@@ -379,6 +875,43 @@ pub fn render_status_bar(app: &App, area: Rect, buf: &mut Buffer) {
                                    Style::default().fg(Color::Magenta)));
     }
 
+    // Saves and exports still running in the background
+    if let Some(task) = app.worker.status() {
+        elements.push(Span::raw(" | "));
+        elements.push(Span::styled(format!(" {} ", task), Style::default().fg(Color::Yellow)));
+    }
+
+    // Show feedback from the last command in place of the shortcuts
+    if let Some(message) = &app.status_message {
+        elements.push(Span::raw(" | "));
+        elements.push(Span::styled(format!(" {} ", message), Style::default().fg(Color::White)));
+
+        Paragraph::new(Line::from(elements))
+            .style(Style::default().bg(Color::Black))
+            .render(area, buf);
+        return;
+    }
+
+    // Editing keys replace the usual shortcuts in input mode
+    if app.mode == AppMode::Input {
+        elements.extend_from_slice(&[
+            Span::raw(" | "),
+            Span::styled(format!(" Port {} ", app.edit_port), Style::default().fg(Color::Yellow)),
+            Span::raw(" | "),
+            Span::styled(" Button letter/arrows: Toggle ", Style::default().fg(Color::Gray)),
+            Span::raw(" | "),
+            Span::styled(" j/k: Frame ", Style::default().fg(Color::Gray)),
+            Span::raw(" | "),
+            Span::styled(" Tab: Port ", Style::default().fg(Color::Gray)),
+            Span::raw(" | "),
+            Span::styled(" Esc: Done ", Style::default().fg(Color::Gray)),
+        ]);
+        Paragraph::new(Line::from(elements))
+            .style(Style::default().bg(Color::Black))
+            .render(area, buf);
+        return;
+    }
+
     // Add keyboard shortcuts
     elements.extend_from_slice(&[
         Span::raw(" | "),
@@ -397,29 +930,116 @@ pub fn render_status_bar(app: &App, area: Rect, buf: &mut Buffer) {
 }
 
 /// Render help dialog
-pub fn render_help(area: Rect, buf: &mut Buffer) {
+pub fn render_help(app: &mut App, area: Rect, buf: &mut Buffer) {
     let help_text = vec![
         "Navigation",
         "j/↓: Next input",
         "k/↑: Previous input",
-        "g: Go to first input",
+        "gg: Go to first input",
         "G: Go to last input",
+        "m<letter>/'<letter>: Set mark/jump to mark (:marks lists them)",
+        "Enter: Show the note on the current frame (marked ✎)",
         "H: Go to first visible line",
         "M: Go to middle visible line",
         "L: Go to last visible line",
         "z: Center current line",
         "Ctrl+d: Half page down",
         "Ctrl+u: Half page up",
+        "Ctrl+t: Find a file below the working directory",
         "Ctrl+f/PageDown: Full page down",
         "Ctrl+b/PageUp: Full page up",
         "NUMBER: Repeat next command N times",
+        "Tab: Edit metadata (Enter: edit field, empty removes it)",
+        "/EXPR: Search the edit port (A+B, start, 0xFC)",
+        "n/N: Next/previous match",
+        "]c/[c: Next/previous difference from :diff",
+        "}/{: Next/previous change of the active port's input",
+        "]b<button>/[b<button>: Next/previous press or release of a button",
+        "]h/[h: Next/previous dense section on the heatmap",
+        ":split [FRAME]: Second pane over the file (Ctrl+w w: switch, Ctrl+w q: close)",
+        ":only: Close the other pane",
+        "",
+        "Editing",
+        "i: Edit the buttons of the current frame",
+        "  a/b/s/t/arrows: Toggle the button with that glyph",
+        "  j/k: Previous/next frame, Tab/1-8: Next port/port N, Esc: Done",
+        "dd: Delete frame (Ndd: N frames)",
+        "o/O: Insert a blank frame after/before",
+        "yy/p/Ctrl+p: Copy frames, paste after/before",
+        "\"+yy/\"+p: Copy frames to/paste from the system clipboard",
+        ".: Repeat the last toggle, delete, insert or paste (N.: with count N)",
+        "u / Ctrl+r: Undo / redo",
         "",
         "Commands",
         ":q or :quit: Exit application",
+        ":q!: Exit without saving changes",
+        ":wq or :x: Save and exit",
         ":NUMBER: Jump to line number",
+        ":time 1:23.456: Jump to the frame at a time",
+        ":w [PATH]: Save file (.gz/.zst/.xz compress)",
+        ":export FORMAT PATH: Write as fm2, m64, dtm, smv, lsmv, vbm, r08, r16m, yaml, json, overlay-json or overlay-csv",
+        ":export-range START END PATH: Write those frames to a new TASD",
+        ":n[ext] / :N, :prev: Open next/previous file",
+        "gt/gT, :tabn/:tabp: Next/previous tab (edits are kept)",
+        ":e PATH: Open a file in a new tab",
+        ":e!: Reload the file from disk, dropping unsaved changes",
+        ":args: List the open files",
+        ":browse [DIR]: Browse the TASD files in a directory (default: the open file's)",
+        ":license [ID]: Pick the movie license",
+        ":authors: Edit attributions",
+        ":category [NAME]: Pick the category",
+        ":fetch-meta ID: Fill metadata from TASVideos",
+        ":attach PATH: Embed a source movie file",
+        ":errors: List the byte ranges skipped in a damaged file",
+        ":latches: Latch timing: latch filter, lag frames and latches of each frame, flagging double/missed ones",
+        ":note [FRAME] TEXT: Note a frame (no text removes it), :notes lists them",
+        ":attachments: List embedded files",
+        ":extract N PATH: Write attachment N to disk",
+        ":filter [EXPR]: Show only matching frames, or clear",
+        ":count EXPR: Count matching frames",
+        ":highlight [EXPR]: Highlight matching frames, or clear",
+        "  EXPR: A & !B, Start | Select, port2.A, changed(A)",
+        ":order [PORTS...]: Show these ports first, or reset",
+        ":stamp [NOTES]: Mark verified (firmware, settings in NOTES)",
+        ":optimize: Merge chunks and drop repeated packets",
+        ":fixframes: Set TotalFrames to the stored input length",
+        ":report structure: Chunk layout and packet sizes",
+        ":report sizes: Chart of bytes per packet type",
+        ":stats: Button presses, holds and blank frames per port",
+        ":addpacket TYPE VALUE|field=value...: Add a packet",
+        ":rmpacket INDEX: Remove a packet (indices from P)",
+        ":checkpoint NAME: Save a named checkpoint",
+        ":checkpoints: List checkpoints",
+        ":checkpoint-diff NAME: Show changes since a checkpoint",
+        ":diff FILE: Compare inputs with another file",
+        ":diffoff: Stop comparing",
+        ":rollback NAME: Restore a checkpoint",
+        ":set framerate FPS|auto: Override framerate",
+        ":set checksums on|off: Store checksums on save",
+        ":hash: SHA-256 of the file and each port's input (:hash embed stores them)",
+        ":set compresslevel N: Compression level",
+        ":set aligndiff on|off: Match frames by content in diffs",
+        ":set optimize on|off: Run :optimize on save",
+        ":set rememberorder on|off: Keep :order per file",
+        ":set verifier NAME: Name credited by :stamp",
+        ":set signkey PATH: Sign with this key on save",
+        ":set renderer CMD: Show packets with a plugin command",
+        ":set events on|off: Column marking lag frames and resets",
+        ":set heatmap on|off: Strip shading button density over the movie",
+        ":set lenient on|off: Open damaged files without asking",
+        ":set readonly on|off: Block edits and saving (--readonly)",
+        ":set columns=frame,port1,hex,timestamp: Choose table columns (saved)",
+        ":set accessories 1=rumble,2=none: N64 paks on the playback hardware (cpak, tpak), flagged if the file declares others",
         "",
         "Other",
         "D: Toggle debug info",
+        "P: List every packet (Enter: hex dump)",
+        "Nv: Hide/show port N",
+        "V: Show all ports",
+        ":port N: Make port N the one edited and searched",
+        ":ports 1,3: Show only these ports (:ports all shows every port)",
+        ":controller N arkanoid|auto: Read port N as an Arkanoid paddle, or as declared",
+        "h/l: Scroll port columns",
         "Esc: Cancel operation",
         "q: Quit",
         "?: Show/hide help",
@@ -444,9 +1064,9 @@ pub fn render_help(area: Rect, buf: &mut Buffer) {
             .collect::<Vec<Line>>(),
     );
 
-    // Calculate dialog position (centered)
-    let width = 50;
-    let height = text.height() as u16 + 2;
+    // Calculate dialog position (centered), clipped to the terminal
+    let width = 50.min(area.width);
+    let height = (text.height() as u16 + 2).min(area.height);
     let x = (area.width - width) / 2;
     let y = (area.height - height) / 2;
     let dialog_area = Rect::new(x, y, width, height);
@@ -455,10 +1075,15 @@ pub fn render_help(area: Rect, buf: &mut Buffer) {
     let dialog_block = Block::default()
         .borders(Borders::ALL)
         .title("Help")
+        .title_bottom(" j/k, PageUp/PageDown: Scroll  q: Close ")
         .style(Style::default().fg(Color::White));
 
     let inner_dialog_area = dialog_block.inner(dialog_area);
 
+    // Keep the last page full when scrolled to the end
+    app.help_page = inner_dialog_area.height as usize;
+    app.help_scroll = app.help_scroll.min(text.height().saturating_sub(app.help_page));
+
     // Render a dark background behind the dialog
     Clear.render(dialog_area, buf);
 
@@ -467,9 +1092,509 @@ pub fn render_help(area: Rect, buf: &mut Buffer) {
 
     // Render the text inside the dialog
     Paragraph::new(text)
+        .scroll((app.help_scroll as u16, 0))
         .render(inner_dialog_area, buf);
 }
 
+/// Render the picker popup
+pub fn render_picker(picker: &Picker, area: Rect, buf: &mut Buffer) {
+    // Calculate dialog position (centered)
+    let width = 70.min(area.width);
+    let height = 20.min(area.height);
+    let x = (area.width - width) / 2;
+    let y = (area.height - height) / 2;
+    let dialog_area = Rect::new(x, y, width, height);
+
+    let dialog_block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("{} (Enter: choose, Tab: complete, Esc: cancel)", picker.title))
+        .style(Style::default().fg(Color::White));
+
+    let inner_area = dialog_block.inner(dialog_area);
+
+    Clear.render(dialog_area, buf);
+    dialog_block.render(dialog_area, buf);
+
+    let filtered = picker.filtered();
+    let mut lines = vec![Line::from(vec![
+        Span::styled("> ", Style::default().fg(Color::Yellow)),
+        Span::raw(picker.query.clone()),
+    ])];
+
+    if filtered.is_empty() {
+        lines.push(Line::styled("No matches - Enter uses the typed text", Style::default().fg(Color::Gray)));
+    }
+
+    // Keep the selection in view
+    let visible = inner_area.height.saturating_sub(1) as usize;
+    let start = picker.selected.saturating_sub(visible.saturating_sub(1));
+
+    for (i, (value, description)) in filtered.iter().enumerate().skip(start).take(visible) {
+        let style = if i == picker.selected {
+            Style::default().bg(Color::DarkGray).fg(Color::Yellow)
+        } else {
+            Style::default()
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:<18}", value), style.add_modifier(Modifier::BOLD)),
+            Span::styled(description.clone(), style),
+        ]));
+    }
+
+    Paragraph::new(lines).render(inner_area, buf);
+}
+
+/// Render the fuzzy file finder
+pub fn render_finder(finder: &Finder, area: Rect, buf: &mut Buffer) {
+    let width = (area.width * 4 / 5).max(40).min(area.width);
+    let height = 20.min(area.height);
+    let x = (area.width - width) / 2;
+    let y = (area.height - height) / 2;
+    let dialog_area = Rect::new(x, y, width, height);
+
+    let dialog_block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            "Find in {}{} (Enter: open, Esc: cancel)",
+            finder.root.display(),
+            if finder.busy() { ", reading titles..." } else { "" },
+        ))
+        .style(Style::default().fg(Color::White));
+
+    let inner_area = dialog_block.inner(dialog_area);
+
+    Clear.render(dialog_area, buf);
+    dialog_block.render(dialog_area, buf);
+
+    let matches = finder.matches();
+    let mut lines = vec![Line::from(vec![
+        Span::styled("> ", Style::default().fg(Color::Yellow)),
+        Span::raw(finder.query.clone()),
+        Span::styled(
+            format!("  {}/{}", matches.len(), finder.candidates.len()),
+            Style::default().fg(Color::Gray),
+        ),
+    ])];
+
+    if matches.is_empty() {
+        lines.push(Line::styled("No matching files", Style::default().fg(Color::Gray)));
+    }
+
+    // Keep the selection in view
+    let visible = inner_area.height.saturating_sub(1) as usize;
+    let start = finder.selected.saturating_sub(visible.saturating_sub(1));
+    let path_width = matches.iter()
+        .map(|(path, _)| finder.display_path(path).chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(inner_area.width as usize / 2);
+
+    for (i, (path, title)) in matches.iter().enumerate().skip(start).take(visible) {
+        let style = if i == finder.selected {
+            Style::default().bg(Color::DarkGray).fg(Color::Yellow)
+        } else {
+            Style::default()
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:<width$}  ", finder.display_path(path), width = path_width), style.add_modifier(Modifier::BOLD)),
+            Span::styled(title.clone(), style),
+        ]));
+    }
+
+    Paragraph::new(lines).render(inner_area, buf);
+}
+
+/// Render the attribution list editor
+pub fn render_attributions(app: &App, area: Rect, buf: &mut Buffer) {
+    let width = 60.min(area.width);
+    let height = 16.min(area.height);
+    let x = (area.width - width) / 2;
+    let y = (area.height - height) / 2;
+    let dialog_area = Rect::new(x, y, width, height);
+
+    let dialog_block = Block::default()
+        .borders(Borders::ALL)
+        .title("Attributions")
+        .style(Style::default().fg(Color::White));
+
+    let inner_area = dialog_block.inner(dialog_area);
+
+    Clear.render(dialog_area, buf);
+    dialog_block.render(dialog_area, buf);
+
+    let editor = &app.attribution_editor;
+    let entries = attributions::entries(&app.tasd.packets);
+    let mut lines = Vec::new();
+
+    if entries.is_empty() {
+        lines.push(Line::styled("No attributions - press a to add one", Style::default().fg(Color::Gray)));
+    }
+
+    for (i, (code, name)) in entries.iter().enumerate() {
+        let selected = i == editor.selected;
+        let style = if selected {
+            Style::default().bg(Color::DarkGray).fg(Color::Yellow)
+        } else {
+            Style::default()
+        };
+        let name = match &editor.editing {
+            Some(typed) if selected => format!("{}_", typed),
+            _ => name.clone(),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:<14}", attributions::type_name(*code)), style.fg(Color::Blue)),
+            Span::styled(name, style),
+        ]));
+    }
+
+    // Key hints on the last line
+    let hint_row = inner_area.height.saturating_sub(1) as usize;
+    while lines.len() < hint_row {
+        lines.push(Line::raw(""));
+    }
+    let hint = if editor.editing.is_some() {
+        "Enter: save  Esc: cancel"
+    } else {
+        "a: add  e: edit  t: type  d: delete  J/K: move  q: close"
+    };
+    lines.push(Line::styled(hint, Style::default().fg(Color::Gray)));
+
+    Paragraph::new(lines).render(inner_area, buf);
+}
+
+/// Render every packet of the file, one per row, over the whole screen; packets taken over by a
+/// renderer plugin are summarized with its rows
+pub fn render_packet_list(list: &mut PacketList, packets: &[Packet], renderers: &Registry, area: Rect, buf: &mut Buffer) {
+    if let Some(hex) = list.hex.as_mut() {
+        render_hex_view(hex, area, buf);
+        return;
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Packets ({}/{})", (list.selected + 1).min(list.rows.len()), list.rows.len()))
+        .title_bottom(" j/k: Move  g/G: First/last  Ctrl+d/u: Half page  Enter: Hex dump  q: Close ")
+        .style(Style::default().fg(Color::White));
+    let inner_area = block.inner(area);
+
+    Clear.render(area, buf);
+    block.render(area, buf);
+
+    // One line goes to the column header
+    list.page = inner_area.height.saturating_sub(1) as usize;
+    list.keep_visible();
+
+    let index_width = list.rows.len().saturating_sub(1).to_string().len().max(1) as u16;
+    let rows: Vec<Row> = list.rows.iter().enumerate()
+        .skip(list.scroll)
+        .take(list.page)
+        .map(|(i, row)| {
+            let style = if i == list.selected {
+                Style::default().bg(Color::DarkGray).fg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            let summary = match packets.get(i).and_then(|packet| renderers.render(i, packet)) {
+                Some(rows) => rows.iter()
+                    .map(|(label, text)| format!("{}: {}", label, text))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                None => row.summary.clone(),
+            };
+            Row::new(vec![
+                Cell::from(i.to_string()),
+                Cell::from(format!("{:04X}", row.key)),
+                Cell::from(row.kind.clone()),
+                Cell::from(row.size.to_string()),
+                Cell::from(summary),
+            ]).style(style)
+        })
+        .collect();
+
+    let header = Row::new(vec!["#", "Key", "Type", "Size", "Summary"])
+        .style(Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD));
+    Widget::render(
+        Table::new(rows, [
+            Constraint::Length(index_width),
+            Constraint::Length(4),
+            Constraint::Length(16),
+            Constraint::Length(8),
+            Constraint::Min(10),
+        ]).header(header),
+        inner_area,
+        buf,
+    );
+}
+
+/// Render the hex and ASCII dump of one packet, with its key and length bytes marked
+fn render_hex_view(hex: &mut HexView, area: Rect, buf: &mut Buffer) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Packet {}: {} ({} bytes)", hex.index, hex.kind, hex.data.len()))
+        .title_bottom(" j/k: Scroll  g/G: Start/end  Ctrl+d/u: Half page  q: Back ")
+        .style(Style::default().fg(Color::White));
+    let inner_area = block.inner(area);
+
+    Clear.render(area, buf);
+    block.render(area, buf);
+
+    // The legend takes the first line and a blank line
+    hex.page = inner_area.height.saturating_sub(2) as usize;
+    hex.scroll_by(0);
+
+    let key_style = Style::default().fg(Color::Cyan);
+    let length_style = Style::default().fg(Color::Magenta);
+    let header_end = hex.key_len + hex.length_len;
+    let style_at = |offset: usize| {
+        if offset < hex.key_len {
+            key_style
+        } else if offset < header_end {
+            length_style
+        } else {
+            Style::default()
+        }
+    };
+
+    let key = hex.data[..hex.key_len].iter().fold(0u32, |key, byte| (key << 8) | *byte as u32);
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(format!("Key {:04X}", key), key_style),
+            Span::raw("  "),
+            Span::styled(format!("Length {} ({} bytes)", hex.payload_len(), hex.length_len), length_style),
+            Span::raw(format!("  Payload at {:#x}", header_end)),
+        ]),
+        Line::raw(""),
+    ];
+    for (line, bytes) in hex.data.chunks(HEX_WIDTH).enumerate().skip(hex.scroll).take(hex.page) {
+        let start = line * HEX_WIDTH;
+        let mut spans = vec![Span::styled(format!("{:08x}  ", start), Style::default().fg(Color::Gray))];
+        for (i, byte) in bytes.iter().enumerate() {
+            // An extra gap splits the line in halves
+            let gap = if i == HEX_WIDTH / 2 { "  " } else { " " };
+            spans.push(Span::raw(if i == 0 { "" } else { gap }));
+            spans.push(Span::styled(format!("{:02x}", byte), style_at(start + i)));
+        }
+        let missing = HEX_WIDTH - bytes.len();
+        spans.push(Span::raw(" ".repeat(missing * 3 + usize::from(bytes.len() <= HEX_WIDTH / 2) + 2)));
+        spans.push(Span::raw("|"));
+        for (i, byte) in bytes.iter().enumerate() {
+            let c = if byte.is_ascii_graphic() || *byte == b' ' { *byte as char } else { '.' };
+            spans.push(Span::styled(c.to_string(), style_at(start + i)));
+        }
+        spans.push(Span::raw("|"));
+        lines.push(Line::from(spans));
+    }
+
+    Paragraph::new(lines).render(inner_area, buf);
+}
+
+/// Render a read-only text popup
+pub fn render_popup(popup: &Popup, area: Rect, buf: &mut Buffer) {
+    let longest = popup.lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    let width = (longest as u16 + 2).max(popup.title.len() as u16 + 4).min(area.width);
+    let height = (popup.lines.len() as u16 + 2).min(area.height);
+    let x = (area.width - width) / 2;
+    let y = (area.height - height) / 2;
+    let dialog_area = Rect::new(x, y, width, height);
+
+    let dialog_block = Block::default()
+        .borders(Borders::ALL)
+        .title(popup.title.clone())
+        .style(Style::default().fg(Color::White));
+
+    let inner_area = dialog_block.inner(dialog_area);
+
+    Clear.render(dialog_area, buf);
+    dialog_block.render(dialog_area, buf);
+
+    let lines: Vec<Line> = popup.lines.iter()
+        .skip(popup.scroll)
+        .map(|line| Line::raw(line.clone()))
+        .collect();
+
+    Paragraph::new(lines).render(inner_area, buf);
+}
+
+/// Render the directory browser with a metadata preview of the highlighted file
+pub fn render_browser(browser: &Browser, area: Rect, buf: &mut Buffer) {
+    let width = (area.width * 9 / 10).max(40).min(area.width);
+    let height = (area.height * 4 / 5).max(10).min(area.height);
+    let x = (area.width - width) / 2;
+    let y = (area.height - height) / 2;
+    let dialog_area = Rect::new(x, y, width, height);
+
+    let dialog_block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Browse {}", browser.dir.display()))
+        .style(Style::default().fg(Color::White));
+
+    let inner_area = dialog_block.inner(dialog_area);
+
+    Clear.render(dialog_area, buf);
+    dialog_block.render(dialog_area, buf);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner_area);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(rows[0]);
+
+    // File list, scrolled to keep the highlight visible
+    let visible = columns[0].height as usize;
+    let start = browser.selected.saturating_sub(visible.saturating_sub(1));
+    let mut lines = Vec::new();
+    if browser.entries.is_empty() {
+        lines.push(Line::styled("No TASD files", Style::default().fg(Color::Gray)));
+    }
+    for (i, path) in browser.entries.iter().enumerate().skip(start).take(visible) {
+        let selected = i == browser.selected;
+        let style = if selected {
+            Style::default().bg(Color::DarkGray).fg(Color::Yellow)
+        } else {
+            Style::default()
+        };
+        let name = match &browser.renaming {
+            Some(typed) if selected => format!("{}_", typed),
+            _ => path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+        };
+        lines.push(Line::styled(name, style));
+    }
+    Paragraph::new(lines).render(columns[0], buf);
+
+    // Preview of the highlighted file
+    let preview_block = Block::default().borders(Borders::LEFT).title(" Preview");
+    let preview_area = preview_block.inner(columns[1]);
+    preview_block.render(columns[1], buf);
+
+    let mut lines = Vec::new();
+    if let Some(preview) = &browser.preview {
+        match &preview.summary {
+            Some(summary) => {
+                let verified = match summary.verified {
+                    Some(true) => "Yes",
+                    Some(false) => "No",
+                    None => "-",
+                };
+                let fields = [
+                    ("Game Title", summary.title.clone()),
+                    ("Console", summary.console.clone()),
+                    ("Frames", summary.frames.to_string()),
+                    ("Duration", summary.duration.clone()),
+                    ("Authors", summary.authors.join(", ")),
+                    ("Verified", verified.to_string()),
+                    ("Size", format!("{} bytes", preview.size)),
+                ];
+                for (label, value) in fields {
+                    lines.push(Line::from(vec![
+                        Span::styled(format!("{:<12}", label), Style::default().fg(Color::Blue)),
+                        Span::raw(value),
+                    ]));
+                }
+            }
+            None => lines.push(Line::styled("Not a readable TASD file", Style::default().fg(Color::Red))),
+        }
+    }
+    Paragraph::new(lines).render(preview_area, buf);
+
+    let hint = if browser.renaming.is_some() {
+        "Enter: rename  Esc: cancel"
+    } else if browser.confirm_delete {
+        "Delete this file? y: yes  any other key: no"
+    } else {
+        "Enter: open  r: rename  d: delete  q: close"
+    };
+    Paragraph::new(hint).style(Style::default().fg(Color::Gray)).render(rows[1], buf);
+}
+
+/// Render the bar chart of bytes used by each packet type
+pub fn render_size_chart(kinds: &[(String, usize, usize)], area: Rect, buf: &mut Buffer) {
+    let width = (area.width * 3 / 4).max(40).min(area.width);
+    let height = (kinds.len() as u16 + 4).min(area.height);
+    let x = (area.width - width) / 2;
+    let y = (area.height - height) / 2;
+    let dialog_area = Rect::new(x, y, width, height);
+
+    let dialog_block = Block::default()
+        .borders(Borders::ALL)
+        .title("File Size by Packet Type")
+        .style(Style::default().fg(Color::White));
+
+    let inner_area = dialog_block.inner(dialog_area);
+
+    Clear.render(dialog_area, buf);
+    dialog_block.render(dialog_area, buf);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(1)])
+        .split(inner_area);
+
+    // Totals for inputs, metadata and attachments
+    let total: usize = kinds.iter().map(|k| k.2).sum();
+    let groups: Vec<Span> = ["Inputs", "Metadata", "Attachments"].iter()
+        .map(|group| {
+            let bytes: usize = kinds.iter()
+                .filter(|k| report::packet_group(&k.0) == *group)
+                .map(|k| k.2)
+                .sum();
+            let percent = if total > 0 { bytes as f64 * 100.0 / total as f64 } else { 0.0 };
+            Span::styled(
+                format!("{}: {} bytes ({:.1}%)  ", group, bytes, percent),
+                Style::default().fg(group_color(group)),
+            )
+        })
+        .collect();
+    Paragraph::new(Line::from(groups)).render(chunks[0], buf);
+
+    let label_width = kinds.iter().map(|k| k.0.len()).max().unwrap_or(0) as u16;
+    let bars: Vec<Bar> = kinds.iter()
+        .map(|(kind, count, bytes)| {
+            Bar::default()
+                .label(Line::from(format!("{:>width$}", kind, width = label_width as usize)))
+                .value(*bytes as u64)
+                .text_value(format!("{} bytes in {} packets", bytes, count))
+                .style(Style::default().fg(group_color(report::packet_group(kind))))
+                .value_style(Style::default().fg(Color::Black).bg(group_color(report::packet_group(kind))))
+        })
+        .collect();
+
+    BarChart::default()
+        .direction(Direction::Horizontal)
+        .bar_width(1)
+        .bar_gap(0)
+        .data(BarGroup::default().bars(&bars))
+        .render(chunks[1], buf);
+}
+
+/// Chart color of a packet group
+fn group_color(group: &str) -> Color {
+    match group {
+        "Inputs" => Color::Green,
+        "Attachments" => Color::Magenta,
+        _ => Color::Cyan,
+    }
+}
+
+/// Render the open files as tabs, marking the ones with unsaved changes
+fn render_tabs(app: &App, area: Rect, buf: &mut Buffer) {
+    let titles = app.arg_list.iter()
+        .enumerate()
+        .map(|(i, path)| {
+            let name = path.file_name().unwrap_or(path.as_os_str()).to_string_lossy();
+            let marker = if app.tab_modified(i) { " [+]" } else { "" };
+            format!("{} {}{}", i + 1, name, marker)
+        });
+
+    Tabs::new(titles)
+        .select(app.arg_index)
+        .style(Style::default().fg(Color::Gray).bg(Color::DarkGray))
+        .highlight_style(Style::default().fg(Color::Black).bg(app.display.highlight_color).add_modifier(Modifier::BOLD))
+        .render(area, buf);
+}
+
 /// Render the entire UI
 pub fn render(app: &mut App, frame: &mut ratatui::Frame) {
     // Split the screen into sidebar and main content
@@ -481,26 +1606,90 @@ pub fn render(app: &mut App, frame: &mut ratatui::Frame) {
         ])
         .split(frame.area());
 
-    // Split main content into input panel and status bar
+    // Reserve a line for the tabs while several files are open, and one for the banner, if there is one
+    let tabs_height = if app.arg_list.len() > 1 { 1 } else { 0 };
+    let banner_height = if app.banner.is_some() { 1 } else { 0 };
+    let main_area = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(tabs_height), Constraint::Length(banner_height), Constraint::Min(1)])
+        .split(chunks[1]);
+
+    if tabs_height > 0 {
+        render_tabs(app, main_area[0], frame.buffer_mut());
+    }
+
+    if let Some(banner) = &app.banner {
+        Paragraph::new(banner.as_str())
+            .style(Style::default().fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD))
+            .render(main_area[1], frame.buffer_mut());
+    }
+
+    // Split main content into input panel, detail panel and status bar
     let main_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Min(1),
+            Constraint::Length(8),
             Constraint::Length(1),
         ])
-        .split(chunks[1]);
+        .split(main_area[2]);
+
+    // Render the sidebar, or the metadata pane in its place while it has focus
+    if app.mode == AppMode::Metadata {
+        render_metadata_pane(app, chunks[0], frame.buffer_mut());
+    } else {
+        render_sidebar(app, chunks[0], frame.buffer_mut());
+    }
 
-    // Render the sidebar
-    render_sidebar(app, chunks[0], frame.buffer_mut());
+    // Render the input panel, with the heatmap strip beside it when enabled
+    if app.config.heatmap {
+        let input_area = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(1), Constraint::Length(6)])
+            .split(main_chunks[0]);
+        render_panes(app, input_area[0], frame.buffer_mut());
+        render_heatmap(app, input_area[1], frame.buffer_mut());
+    } else {
+        render_panes(app, main_chunks[0], frame.buffer_mut());
+    }
 
-    // Render the input panel
-    render_inputs(app, main_chunks[0], frame.buffer_mut());
+    // Render the cursor row detail panel
+    render_detail(app, main_chunks[1], frame.buffer_mut());
 
     // Render the status bar
-    render_status_bar(app, main_chunks[1], frame.buffer_mut());
+    render_status_bar(app, main_chunks[2], frame.buffer_mut());
 
     // Render help dialog if in help mode
     if app.mode == AppMode::Help {
-        render_help(frame.area(), frame.buffer_mut());
+        render_help(app, frame.area(), frame.buffer_mut());
+    }
+
+    if app.mode == AppMode::Attributions {
+        render_attributions(app, frame.area(), frame.buffer_mut());
+    }
+
+    if let Some(kinds) = &app.size_chart {
+        render_size_chart(kinds, frame.area(), frame.buffer_mut());
+    }
+
+    if let Some(finder) = &app.finder {
+        render_finder(finder, frame.area(), frame.buffer_mut());
+    }
+
+    if let Some(browser) = &app.browser {
+        render_browser(browser, frame.area(), frame.buffer_mut());
+    }
+
+    if let Some(list) = app.packet_list.as_mut() {
+        render_packet_list(list, &app.tasd.packets, &app.renderers, frame.area(), frame.buffer_mut());
+    }
+
+    if let Some(popup) = &app.popup {
+        render_popup(popup, frame.area(), frame.buffer_mut());
+    }
+
+    // Render the picker popup on top of everything else
+    if let Some(picker) = &app.picker {
+        render_picker(picker, frame.area(), frame.buffer_mut());
     }
 }
\ No newline at end of file
@@ -0,0 +1,244 @@
+use color_eyre::{eyre::eyre, Result};
+use tasd_lib::{
+    Attribution, AttributionType, Comment, Console, ConsoleType, InputChunk, Packet, PortController, RomName,
+    TotalFrames, TotalRerecords, Transition, TransitionType, TASD,
+};
+use crate::catalog;
+use crate::controller::ControllerType;
+
+/// VisualBoyAdvance movie signature, "VBM" followed by an EOF byte
+const SIGNATURE: &[u8; 4] = b"VBM\x1A";
+/// Offset of the controller data, which follows the fixed-size header
+const HEADER_LEN: usize = 0x100;
+/// Bytes per controller per frame in the movie
+const SAMPLE_LEN: usize = 2;
+/// Sample bit requesting a soft reset on that frame
+const RESET_BIT: u16 = 0x0400;
+/// Sample bits holding buttons, below the reset and motion sensor bits
+const BUTTON_BITS: u16 = 0x03FF;
+/// Transition index type counting frames
+const INDEX_FRAME: u8 = 1;
+/// System flags: GBA, GBC and Super Game Boy
+const SYSTEM_GBA: u8 = 0x01;
+const SYSTEM_GBC: u8 = 0x02;
+const SYSTEM_SGB: u8 = 0x04;
+
+/// PortController code of each handheld's built-in buttons
+fn controller_code(console: Console) -> Option<u16> {
+    match console {
+        Console::Gb => Some(ControllerType::GameBoy.code()),
+        Console::Gbc => Some(ControllerType::GameBoyColor.code()),
+        Console::Gba => Some(ControllerType::GameBoyAdvance.code()),
+        _ => None,
+    }
+}
+
+/// Bytes of TASD input per frame: Game Boy buttons fit in one byte, the GBA adds L and R
+fn frame_size(console: Console) -> usize {
+    if console == Console::Gba { 2 } else { 1 }
+}
+
+/// Read a NUL-padded string field
+fn read_str(data: &[u8], offset: usize, len: usize) -> String {
+    let field = &data[offset..offset + len];
+    let end = field.iter().position(|b| *b == 0).unwrap_or(len);
+    String::from_utf8_lossy(&field[..end]).trim().to_string()
+}
+
+/// Write at most `len` bytes of a string, cutting it at a character boundary if it is too long
+fn write_str(data: &mut [u8], offset: usize, len: usize, text: &str) {
+    let mut end = text.len().min(len);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    data[offset..offset + end].copy_from_slice(&text.as_bytes()[..end]);
+}
+
+/// Convert a VisualBoyAdvance `.vbm` movie into TASD packets
+///
+/// TASD input keeps the movie's button bits (A, B, Select, Start, Right, Left, Up, Down, then R
+/// and L on the GBA) active high, dropping the reset and motion sensor bits.
+pub fn import(data: &[u8]) -> Result<TASD> {
+    if data.len() < HEADER_LEN || &data[..4] != SIGNATURE {
+        return Err(eyre!("Not a VisualBoyAdvance movie (missing VBM signature)"));
+    }
+    let read_u32 = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+
+    let frame_count = read_u32(0x0C) as usize;
+    let rerecords = read_u32(0x10);
+    let start_flags = data[0x14];
+    let controller_flags = data[0x15];
+    let system_flags = data[0x16];
+    let data_offset = read_u32(0x3C) as usize;
+
+    if start_flags & 0x03 != 0 {
+        return Err(eyre!("Movies that start from a savestate or SRAM cannot be converted"));
+    }
+    if system_flags & SYSTEM_SGB != 0 {
+        return Err(eyre!("Super Game Boy movies cannot be converted"));
+    }
+    let console = if system_flags & SYSTEM_GBA != 0 {
+        Console::Gba
+    } else if system_flags & SYSTEM_GBC != 0 {
+        Console::Gbc
+    } else {
+        Console::Gb
+    };
+
+    // Bits 0-3 mark connected controllers
+    let ports: Vec<u8> = (0..4).filter(|i| controller_flags & (1 << i) != 0).map(|i| i as u8 + 1).collect();
+    if ports.is_empty() {
+        return Err(eyre!("VBM movie has no controllers connected"));
+    }
+    if data_offset > data.len() {
+        return Err(eyre!("VBM controller data offset {:#x} is past the end of the file", data_offset));
+    }
+
+    let mut tasd = TASD::new();
+    let packets = &mut tasd.packets;
+    packets.push(Packet::ConsoleType(ConsoleType { console, name: String::new() }));
+
+    let rom_name = read_str(data, 0x24, 12);
+    if !rom_name.is_empty() {
+        packets.push(Packet::RomName(RomName { name: rom_name }));
+    }
+    let author = read_str(data, 0x40, 64);
+    if !author.is_empty() {
+        packets.push(Packet::Attribution(Attribution { attribution_type: AttributionType::Author, name: author }));
+    }
+    let description = read_str(data, 0x80, 128);
+    if !description.is_empty() {
+        packets.push(Packet::Comment(Comment { comment: description }));
+    }
+    packets.push(Packet::TotalRerecords(TotalRerecords { rerecords }));
+
+    let code = controller_code(console).unwrap_or_default();
+    for port in &ports {
+        packets.push(Packet::PortController(PortController { port: *port, controller_type: code }));
+    }
+
+    let stride = SAMPLE_LEN * ports.len();
+    let frames = frame_count.min((data.len() - data_offset) / stride);
+    packets.push(Packet::TotalFrames(TotalFrames { frames: frames as u32 }));
+
+    let size = frame_size(console);
+    let mut inputs = vec![Vec::with_capacity(frames * size); ports.len()];
+    for (frame, chunk) in data[data_offset..data_offset + frames * stride].chunks_exact(stride).enumerate() {
+        let samples: Vec<u16> = chunk.chunks_exact(SAMPLE_LEN).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect();
+
+        if samples.iter().any(|sample| sample & RESET_BIT != 0) {
+            packets.push(Packet::Transition(Transition {
+                index_type: INDEX_FRAME,
+                index: frame as u64,
+                transition_type: TransitionType::SoftReset,
+            }));
+        }
+        for (port, sample) in inputs.iter_mut().zip(samples) {
+            port.extend_from_slice(&(sample & BUTTON_BITS).to_le_bytes()[..size]);
+        }
+    }
+
+    for (port, inputs) in ports.iter().zip(inputs) {
+        packets.push(Packet::InputChunk(InputChunk { port: *port, inputs }));
+    }
+
+    Ok(tasd)
+}
+
+/// Convert a TASD file for a Game Boy, Game Boy Color or GBA into a VisualBoyAdvance `.vbm` movie
+pub fn export(tasd: &TASD) -> Result<Vec<u8>> {
+    let console = tasd.packets.iter()
+        .find_map(|packet| match packet {
+            Packet::ConsoleType(ct) => Some(ct.console),
+            _ => None,
+        })
+        .filter(|console| controller_code(*console).is_some())
+        .ok_or_else(|| eyre!("VBM movies need a Game Boy, Game Boy Color or GBA ConsoleType"))?;
+    let code = controller_code(console).unwrap_or_default();
+
+    let mut ports = Vec::new();
+    let mut rom_name = String::new();
+    let mut authors = Vec::new();
+    let mut description = None;
+    let mut rerecords = 0u32;
+    let mut resets = Vec::new();
+
+    for packet in &tasd.packets {
+        match packet {
+            Packet::PortController(pc) if pc.controller_type == code => ports.push(pc.port),
+            Packet::RomName(rn) => rom_name = rn.name.clone(),
+            Packet::Attribution(at) if at.attribution_type as u8 == 1 => authors.push(at.name.clone()),
+            Packet::Comment(c) if description.is_none() && !c.comment.starts_with("tasd-cli:") => {
+                description = Some(c.comment.clone());
+            }
+            Packet::TotalRerecords(tr) => rerecords = tr.rerecords,
+            Packet::Transition(tr) if tr.index_type == INDEX_FRAME && tr.transition_type as u8 == 1 => {
+                resets.push(tr.index as usize);
+            }
+            _ => {}
+        }
+    }
+
+    ports.sort();
+    if ports.is_empty() {
+        return Err(eyre!("No {} controller declared, nothing to export", catalog::console_name(console as u8)));
+    }
+    if let Some(port) = ports.iter().find(|port| !(1..=4).contains(*port)) {
+        return Err(eyre!("VBM movies only have controllers 1-4, found port {}", port));
+    }
+
+    let size = frame_size(console);
+    let inputs: Vec<Vec<u8>> = ports.iter()
+        .map(|port| {
+            tasd.packets.iter()
+                .filter_map(|packet| match packet {
+                    Packet::InputChunk(chunk) if chunk.port == *port => Some(chunk.inputs.as_slice()),
+                    _ => None,
+                })
+                .flatten()
+                .copied()
+                .collect()
+        })
+        .collect();
+    let frames = inputs.iter().map(|port| port.len() / size).max().unwrap_or(0);
+
+    let controller_flags = ports.iter().fold(0u8, |bits, port| bits | 1 << (port - 1));
+    let system_flags = match console {
+        Console::Gba => SYSTEM_GBA,
+        Console::Gbc => SYSTEM_GBC,
+        _ => 0,
+    };
+    let uid = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as u32)
+        .unwrap_or(0);
+
+    let mut data = vec![0u8; HEADER_LEN];
+    data[..4].copy_from_slice(SIGNATURE);
+    data[0x04..0x08].copy_from_slice(&1u32.to_le_bytes());
+    data[0x08..0x0C].copy_from_slice(&uid.to_le_bytes());
+    data[0x0C..0x10].copy_from_slice(&(frames as u32).to_le_bytes());
+    data[0x10..0x14].copy_from_slice(&rerecords.to_le_bytes());
+    data[0x15] = controller_flags;
+    data[0x16] = system_flags;
+    write_str(&mut data, 0x24, 12, &rom_name);
+    data[0x3C..0x40].copy_from_slice(&(HEADER_LEN as u32).to_le_bytes());
+    write_str(&mut data, 0x40, 63, &authors.join(", "));
+    write_str(&mut data, 0x80, 127, description.as_deref().unwrap_or_default());
+
+    for frame in 0..frames {
+        for port in &inputs {
+            let mut bytes = [0u8; SAMPLE_LEN];
+            if let Some(input) = port.get(frame * size..(frame + 1) * size) {
+                bytes[..size].copy_from_slice(input);
+            }
+            let mut sample = u16::from_le_bytes(bytes);
+            if resets.contains(&frame) {
+                sample |= RESET_BIT;
+            }
+            data.extend_from_slice(&sample.to_le_bytes());
+        }
+    }
+
+    Ok(data)
+}
@@ -0,0 +1,150 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::Instant;
+use color_eyre::{eyre::eyre, Result};
+use crate::input_index::InputIndex;
+use crate::repair::Salvage;
+
+/// Spinner frames shown in the status bar while a task runs
+const SPINNER: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// What a background task hands back to the UI thread
+pub enum Done {
+    /// A movie was written; `generation` is the edit history state it holds, so a save of an open file can clear its modified flag
+    Saved { path: PathBuf, generation: u64, size: usize },
+    /// A file was read and indexed, ready to be shown
+    Loaded(Box<Loaded>),
+    /// Anything else, shown in the status bar
+    Message(String),
+}
+
+/// A file opened on the worker, since reading and indexing a large one takes a while
+pub struct Loaded {
+    pub path: PathBuf,
+    /// Drop unsaved changes if it is the shown file, like `:e!`
+    pub force: bool,
+    /// History generation of the shown file when loading started, to notice edits made meanwhile
+    pub generation: u64,
+    pub salvage: Salvage,
+    pub input_index: InputIndex,
+}
+
+/// A task running on its own thread
+struct Task {
+    label: String,
+    /// File the task reads or writes, so two tasks never work on the same one at once
+    target: PathBuf,
+    started: Instant,
+    receiver: Receiver<Result<Done>>,
+}
+
+/// Runs slow work (saving, converting) off the UI thread, so the TUI keeps drawing and reading keys
+#[derive(Default)]
+pub struct Worker {
+    tasks: Vec<Task>,
+}
+
+impl Worker {
+    /// Start a task that reads or writes `target`; its result is picked up by `poll`
+    pub fn spawn(&mut self, label: String, target: PathBuf, job: impl FnOnce() -> Result<Done> + Send + 'static) {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            // The receiver only goes away when the app has stopped listening
+            let _ = sender.send(job());
+        });
+        self.tasks.push(Task { label, target, started: Instant::now(), receiver });
+    }
+
+    pub fn busy(&self) -> bool {
+        !self.tasks.is_empty()
+    }
+
+    /// Whether a running task reads or writes the file at `path`
+    pub fn writing(&self, path: &Path) -> bool {
+        self.tasks.iter().any(|task| same_file(&task.target, path))
+    }
+
+    /// Results of the tasks that have finished, with their labels
+    pub fn poll(&mut self) -> Vec<(String, Result<Done>)> {
+        let mut finished = Vec::new();
+        self.tasks.retain(|task| match task.receiver.try_recv() {
+            Ok(result) => {
+                finished.push((task.label.clone(), result));
+                false
+            }
+            Err(TryRecvError::Empty) => true,
+            Err(TryRecvError::Disconnected) => {
+                finished.push((task.label.clone(), Err(eyre!("the task crashed"))));
+                false
+            }
+        });
+        finished
+    }
+
+    /// Block until every task has finished, e.g. so quitting doesn't cut a save short
+    pub fn wait(&mut self) -> Vec<(String, Result<Done>)> {
+        self.tasks.drain(..)
+            .map(|task| {
+                let result = task.receiver.recv().unwrap_or_else(|_| Err(eyre!("the task crashed")));
+                (task.label, result)
+            })
+            .collect()
+    }
+
+    /// Block until the tasks writing `path` have finished, e.g. before writing it on the UI thread
+    pub fn wait_for(&mut self, path: &Path) -> Vec<(String, Result<Done>)> {
+        let (writing, others) = std::mem::take(&mut self.tasks).into_iter()
+            .partition(|task| same_file(&task.target, path));
+        self.tasks = others;
+        writing.into_iter()
+            .map(|task: Task| {
+                let result = task.receiver.recv().unwrap_or_else(|_| Err(eyre!("the task crashed")));
+                (task.label, result)
+            })
+            .collect()
+    }
+
+    /// Status bar text for the running tasks, with a spinner and the time taken so far
+    pub fn status(&self) -> Option<String> {
+        let task = self.tasks.first()?;
+        let elapsed = task.started.elapsed();
+        let frame = SPINNER[(elapsed.as_millis() / 100) as usize % SPINNER.len()];
+        let more = match self.tasks.len() {
+            1 => String::new(),
+            count => format!(" (+{} more)", count - 1),
+        };
+        Some(format!("{} {}... {}s{}", frame, task.label, elapsed.as_secs(), more))
+    }
+}
+
+/// Whether two paths name the same file, even when spelled differently (`movie.tasd`, `./movie.tasd`)
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Write a file through a temporary file beside it, renamed over it once complete, so a failed
+/// or interrupted write leaves the old file intact rather than a truncated one
+pub fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    // Replace the file a symlink points at, not the link
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let name = path.file_name().ok_or_else(|| eyre!("Not a file path: {}", path.display()))?;
+    let temp = path.with_file_name(format!(".{}.{}.tmp", name.to_string_lossy(), std::process::id()));
+
+    let result = (|| {
+        let mut file = std::fs::File::create(&temp)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+        if let Ok(metadata) = std::fs::metadata(&path) {
+            std::fs::set_permissions(&temp, metadata.permissions())?;
+        }
+        std::fs::rename(&temp, &path)
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    Ok(result?)
+}